@@ -0,0 +1,150 @@
+//! Customer and staff authentication: password hashing, JWTs and the
+//! extractors handlers use to find out who is calling
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::db;
+use crate::handlers::{checkout::ErrorResponse, database_error};
+use crate::models::{Permission, StaffRole};
+use crate::AppState;
+
+mod password;
+mod tokens;
+
+pub use password::{check_password, hash_password, verify_password};
+pub use tokens::{
+    Claims, TokenError, TokenKeys, TokenType, ACCESS_TOKEN_TTL, REFRESH_TOKEN_TTL, STAFF_TOKEN_TTL,
+};
+
+/// The customer a valid access token was issued to
+///
+/// Handlers taking this extractor reject requests without an
+/// `Authorization: Bearer <access token>` header with 401.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthCustomer {
+    pub id: Uuid,
+}
+
+/// The calling customer, or `None` for anonymous requests
+///
+/// A token that is present but invalid is still rejected, so an expired
+/// session is never silently treated as anonymous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptionalCustomer(pub Option<AuthCustomer>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthCustomer {
+    type Rejection = (StatusCode, Json<ErrorResponse>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let OptionalCustomer(customer) = OptionalCustomer::from_request_parts(parts, state).await?;
+        customer.ok_or_else(|| unauthorized("A bearer access token is required"))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for OptionalCustomer {
+    type Rejection = (StatusCode, Json<ErrorResponse>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(header) = parts.headers.get(AUTHORIZATION) else {
+            return Ok(OptionalCustomer(None));
+        };
+
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| unauthorized("Authorization header must be a bearer token"))?;
+
+        let claims = state
+            .tokens
+            .decode(token, TokenType::Access)
+            .map_err(|_| unauthorized("Access token is invalid or expired"))?;
+
+        Ok(OptionalCustomer(Some(AuthCustomer { id: claims.sub })))
+    }
+}
+
+/// A signed-in staff member, with their current role
+///
+/// The role is read from the database on every request, so role changes and
+/// removed accounts take effect before the token expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthStaff {
+    pub id: Uuid,
+    pub role: StaffRole,
+}
+
+impl AuthStaff {
+    /// Refuse with 403 unless the staff member's role allows `permission`
+    pub fn require(&self, permission: Permission) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+        if self.role.allows(permission) {
+            return Ok(());
+        }
+
+        tracing::warn!("Staff {} ({}) may not {:?}", self.id, self.role, permission);
+        Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "forbidden".to_string(),
+                message: format!("The {} role does not allow this", self.role),
+            }),
+        ))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthStaff {
+    type Rejection = (StatusCode, Json<ErrorResponse>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| unauthorized("A bearer staff token is required"))?;
+
+        let claims = state
+            .tokens
+            .decode(token, TokenType::Staff)
+            .map_err(|_| unauthorized("Staff token is invalid or expired"))?;
+
+        let staff = db::staff::get_staff(&state.db, claims.sub)
+            .await
+            .map_err(database_error)?
+            .ok_or_else(|| unauthorized("Staff account no longer exists"))?;
+
+        Ok(AuthStaff {
+            id: staff.id,
+            role: staff.role,
+        })
+    }
+}
+
+/// A 401 response with the `unauthorized` error code
+pub(crate) fn unauthorized(message: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ErrorResponse {
+            error: "unauthorized".to_string(),
+            message: message.to_string(),
+        }),
+    )
+}
@@ -0,0 +1,74 @@
+use std::sync::OnceLock;
+
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use uuid::Uuid;
+
+/// Hash a password with Argon2id and a random salt, in PHC string format
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    // A v4 UUID is 16 random bytes from the OS, the salt length Argon2 recommends
+    let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes())?;
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Check `password` against a stored hash; malformed hashes never match
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Check a sign-in attempt against the stored hash, `None` when no account
+/// has the email, on a blocking thread since Argon2 is deliberately slow
+///
+/// A missing account costs as much time as a wrong password, so response
+/// times do not reveal which emails are registered.
+pub async fn check_password(password: String, hash: Option<String>) -> bool {
+    static DUMMY: OnceLock<String> = OnceLock::new();
+
+    tokio::task::spawn_blocking(move || match hash {
+        Some(hash) => verify_password(&password, &hash),
+        None => {
+            let dummy =
+                DUMMY.get_or_init(|| hash_password("not a real password").unwrap_or_default());
+            verify_password(&password, dummy);
+            false
+        }
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_verifies_only_the_same_password() {
+        let hash = hash_password("correct horse battery").unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse battery", &hash));
+        assert!(!verify_password("correct horse battery!", &hash));
+        assert!(!verify_password("correct horse battery", "not-a-hash"));
+
+        // Salted: the same password hashes differently every time
+        assert_ne!(hash, hash_password("correct horse battery").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_check_password_without_account_fails() {
+        let hash = hash_password("correct horse battery").unwrap();
+
+        assert!(check_password("correct horse battery".to_string(), Some(hash)).await);
+        assert!(!check_password("correct horse battery".to_string(), None).await);
+    }
+}
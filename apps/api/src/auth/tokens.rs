@@ -0,0 +1,179 @@
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How long an access token is accepted
+pub const ACCESS_TOKEN_TTL: Duration = Duration::minutes(15);
+/// How long a refresh token can be exchanged for new tokens
+pub const REFRESH_TOKEN_TTL: Duration = Duration::days(30);
+/// How long a staff token is accepted: one working shift
+pub const STAFF_TOKEN_TTL: Duration = Duration::hours(8);
+
+/// What a token may be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenType {
+    /// Sent as `Authorization: Bearer` on API requests
+    Access,
+    /// Only exchanged at `/api/auth/refresh`
+    Refresh,
+    /// Sent as `Authorization: Bearer` on `/api/admin` requests
+    Staff,
+}
+
+/// JWT claims of both token types
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claims {
+    /// Customer ID, or staff ID on staff tokens
+    pub sub: Uuid,
+    pub typ: TokenType,
+    pub iat: i64,
+    pub exp: i64,
+    /// Refresh token ID in `refresh_tokens`; absent on access tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<Uuid>,
+}
+
+/// Why a token was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    /// Bad signature, malformed or expired
+    Invalid,
+    /// A valid token of the other type
+    WrongType,
+}
+
+/// HS256 keys tokens are signed and checked with
+pub struct TokenKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+impl TokenKeys {
+    pub fn new(secret: &[u8]) -> Self {
+        TokenKeys {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// A short-lived access token for `customer_id`
+    pub fn access_token(&self, customer_id: Uuid, now: DateTime<Utc>) -> String {
+        self.encode(&Claims {
+            sub: customer_id,
+            typ: TokenType::Access,
+            iat: now.timestamp(),
+            exp: (now + ACCESS_TOKEN_TTL).timestamp(),
+            jti: None,
+        })
+    }
+
+    /// A refresh token for the `refresh_tokens` row `id`
+    pub fn refresh_token(&self, customer_id: Uuid, id: Uuid, expires_at: DateTime<Utc>) -> String {
+        self.encode(&Claims {
+            sub: customer_id,
+            typ: TokenType::Refresh,
+            iat: Utc::now().timestamp(),
+            exp: expires_at.timestamp(),
+            jti: Some(id),
+        })
+    }
+
+    /// An admin API token for `staff_id`
+    pub fn staff_token(&self, staff_id: Uuid, now: DateTime<Utc>) -> String {
+        self.encode(&Claims {
+            sub: staff_id,
+            typ: TokenType::Staff,
+            iat: now.timestamp(),
+            exp: (now + STAFF_TOKEN_TTL).timestamp(),
+            jti: None,
+        })
+    }
+
+    /// Check the signature, expiry and type of `token`
+    pub fn decode(&self, token: &str, typ: TokenType) -> Result<Claims, TokenError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.decoding, &validation)
+            .map_err(|_| TokenError::Invalid)?
+            .claims;
+
+        if claims.typ != typ {
+            return Err(TokenError::WrongType);
+        }
+        Ok(claims)
+    }
+
+    fn encode(&self, claims: &Claims) -> String {
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), claims, &self.encoding)
+            .expect("HS256 signing cannot fail")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> TokenKeys {
+        TokenKeys::new(b"test secret that is long enough!")
+    }
+
+    #[test]
+    fn test_tokens_round_trip_by_type() {
+        let customer = Uuid::new_v4();
+        let jti = Uuid::new_v4();
+
+        let access = keys().access_token(customer, Utc::now());
+        let claims = keys().decode(&access, TokenType::Access).unwrap();
+        assert_eq!((claims.sub, claims.jti), (customer, None));
+
+        let refresh = keys().refresh_token(customer, jti, Utc::now() + REFRESH_TOKEN_TTL);
+        let claims = keys().decode(&refresh, TokenType::Refresh).unwrap();
+        assert_eq!((claims.sub, claims.jti), (customer, Some(jti)));
+
+        // No token can stand in for another
+        let staff = keys().staff_token(customer, Utc::now());
+        assert!(keys().decode(&staff, TokenType::Staff).is_ok());
+        assert_eq!(
+            keys().decode(&access, TokenType::Staff),
+            Err(TokenError::WrongType)
+        );
+        assert_eq!(
+            keys().decode(&staff, TokenType::Access),
+            Err(TokenError::WrongType)
+        );
+        assert_eq!(
+            keys().decode(&access, TokenType::Refresh),
+            Err(TokenError::WrongType)
+        );
+        assert_eq!(
+            keys().decode(&refresh, TokenType::Access),
+            Err(TokenError::WrongType)
+        );
+    }
+
+    #[test]
+    fn test_expired_and_forged_tokens_rejected() {
+        let customer = Uuid::new_v4();
+
+        let expired = keys().access_token(customer, Utc::now() - Duration::hours(1));
+        assert_eq!(
+            keys().decode(&expired, TokenType::Access),
+            Err(TokenError::Invalid)
+        );
+
+        let forged =
+            TokenKeys::new(b"some other secret, also 32 bytes").access_token(customer, Utc::now());
+        assert_eq!(
+            keys().decode(&forged, TokenType::Access),
+            Err(TokenError::Invalid)
+        );
+
+        assert_eq!(
+            keys().decode("not.a.token", TokenType::Access),
+            Err(TokenError::Invalid)
+        );
+    }
+}
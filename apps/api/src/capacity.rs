@@ -0,0 +1,73 @@
+use chrono::{Duration, NaiveDate, Utc};
+use quote_core::{PriceBook, Quote, ShopLoad};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::db;
+use crate::models::Order;
+use crate::AppState;
+
+/// Oven hours already booked from `today` on
+pub async fn shop_load(pool: &PgPool, today: NaiveDate) -> Result<ShopLoad, sqlx::Error> {
+    Ok(ShopLoad {
+        booked_hours: db::oven_bookings::booked_hours(pool, today).await?,
+    })
+}
+
+/// Date a job finishes when it takes `lead_days` from `today`
+pub fn completion_date(today: NaiveDate, lead_days: u32) -> NaiveDate {
+    today + Duration::days(lead_days.into())
+}
+
+/// Reserve the oven time a paid order needs in the first free slots
+///
+/// Books without a `[capacity]` section do not track oven time, so nothing
+/// is booked for them.
+pub async fn book_oven_time(
+    tx: &mut Transaction<'_, Postgres>,
+    pool: &PgPool,
+    order_id: Uuid,
+    quote: &Quote,
+    book: &PriceBook,
+    today: NaiveDate,
+) -> Result<(), sqlx::Error> {
+    let Some(capacity) = &book.capacity else {
+        return Ok(());
+    };
+
+    let load = shop_load(pool, today).await?;
+    let slots: Vec<(NaiveDate, f64)> = capacity
+        .schedule(&load, capacity.oven_hours(quote))
+        .into_iter()
+        .map(|(day, hours)| (today + Duration::days(day.into()), hours))
+        .collect();
+
+    db::oven_bookings::insert_bookings(tx, order_id, &slots).await?;
+    tracing::info!("Booked {} oven days for order {}", slots.len(), order_id);
+
+    Ok(())
+}
+
+/// Reserve oven time for a newly paid order so later quotes see the load,
+/// with the book in effect today
+pub async fn book_paid_order(
+    state: &AppState,
+    tx: &mut Transaction<'_, Postgres>,
+    order: &Order,
+) -> Result<(), sqlx::Error> {
+    let Some(quote) = db::quotes::get_quote(&state.db, order.quote_id).await? else {
+        tracing::warn!("Order {} has no quote {}", order.id, order.quote_id);
+        return Ok(());
+    };
+
+    let today = Utc::now().date_naive();
+    let Some(book) = state.price_books.effective_at(&today.to_string()) else {
+        tracing::error!(
+            "No price book is effective on {}, oven time not booked",
+            today
+        );
+        return Ok(());
+    };
+
+    book_oven_time(tx, &state.db, order.id, &quote.input, book, today).await
+}
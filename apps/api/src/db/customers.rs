@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::models::Account;
+
+/// An account with the password hash it signs in with
+#[derive(FromRow)]
+pub struct Credentials {
+    #[sqlx(flatten)]
+    pub account: Account,
+    pub password_hash: String,
+}
+
+/// Create an account; fails with a unique violation when the email is taken
+pub async fn insert_customer(
+    pool: &PgPool,
+    email: &str,
+    password_hash: &str,
+    name: Option<&str>,
+) -> Result<Account, sqlx::Error> {
+    sqlx::query_as::<_, Account>(
+        "INSERT INTO customers (id, email, password_hash, name)
+         VALUES ($1, $2, $3, $4)
+         RETURNING id, email, name, created_at",
+    )
+    .bind(Uuid::new_v4())
+    .bind(email)
+    .bind(password_hash)
+    .bind(name)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_customer(pool: &PgPool, id: Uuid) -> Result<Option<Account>, sqlx::Error> {
+    sqlx::query_as::<_, Account>("SELECT id, email, name, created_at FROM customers WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn get_credentials(
+    pool: &PgPool,
+    email: &str,
+) -> Result<Option<Credentials>, sqlx::Error> {
+    sqlx::query_as::<_, Credentials>(
+        "SELECT id, email, name, created_at, password_hash FROM customers WHERE email = $1",
+    )
+    .bind(email)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record an issued refresh token
+pub async fn insert_refresh_token(
+    pool: &PgPool,
+    id: Uuid,
+    customer_id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO refresh_tokens (id, customer_id, expires_at) VALUES ($1, $2, $3)")
+        .bind(id)
+        .bind(customer_id)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Revoke a live refresh token, returning whether it was live
+///
+/// Revoking and checking happen in one statement, so a token can only ever
+/// be exchanged once even when two requests race with it.
+pub async fn revoke_refresh_token(
+    pool: &PgPool,
+    id: Uuid,
+    customer_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = now()
+         WHERE id = $1 AND customer_id = $2 AND revoked_at IS NULL AND expires_at > now()",
+    )
+    .bind(id)
+    .bind(customer_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
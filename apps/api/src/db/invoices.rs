@@ -0,0 +1,125 @@
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::models::{BankDetails, Invoice};
+
+/// An invoice issued for an order at checkout
+pub struct NewInvoice<'a> {
+    pub order_id: Uuid,
+    pub payment_id: Uuid,
+    pub number: &'a str,
+    pub amount: i64,
+    pub currency: &'a str,
+    pub customer_email: &'a str,
+    pub bank: &'a BankDetails,
+    pub issued_on: NaiveDate,
+    pub due_on: NaiveDate,
+}
+
+const INVOICE_COLUMNS: &str =
+    "id, order_id, payment_id, number, amount, currency, customer_email, account_holder, iban, \
+     bic, issued_on, due_on, paid_on, received_by, created_at";
+
+/// Take the next invoice number, e.g. `INV-2026-000042` for an invoice
+/// issued in 2026
+pub async fn next_invoice_number(
+    tx: &mut Transaction<'_, Postgres>,
+    issued_on: NaiveDate,
+) -> Result<String, sqlx::Error> {
+    let seq: i64 = sqlx::query_scalar("SELECT nextval('invoice_number_seq')")
+        .fetch_one(&mut **tx)
+        .await?;
+
+    Ok(format!("INV-{}-{:06}", issued_on.format("%Y"), seq))
+}
+
+pub async fn insert_invoice(
+    tx: &mut Transaction<'_, Postgres>,
+    invoice: &NewInvoice<'_>,
+) -> Result<Invoice, sqlx::Error> {
+    sqlx::query_as::<_, Invoice>(&format!(
+        "INSERT INTO invoices
+            (id, order_id, payment_id, number, amount, currency, customer_email, account_holder,
+             iban, bic, issued_on, due_on)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+         RETURNING {}",
+        INVOICE_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(invoice.order_id)
+    .bind(invoice.payment_id)
+    .bind(invoice.number)
+    .bind(invoice.amount)
+    .bind(invoice.currency)
+    .bind(invoice.customer_email)
+    .bind(&invoice.bank.account_holder)
+    .bind(&invoice.bank.iban)
+    .bind(&invoice.bank.bic)
+    .bind(invoice.issued_on)
+    .bind(invoice.due_on)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+pub async fn get_invoice_for_order(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<Option<Invoice>, sqlx::Error> {
+    sqlx::query_as::<_, Invoice>(&format!(
+        "SELECT {} FROM invoices WHERE order_id = $1",
+        INVOICE_COLUMNS
+    ))
+    .bind(order_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// An invoice, locked until the transaction ends so a transfer is only
+/// recorded once
+pub async fn lock_invoice(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+) -> Result<Option<Invoice>, sqlx::Error> {
+    sqlx::query_as::<_, Invoice>(&format!(
+        "SELECT {} FROM invoices WHERE id = $1 FOR UPDATE",
+        INVOICE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+pub async fn mark_paid(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    paid_on: NaiveDate,
+    received_by: Uuid,
+) -> Result<Invoice, sqlx::Error> {
+    sqlx::query_as::<_, Invoice>(&format!(
+        "UPDATE invoices SET paid_on = $2, received_by = $3 WHERE id = $1 RETURNING {}",
+        INVOICE_COLUMNS
+    ))
+    .bind(id)
+    .bind(paid_on)
+    .bind(received_by)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Unpaid invoices due before `today` whose orders still await payment,
+/// longest overdue first
+pub async fn list_overdue(pool: &PgPool, today: NaiveDate) -> Result<Vec<Invoice>, sqlx::Error> {
+    sqlx::query_as::<_, Invoice>(&format!(
+        "SELECT {} FROM invoices i
+         WHERE paid_on IS NULL AND due_on < $1
+           AND EXISTS (
+               SELECT 1 FROM orders o WHERE o.id = i.order_id AND o.status = 'awaiting_payment'
+           )
+         ORDER BY due_on, number",
+        INVOICE_COLUMNS
+    ))
+    .bind(today)
+    .fetch_all(pool)
+    .await
+}
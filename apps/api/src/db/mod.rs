@@ -1 +1,32 @@
-// Database module - to be implemented
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, PgPool};
+
+pub mod customers;
+pub mod invoices;
+pub mod orders;
+pub mod oven_bookings;
+pub mod payments;
+pub mod quotes;
+pub mod refunds;
+pub mod staff;
+pub mod webhook_events;
+
+/// Migrations from `db/migrations`, embedded at compile time
+pub static MIGRATOR: Migrator = sqlx::migrate!("../../db/migrations");
+
+/// Connect to PostgreSQL and bring the schema up to date
+pub async fn connect(database_url: &str) -> Result<PgPool, sqlx::Error> {
+    let pool = PgPoolOptions::new()
+        .max_connections(10)
+        .connect(database_url)
+        .await?;
+
+    MIGRATOR.run(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Whether `e` is a unique constraint violation
+pub fn is_unique_violation(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|e| e.kind() == sqlx::error::ErrorKind::UniqueViolation)
+}
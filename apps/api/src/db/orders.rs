@@ -0,0 +1,249 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::models::{Order, OrderItem, OrderStatus, OrderStatusChange};
+
+/// Filters for the admin order search
+#[derive(Debug, Default)]
+pub struct OrderFilter<'a> {
+    pub status: Option<OrderStatus>,
+    /// Matches part of the customer email, or a whole order, quote or session ID
+    pub query: Option<&'a str>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Order fields known when checkout starts
+pub struct NewOrder<'a> {
+    pub id: Uuid,
+    pub quote_id: Uuid,
+    pub status: OrderStatus,
+    pub customer_email: Option<&'a str>,
+    pub customer_id: Option<Uuid>,
+    pub total_amount: i64,
+    pub deposit_amount: Option<i64>,
+    pub currency: &'a str,
+    /// Absent for orders paid by invoice
+    pub stripe_checkout_session_id: Option<&'a str>,
+}
+
+/// A priced line belonging to an order
+pub struct NewOrderItem<'a> {
+    pub name: &'a str,
+    pub description: Option<&'a str>,
+    pub quantity: i32,
+    pub unit_amount: i64,
+}
+
+const ORDER_COLUMNS: &str = "id, quote_id, status, customer_email, customer_id, total_amount, \
+     deposit_amount, currency, stripe_checkout_session_id, created_at, updated_at";
+
+pub async fn insert_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order: &NewOrder<'_>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO orders
+            (id, quote_id, status, customer_email, customer_id, total_amount, deposit_amount,
+             currency, stripe_checkout_session_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    )
+    .bind(order.id)
+    .bind(order.quote_id)
+    .bind(order.status)
+    .bind(order.customer_email)
+    .bind(order.customer_id)
+    .bind(order.total_amount)
+    .bind(order.deposit_amount)
+    .bind(order.currency)
+    .bind(order.stripe_checkout_session_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_order_item(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+    item: &NewOrderItem<'_>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO order_items (id, order_id, name, description, quantity, unit_amount)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_id)
+    .bind(item.name)
+    .bind(item.description)
+    .bind(item.quantity)
+    .bind(item.unit_amount)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_order(pool: &PgPool, id: Uuid) -> Result<Option<Order>, sqlx::Error> {
+    sqlx::query_as::<_, Order>(&format!(
+        "SELECT {} FROM orders WHERE id = $1",
+        ORDER_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// A customer's orders, newest first
+pub async fn list_orders_for_customer(
+    pool: &PgPool,
+    customer_id: Uuid,
+) -> Result<Vec<Order>, sqlx::Error> {
+    sqlx::query_as::<_, Order>(&format!(
+        "SELECT {} FROM orders WHERE customer_id = $1 ORDER BY created_at DESC",
+        ORDER_COLUMNS
+    ))
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_order_by_checkout_session(
+    pool: &PgPool,
+    session_id: &str,
+) -> Result<Option<Order>, sqlx::Error> {
+    sqlx::query_as::<_, Order>(&format!(
+        "SELECT {} FROM orders WHERE stripe_checkout_session_id = $1",
+        ORDER_COLUMNS
+    ))
+    .bind(session_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// An order, locked until the transaction ends
+pub async fn lock_order(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+) -> Result<Option<Order>, sqlx::Error> {
+    sqlx::query_as::<_, Order>(&format!(
+        "SELECT {} FROM orders WHERE id = $1 FOR UPDATE",
+        ORDER_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Move an order from `from` to `to`
+///
+/// Returns `false` when the order is no longer in `from`, so a concurrent
+/// update is never silently overwritten.
+pub async fn update_order_status(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    from: OrderStatus,
+    to: OrderStatus,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE orders SET status = $3, updated_at = now() WHERE id = $1 AND status = $2",
+    )
+    .bind(id)
+    .bind(from)
+    .bind(to)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Orders matching `filter`, newest first
+pub async fn search_orders(
+    pool: &PgPool,
+    filter: &OrderFilter<'_>,
+) -> Result<Vec<Order>, sqlx::Error> {
+    sqlx::query_as::<_, Order>(&format!(
+        "SELECT {} FROM orders
+         WHERE ($1::text IS NULL OR status = $1)
+           AND ($2::text IS NULL
+                OR strpos(lower(customer_email), lower($2)) > 0
+                OR id::text = lower($2)
+                OR quote_id::text = lower($2)
+                OR stripe_checkout_session_id = $2)
+         ORDER BY created_at DESC
+         LIMIT $3 OFFSET $4",
+        ORDER_COLUMNS
+    ))
+    .bind(filter.status)
+    .bind(filter.query)
+    .bind(filter.limit)
+    .bind(filter.offset)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn list_order_items(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<Vec<OrderItem>, sqlx::Error> {
+    sqlx::query_as::<_, OrderItem>(
+        "SELECT name, description, quantity, unit_amount FROM order_items
+         WHERE order_id = $1 ORDER BY created_at, id",
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a status change made by `staff_id`, or by a webhook when `None`
+pub async fn insert_status_change(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+    from: OrderStatus,
+    to: OrderStatus,
+    staff_id: Option<Uuid>,
+    note: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO order_status_changes (id, order_id, from_status, to_status, staff_id, note)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_id)
+    .bind(from)
+    .bind(to)
+    .bind(staff_id)
+    .bind(note)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// An order's status history, oldest first
+pub async fn list_status_changes(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<Vec<OrderStatusChange>, sqlx::Error> {
+    sqlx::query_as::<_, OrderStatusChange>(
+        "SELECT from_status, to_status, staff_id, note, created_at FROM order_status_changes
+         WHERE order_id = $1 ORDER BY created_at",
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Whether any order for the quote has been paid
+pub async fn quote_has_paid_order(pool: &PgPool, quote_id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (
+            SELECT 1 FROM orders
+            WHERE quote_id = $1
+              AND status NOT IN ('quoted', 'awaiting_payment', 'payment_failed', 'cancelled', 'expired')
+         )",
+    )
+    .bind(quote_id)
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,62 @@
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// Booked oven hours per day from `from` on, one entry per day up to the
+/// last booked day
+pub async fn booked_hours(pool: &PgPool, from: NaiveDate) -> Result<Vec<f64>, sqlx::Error> {
+    let rows: Vec<(NaiveDate, f64)> = sqlx::query_as(
+        "SELECT day, SUM(hours) FROM oven_bookings WHERE day >= $1 GROUP BY day ORDER BY day",
+    )
+    .bind(from)
+    .fetch_all(pool)
+    .await?;
+
+    let mut hours = Vec::new();
+    for (day, booked) in rows {
+        let offset = (day - from).num_days() as usize;
+        if hours.len() <= offset {
+            hours.resize(offset + 1, 0.0);
+        }
+        hours[offset] = booked;
+    }
+
+    Ok(hours)
+}
+
+/// Reserve oven time for an order
+///
+/// An order is only ever booked once; bookings it already has are kept.
+pub async fn insert_bookings(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+    slots: &[(NaiveDate, f64)],
+) -> Result<(), sqlx::Error> {
+    for (day, hours) in slots {
+        sqlx::query(
+            "INSERT INTO oven_bookings (id, order_id, day, hours) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (order_id, day) DO NOTHING",
+        )
+        .bind(Uuid::new_v4())
+        .bind(order_id)
+        .bind(day)
+        .bind(hours)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Give up an order's oven time, e.g. when it is cancelled
+pub async fn release_bookings(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM oven_bookings WHERE order_id = $1")
+        .bind(order_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::models::{Payment, PaymentKind};
+
+/// A payment attempt recorded against an order
+pub struct NewPayment<'a> {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub kind: PaymentKind,
+    pub provider: &'a str,
+    pub provider_reference: &'a str,
+    pub checkout_url: Option<&'a str>,
+    pub amount: i64,
+    pub currency: &'a str,
+    pub status: &'a str,
+}
+
+const PAYMENT_COLUMNS: &str =
+    "id, order_id, kind, provider, provider_reference, checkout_url, payment_intent_id, amount, \
+     captured_amount, refunded_amount, currency, status, created_at, updated_at";
+
+pub async fn insert_payment(
+    tx: &mut Transaction<'_, Postgres>,
+    payment: &NewPayment<'_>,
+) -> Result<Payment, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "INSERT INTO payments
+            (id, order_id, kind, provider, provider_reference, checkout_url, amount, currency,
+             status)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING {}",
+        PAYMENT_COLUMNS
+    ))
+    .bind(payment.id)
+    .bind(payment.order_id)
+    .bind(payment.kind)
+    .bind(payment.provider)
+    .bind(payment.provider_reference)
+    .bind(payment.checkout_url)
+    .bind(payment.amount)
+    .bind(payment.currency)
+    .bind(payment.status)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+pub async fn update_payment_status(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    status: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE payments SET status = $2, updated_at = now() WHERE id = $1")
+        .bind(id)
+        .bind(status)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_payment(pool: &PgPool, id: Uuid) -> Result<Option<Payment>, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "SELECT {} FROM payments WHERE id = $1",
+        PAYMENT_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// The payment made through a checkout session
+pub async fn get_payment_by_reference(
+    pool: &PgPool,
+    provider: &str,
+    provider_reference: &str,
+) -> Result<Option<Payment>, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "SELECT {} FROM payments WHERE provider = $1 AND provider_reference = $2",
+        PAYMENT_COLUMNS
+    ))
+    .bind(provider)
+    .bind(provider_reference)
+    .fetch_optional(pool)
+    .await
+}
+
+/// The payment taken when the order was checked out, in full or as a deposit
+pub async fn get_checkout_payment(
+    pool: &PgPool,
+    order_id: Uuid,
+    provider: &str,
+) -> Result<Option<Payment>, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "SELECT {} FROM payments
+         WHERE order_id = $1 AND provider = $2 AND kind <> 'balance'
+         ORDER BY created_at
+         LIMIT 1",
+        PAYMENT_COLUMNS
+    ))
+    .bind(order_id)
+    .bind(provider)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record what a paid checkout session captured, and the payment ID refunds
+/// are issued against; without an amount the whole charge was captured
+pub async fn record_capture(
+    pool: &PgPool,
+    provider: &str,
+    provider_reference: &str,
+    payment_intent_id: Option<&str>,
+    captured_amount: Option<i64>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE payments
+         SET payment_intent_id = COALESCE($3, payment_intent_id),
+             captured_amount = COALESCE($4, amount),
+             updated_at = now()
+         WHERE provider = $1 AND provider_reference = $2",
+    )
+    .bind(provider)
+    .bind(provider_reference)
+    .bind(payment_intent_id)
+    .bind(captured_amount)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record what a payment intent captured, for payments that settle after
+/// their checkout session completed
+pub async fn record_intent_capture(
+    pool: &PgPool,
+    id: Uuid,
+    payment_intent_id: &str,
+    captured_amount: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE payments
+         SET payment_intent_id = $2, captured_amount = $3, updated_at = now()
+         WHERE id = $1 AND (payment_intent_id = $2 OR payment_intent_id IS NULL)",
+    )
+    .bind(id)
+    .bind(payment_intent_id)
+    .bind(captured_amount)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Settle a payment made outside a payment provider, e.g. by bank transfer,
+/// as captured in full
+pub async fn record_received(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE payments
+         SET captured_amount = amount, status = 'succeeded', updated_at = now()
+         WHERE id = $1",
+    )
+    .bind(id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_payments_for_order(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<Vec<Payment>, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "SELECT {} FROM payments WHERE order_id = $1 ORDER BY created_at",
+        PAYMENT_COLUMNS
+    ))
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Mark balance links the customer has not paid yet as expired, before a new
+/// one replaces them
+pub async fn expire_pending_balances(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE payments SET status = 'expired', updated_at = now()
+         WHERE order_id = $1 AND kind = 'balance' AND status = 'pending'",
+    )
+    .bind(order_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Total captured across an order's payments through `provider`
+pub async fn captured_total(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+    provider: &str,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(captured_amount), 0)::BIGINT FROM payments
+         WHERE order_id = $1 AND provider = $2",
+    )
+    .bind(order_id)
+    .bind(provider)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Whether something was captured for the order and all of it has been refunded
+pub async fn fully_refunded(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(captured_amount), 0) > 0
+            AND COALESCE(SUM(captured_amount), 0) = COALESCE(SUM(refunded_amount), 0)
+         FROM payments WHERE order_id = $1",
+    )
+    .bind(order_id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// The order's latest payment with some of its capture not yet refunded or
+/// held by a pending refund, locked until the transaction ends so concurrent
+/// refunds are checked against each other
+pub async fn lock_refundable_payment(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+    provider: &str,
+) -> Result<Option<Payment>, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "SELECT {} FROM payments p
+         WHERE order_id = $1 AND provider = $2
+           AND captured_amount > (
+               SELECT COALESCE(SUM(amount), 0) FROM refunds r
+               WHERE r.payment_id = p.id AND r.status IN ('pending', 'succeeded')
+           )
+         ORDER BY created_at DESC
+         LIMIT 1
+         FOR UPDATE",
+        PAYMENT_COLUMNS
+    ))
+    .bind(order_id)
+    .bind(provider)
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// A payment by its provider payment ID, locked like [`lock_refundable_payment`]
+pub async fn lock_payment_by_intent(
+    tx: &mut Transaction<'_, Postgres>,
+    provider: &str,
+    payment_intent_id: &str,
+) -> Result<Option<Payment>, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "SELECT {} FROM payments
+         WHERE provider = $1 AND payment_intent_id = $2
+         FOR UPDATE",
+        PAYMENT_COLUMNS
+    ))
+    .bind(provider)
+    .bind(payment_intent_id)
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+pub async fn lock_payment(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+) -> Result<Payment, sqlx::Error> {
+    sqlx::query_as::<_, Payment>(&format!(
+        "SELECT {} FROM payments WHERE id = $1 FOR UPDATE",
+        PAYMENT_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+pub async fn set_refunded(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    refunded_amount: i64,
+    status: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE payments SET refunded_amount = $2, status = $3, updated_at = now() WHERE id = $1",
+    )
+    .bind(id)
+    .bind(refunded_amount)
+    .bind(status)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use quote_core::{Money, PricedQuote, Quote};
+use sqlx::{types::Json, FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::models::{PriceOverride, StoredQuote};
+
+#[derive(FromRow)]
+struct QuoteRow {
+    id: Uuid,
+    input: Json<Quote>,
+    output: Json<PricedQuote>,
+    customer_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    override_amount: Option<i64>,
+    override_reason: Option<String>,
+    override_staff_id: Option<Uuid>,
+    override_created_at: Option<DateTime<Utc>>,
+}
+
+impl From<QuoteRow> for StoredQuote {
+    fn from(row: QuoteRow) -> Self {
+        let price_override = match (
+            row.override_amount,
+            row.override_reason,
+            row.override_staff_id,
+            row.override_created_at,
+        ) {
+            (Some(amount), Some(reason), Some(staff_id), Some(created_at)) => Some(PriceOverride {
+                amount: Money::new(amount, row.output.currency),
+                reason,
+                staff_id,
+                created_at,
+            }),
+            _ => None,
+        };
+
+        StoredQuote {
+            id: row.id,
+            input: row.input.0,
+            output: row.output.0,
+            customer_id: row.customer_id,
+            price_override,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+/// Quotes with their latest price override, selected as `q`
+const QUOTE_SELECT: &str =
+    "SELECT q.id, q.input, q.output, q.customer_id, q.created_at, q.expires_at,
+        o.amount AS override_amount, o.reason AS override_reason,
+        o.staff_id AS override_staff_id, o.created_at AS override_created_at
+     FROM quotes q
+     LEFT JOIN LATERAL (
+        SELECT amount, reason, staff_id, created_at FROM price_overrides
+        WHERE quote_id = q.id
+        ORDER BY created_at DESC
+        LIMIT 1
+     ) o ON true";
+
+pub async fn insert_quote(pool: &PgPool, quote: &StoredQuote) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO quotes
+            (id, input, output, total_amount, currency, customer_id, created_at, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(quote.id)
+    .bind(Json(&quote.input))
+    .bind(Json(&quote.output))
+    .bind(quote.total_amount())
+    .bind(quote.output.currency.code())
+    .bind(quote.customer_id)
+    .bind(quote.created_at)
+    .bind(quote.expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_quote(pool: &PgPool, id: Uuid) -> Result<Option<StoredQuote>, sqlx::Error> {
+    let row = sqlx::query_as::<_, QuoteRow>(&format!("{} WHERE q.id = $1", QUOTE_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(StoredQuote::from))
+}
+
+/// A customer's quotes, newest first
+pub async fn list_quotes_for_customer(
+    pool: &PgPool,
+    customer_id: Uuid,
+) -> Result<Vec<StoredQuote>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, QuoteRow>(&format!(
+        "{} WHERE q.customer_id = $1 ORDER BY q.created_at DESC",
+        QUOTE_SELECT
+    ))
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(StoredQuote::from).collect())
+}
+
+/// Agree a price for a quote in place of the quoted one
+pub async fn insert_price_override(
+    pool: &PgPool,
+    quote_id: Uuid,
+    amount: Money,
+    reason: &str,
+    staff_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO price_overrides (id, quote_id, amount, currency, reason, staff_id)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(quote_id)
+    .bind(amount.amount)
+    .bind(amount.currency.code())
+    .bind(reason)
+    .bind(staff_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::models::{Refund, RefundStatus};
+
+/// A refund about to be requested from the provider, or reported by it
+pub struct NewRefund<'a> {
+    pub payment_id: Uuid,
+    pub order_id: Uuid,
+    pub provider_refund_id: Option<&'a str>,
+    pub amount: i64,
+    pub currency: &'a str,
+    pub reason: &'a str,
+    pub status: RefundStatus,
+    pub staff_id: Option<Uuid>,
+}
+
+const REFUND_COLUMNS: &str = "id, order_id, payment_id, provider_refund_id, amount, currency, \
+     reason, status, staff_id, created_at, updated_at";
+
+pub async fn insert_refund(
+    tx: &mut Transaction<'_, Postgres>,
+    refund: &NewRefund<'_>,
+) -> Result<Refund, sqlx::Error> {
+    sqlx::query_as::<_, Refund>(&format!(
+        "INSERT INTO refunds
+            (id, payment_id, order_id, provider_refund_id, amount, currency, reason, status, staff_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING {}",
+        REFUND_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(refund.payment_id)
+    .bind(refund.order_id)
+    .bind(refund.provider_refund_id)
+    .bind(refund.amount)
+    .bind(refund.currency)
+    .bind(refund.reason)
+    .bind(refund.status)
+    .bind(refund.staff_id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Record the provider's ID and latest status for a refund
+pub async fn update_refund(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    provider_refund_id: Option<&str>,
+    status: RefundStatus,
+) -> Result<Refund, sqlx::Error> {
+    sqlx::query_as::<_, Refund>(&format!(
+        "UPDATE refunds
+         SET provider_refund_id = COALESCE($2, provider_refund_id), status = $3, updated_at = now()
+         WHERE id = $1
+         RETURNING {}",
+        REFUND_COLUMNS
+    ))
+    .bind(id)
+    .bind(provider_refund_id)
+    .bind(status)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// A payment's refund by the provider's ID, or by our ID from the refund's metadata
+pub async fn find_refund(
+    tx: &mut Transaction<'_, Postgres>,
+    payment_id: Uuid,
+    provider_refund_id: &str,
+    id: Option<Uuid>,
+) -> Result<Option<Refund>, sqlx::Error> {
+    sqlx::query_as::<_, Refund>(&format!(
+        "SELECT {} FROM refunds
+         WHERE payment_id = $1 AND (provider_refund_id = $2 OR id = $3)
+         ORDER BY provider_refund_id = $2 DESC
+         LIMIT 1",
+        REFUND_COLUMNS
+    ))
+    .bind(payment_id)
+    .bind(provider_refund_id)
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Total of a payment's refunds that have succeeded, or may still succeed
+pub async fn held_amount(
+    tx: &mut Transaction<'_, Postgres>,
+    payment_id: Uuid,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0)::BIGINT FROM refunds
+         WHERE payment_id = $1 AND status IN ('pending', 'succeeded')",
+    )
+    .bind(payment_id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Total of a payment's refunds that have succeeded
+pub async fn succeeded_amount(
+    tx: &mut Transaction<'_, Postgres>,
+    payment_id: Uuid,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0)::BIGINT FROM refunds
+         WHERE payment_id = $1 AND status = 'succeeded'",
+    )
+    .bind(payment_id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+pub async fn list_refunds_for_order(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<Vec<Refund>, sqlx::Error> {
+    sqlx::query_as::<_, Refund>(&format!(
+        "SELECT {} FROM refunds WHERE order_id = $1 ORDER BY created_at",
+        REFUND_COLUMNS
+    ))
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,75 @@
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::models::{Staff, StaffRole};
+
+/// A staff account with the password hash it signs in with
+#[derive(FromRow)]
+pub struct StaffCredentials {
+    #[sqlx(flatten)]
+    pub staff: Staff,
+    pub password_hash: String,
+}
+
+const STAFF_COLUMNS: &str = "id, email, name, role, created_at";
+
+/// Create a staff account; fails with a unique violation when the email is taken
+pub async fn insert_staff(
+    pool: &PgPool,
+    email: &str,
+    password_hash: &str,
+    name: Option<&str>,
+    role: StaffRole,
+) -> Result<Staff, sqlx::Error> {
+    sqlx::query_as::<_, Staff>(&format!(
+        "INSERT INTO staff (id, email, password_hash, name, role)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING {}",
+        STAFF_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(email)
+    .bind(password_hash)
+    .bind(name)
+    .bind(role)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_staff(pool: &PgPool, id: Uuid) -> Result<Option<Staff>, sqlx::Error> {
+    sqlx::query_as::<_, Staff>(&format!(
+        "SELECT {} FROM staff WHERE id = $1",
+        STAFF_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_staff_credentials(
+    pool: &PgPool,
+    email: &str,
+) -> Result<Option<StaffCredentials>, sqlx::Error> {
+    sqlx::query_as::<_, StaffCredentials>(&format!(
+        "SELECT {}, password_hash FROM staff WHERE email = $1",
+        STAFF_COLUMNS
+    ))
+    .bind(email)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn list_staff(pool: &PgPool) -> Result<Vec<Staff>, sqlx::Error> {
+    sqlx::query_as::<_, Staff>(&format!(
+        "SELECT {} FROM staff ORDER BY created_at",
+        STAFF_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_owners(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM staff WHERE role = 'owner'")
+        .fetch_one(pool)
+        .await
+}
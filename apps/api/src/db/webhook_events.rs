@@ -0,0 +1,61 @@
+use sqlx::PgPool;
+
+use crate::models::WebhookEvent;
+
+const EVENT_COLUMNS: &str =
+    "id, event_type, payload, received_at, processed_at, delivery_count, replayed_at";
+
+/// Record a delivery in the ledger, returning the stored event
+///
+/// Redeliveries of a known event ID bump `delivery_count` and return the
+/// existing row, so callers can tell from `processed_at` whether to apply it.
+pub async fn record_event(
+    pool: &PgPool,
+    id: &str,
+    event_type: &str,
+    payload: &serde_json::Value,
+) -> Result<WebhookEvent, sqlx::Error> {
+    sqlx::query_as::<_, WebhookEvent>(&format!(
+        "INSERT INTO webhook_events (id, event_type, payload)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (id) DO UPDATE SET delivery_count = webhook_events.delivery_count + 1
+         RETURNING {}",
+        EVENT_COLUMNS
+    ))
+    .bind(id)
+    .bind(event_type)
+    .bind(payload)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_event(pool: &PgPool, id: &str) -> Result<Option<WebhookEvent>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookEvent>(&format!(
+        "SELECT {} FROM webhook_events WHERE id = $1",
+        EVENT_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn mark_processed(pool: &PgPool, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE webhook_events SET processed_at = now() WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn mark_replayed(pool: &PgPool, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE webhook_events SET replayed_at = now(), processed_at = COALESCE(processed_at, now())
+         WHERE id = $1",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
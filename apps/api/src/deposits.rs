@@ -0,0 +1,149 @@
+//! Balance payments of orders that paid a deposit at checkout
+//!
+//! The balance is requested through a second checkout session once the
+//! parts are ready for pickup. A new link replaces any unpaid earlier one,
+//! e.g. after the provider expired it.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::db::{self, payments::NewPayment};
+use crate::handlers::checkout::return_urls;
+use crate::models::{Order, OrderStatus, Payment, PaymentKind};
+use crate::payments::{CheckoutItem, CheckoutRequest, PaymentError};
+use crate::AppState;
+
+#[derive(Debug)]
+pub enum BalanceError {
+    /// The order was paid in full at checkout
+    NoDeposit,
+    /// The parts are not ready for pickup yet, or the order was closed
+    NotReady(OrderStatus),
+    /// The balance has been paid already
+    AlreadyPaid,
+    Provider(PaymentError),
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BalanceError::NoDeposit => f.write_str("order was paid in full at checkout"),
+            BalanceError::NotReady(status) => {
+                write!(
+                    f,
+                    "balance is due once ready for pickup, order is {}",
+                    status
+                )
+            }
+            BalanceError::AlreadyPaid => f.write_str("balance has been paid already"),
+            BalanceError::Provider(e) => write!(f, "payment provider error: {}", e),
+            BalanceError::Database(e) => write!(f, "database error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for BalanceError {
+    fn from(e: sqlx::Error) -> Self {
+        BalanceError::Database(e)
+    }
+}
+
+/// Create a checkout session for what is left to pay on a deposit order
+pub async fn request_balance(state: &AppState, order: &Order) -> Result<Payment, BalanceError> {
+    let deposit = order.deposit_amount.ok_or(BalanceError::NoDeposit)?;
+    if !matches!(
+        order.status,
+        OrderStatus::ReadyForPickup | OrderStatus::Shipped
+    ) {
+        return Err(BalanceError::NotReady(order.status));
+    }
+
+    let payments = db::payments::list_payments_for_order(&state.db, order.id).await?;
+    if balance_paid(&payments) {
+        return Err(BalanceError::AlreadyPaid);
+    }
+
+    let balance = order.total_amount - deposit;
+    let currency = order
+        .currency
+        .parse()
+        .map_err(|e: String| BalanceError::Provider(PaymentError(e)))?;
+    let payment_id = Uuid::new_v4();
+    let (success_url, cancel_url) = return_urls(None, None);
+
+    let session = state
+        .payments
+        .create_checkout_session(CheckoutRequest {
+            currency,
+            items: vec![CheckoutItem {
+                name: "Powder Coating - Balance".to_string(),
+                description: Some(format!("Order {}, deposit already paid", order.id)),
+                amount: balance,
+            }],
+            customer_email: order.customer_email.clone(),
+            success_url,
+            cancel_url,
+            metadata: HashMap::from([
+                ("order_id".to_string(), order.id.to_string()),
+                ("quote_id".to_string(), order.quote_id.to_string()),
+                ("payment_id".to_string(), payment_id.to_string()),
+                (
+                    "payment_kind".to_string(),
+                    PaymentKind::Balance.as_str().to_string(),
+                ),
+                ("total_amount".to_string(), balance.to_string()),
+                ("currency".to_string(), order.currency.clone()),
+            ]),
+        })
+        .await
+        .map_err(BalanceError::Provider)?;
+
+    let mut tx = state.db.begin().await?;
+    db::payments::expire_pending_balances(&mut tx, order.id).await?;
+    let payment = db::payments::insert_payment(
+        &mut tx,
+        &NewPayment {
+            id: payment_id,
+            order_id: order.id,
+            kind: PaymentKind::Balance,
+            provider: state.payments.name(),
+            provider_reference: &session.id,
+            checkout_url: Some(&session.url),
+            amount: balance,
+            currency: &order.currency,
+            status: "pending",
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "Requested balance of {} {} for order {} with session {}",
+        balance,
+        order.currency,
+        order.id,
+        session.id
+    );
+
+    Ok(payment)
+}
+
+/// Whether an order that paid a deposit still owes its balance; such orders
+/// do not leave the shop
+pub async fn balance_due(state: &AppState, order: &Order) -> Result<bool, sqlx::Error> {
+    let payments = db::payments::list_payments_for_order(&state.db, order.id).await?;
+
+    Ok(payments
+        .iter()
+        .any(|payment| payment.kind == PaymentKind::Deposit)
+        && !balance_paid(&payments))
+}
+
+fn balance_paid(payments: &[Payment]) -> bool {
+    payments
+        .iter()
+        .any(|payment| payment.kind == PaymentKind::Balance && payment.captured_amount > 0)
+}
@@ -0,0 +1,80 @@
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::auth::{unauthorized, AuthCustomer};
+use crate::db;
+use crate::handlers::{checkout::ErrorResponse, database_error};
+use crate::models::{Account, Order, StoredQuote};
+use crate::AppState;
+
+/// Get Account
+///
+/// Returns the signed-in customer's account.
+#[utoipa::path(
+    get,
+    path = "/api/account",
+    responses(
+        (status = 200, description = "Signed-in account", body = Account),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "account"
+)]
+pub async fn get_account(
+    State(state): State<AppState>,
+    customer: AuthCustomer,
+) -> Result<Json<Account>, (StatusCode, Json<ErrorResponse>)> {
+    db::customers::get_customer(&state.db, customer.id)
+        .await
+        .map_err(database_error)?
+        .map(Json)
+        .ok_or_else(|| unauthorized("Account no longer exists"))
+}
+
+/// List Account Quotes
+///
+/// Quotes created while signed in, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/account/quotes",
+    responses(
+        (status = 200, description = "The customer's quotes", body = Vec<StoredQuote>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "account"
+)]
+pub async fn list_account_quotes(
+    State(state): State<AppState>,
+    customer: AuthCustomer,
+) -> Result<Json<Vec<StoredQuote>>, (StatusCode, Json<ErrorResponse>)> {
+    db::quotes::list_quotes_for_customer(&state.db, customer.id)
+        .await
+        .map(Json)
+        .map_err(database_error)
+}
+
+/// List Account Orders
+///
+/// Orders checked out by or for the signed-in customer, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/account/orders",
+    responses(
+        (status = 200, description = "The customer's orders", body = Vec<Order>),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "account"
+)]
+pub async fn list_account_orders(
+    State(state): State<AppState>,
+    customer: AuthCustomer,
+) -> Result<Json<Vec<Order>>, (StatusCode, Json<ErrorResponse>)> {
+    db::orders::list_orders_for_customer(&state.db, customer.id)
+        .await
+        .map(Json)
+        .map_err(database_error)
+}
@@ -0,0 +1,71 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::auth::{self, STAFF_TOKEN_TTL};
+use crate::db;
+use crate::handlers::{
+    auth::{invalid_credentials, normalize_email},
+    checkout::ErrorResponse,
+    database_error,
+};
+use crate::models::Staff;
+use crate::AppState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StaffLoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// Token for a signed-in staff member
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StaffTokenResponse {
+    /// Send as `Authorization: Bearer <access_token>` to `/api/admin`
+    pub access_token: String,
+    /// Always `"Bearer"`
+    pub token_type: String,
+    /// Seconds until the token expires; sign in again afterwards
+    pub expires_in: i64,
+    pub staff: Staff,
+}
+
+/// Staff Log In
+///
+/// Exchanges a staff email and password for an admin API token valid for
+/// one shift. Customer tokens are not accepted by admin endpoints.
+#[utoipa::path(
+    post,
+    path = "/api/admin/auth/login",
+    request_body = StaffLoginRequest,
+    responses(
+        (status = 200, description = "Signed in", body = StaffTokenResponse),
+        (status = 401, description = "Wrong email or password", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "admin"
+)]
+pub async fn staff_login(
+    State(state): State<AppState>,
+    Json(request): Json<StaffLoginRequest>,
+) -> Result<Json<StaffTokenResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let credentials = db::staff::get_staff_credentials(&state.db, &normalize_email(&request.email))
+        .await
+        .map_err(database_error)?;
+
+    let (staff, hash) = credentials.map(|c| (c.staff, c.password_hash)).unzip();
+    if !auth::check_password(request.password, hash).await {
+        tracing::warn!("Rejected staff sign-in");
+        return Err(invalid_credentials());
+    }
+    let staff = staff.ok_or_else(invalid_credentials)?;
+    tracing::info!("Staff {} ({}) signed in", staff.id, staff.role);
+
+    Ok(Json(StaffTokenResponse {
+        access_token: state.tokens.staff_token(staff.id, Utc::now()),
+        token_type: "Bearer".to_string(),
+        expires_in: STAFF_TOKEN_TTL.num_seconds(),
+        staff,
+    }))
+}
@@ -0,0 +1,137 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::client_error;
+use crate::auth::AuthStaff;
+use crate::db;
+use crate::handlers::{checkout::ErrorResponse, database_error};
+use crate::invoices::{self, TransferError};
+use crate::models::{Invoice, Permission};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecordTransferRequest {
+    /// Date the money arrived on the bank account; today when absent
+    pub received_on: Option<NaiveDate>,
+    /// Kept in the order's status history, e.g. the bank's transaction reference
+    pub note: Option<String>,
+}
+
+/// An unpaid invoice past its due date
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OverdueInvoice {
+    pub invoice: Invoice,
+    pub days_overdue: i64,
+}
+
+/// Record Bank Transfer
+///
+/// Records that the bank transfer for an invoice arrived in full. The order
+/// moves from `awaiting_payment` to `paid` and is booked into the oven
+/// schedule, as card payments are.
+#[utoipa::path(
+    post,
+    path = "/api/admin/invoices/{invoice_id}/transfer",
+    params(
+        ("invoice_id" = Uuid, Path, description = "Invoice ID")
+    ),
+    request_body = RecordTransferRequest,
+    responses(
+        (status = 200, description = "Transfer recorded", body = Invoice),
+        (status = 400, description = "Received date in the future", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not record payments", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse),
+        (status = 409, description = "Invoice already paid, or order no longer awaiting payment", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn record_transfer(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(invoice_id): Path<Uuid>,
+    Json(request): Json<RecordTransferRequest>,
+) -> Result<Json<Invoice>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::RecordPayments)?;
+
+    let today = Utc::now().date_naive();
+    let received_on = request.received_on.unwrap_or(today);
+    if received_on > today {
+        return Err(client_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_date",
+            "A transfer cannot be received in the future",
+        ));
+    }
+    let note = request
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+
+    invoices::record_transfer(&state, invoice_id, received_on, staff.id, note)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            TransferError::NotFound => client_error(
+                StatusCode::NOT_FOUND,
+                "invoice_not_found",
+                format!("Invoice {} does not exist", invoice_id),
+            ),
+            TransferError::AlreadyPaid => client_error(
+                StatusCode::CONFLICT,
+                "invoice_paid",
+                "Invoice has been paid already",
+            ),
+            TransferError::NotAwaitingPayment(_) => {
+                client_error(StatusCode::CONFLICT, "not_awaiting_payment", e.to_string())
+            }
+            TransferError::Database(e) => database_error(e),
+        })
+}
+
+/// List Overdue Invoices
+///
+/// Unpaid invoices past their due date whose orders still await payment,
+/// longest overdue first. Invoices of cancelled orders are left out.
+#[utoipa::path(
+    get,
+    path = "/api/admin/invoices/overdue",
+    responses(
+        (status = 200, description = "Overdue invoices", body = Vec<OverdueInvoice>),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn list_overdue_invoices(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+) -> Result<Json<Vec<OverdueInvoice>>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::ViewOrders)?;
+
+    let today = Utc::now().date_naive();
+    let invoices = db::invoices::list_overdue(&state.db, today)
+        .await
+        .map_err(database_error)?;
+
+    Ok(Json(
+        invoices
+            .into_iter()
+            .map(|invoice| OverdueInvoice {
+                days_overdue: invoice.days_overdue(today),
+                invoice,
+            })
+            .collect(),
+    ))
+}
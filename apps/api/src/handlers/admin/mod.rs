@@ -0,0 +1,28 @@
+//! Shop staff endpoints under `/api/admin`, authenticated with staff tokens
+//! and gated by role
+
+pub mod auth;
+pub mod invoices;
+pub mod orders;
+pub mod quotes;
+pub mod staff;
+pub mod webhooks;
+
+use axum::{http::StatusCode, Json};
+
+use crate::handlers::checkout::ErrorResponse;
+
+/// A 4xx response with an error code and message
+fn client_error(
+    status: StatusCode,
+    error: &str,
+    message: impl Into<String>,
+) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+            message: message.into(),
+        }),
+    )
+}
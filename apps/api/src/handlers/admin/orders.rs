@@ -0,0 +1,494 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::client_error;
+use crate::auth::AuthStaff;
+use crate::db::{self, orders::OrderFilter};
+use crate::deposits::{self, BalanceError};
+use crate::handlers::{checkout::ErrorResponse, database_error};
+use crate::models::{
+    Invoice, Order, OrderItem, OrderStatus, OrderStatusChange, Payment, Permission, Refund,
+};
+use crate::refunds::{self, RefundError};
+use crate::AppState;
+
+/// Orders returned per page when no limit is given
+const DEFAULT_PAGE_SIZE: i64 = 50;
+/// Largest page the search returns
+const MAX_PAGE_SIZE: i64 = 200;
+
+/// Statuses staff move orders to by hand. Payment statuses only come from
+/// the payment provider, and cancelling has its own endpoint.
+const PRODUCTION_STATUSES: [OrderStatus; 6] = [
+    OrderStatus::InPrep,
+    OrderStatus::Coating,
+    OrderStatus::Curing,
+    OrderStatus::Qc,
+    OrderStatus::ReadyForPickup,
+    OrderStatus::Shipped,
+];
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OrderSearchParams {
+    /// Only orders in this status
+    pub status: Option<OrderStatus>,
+    /// Part of the customer email, or a whole order, quote or checkout session ID
+    pub q: Option<String>,
+    /// Page size, at most 200 (default 50)
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// An order with its lines, status history, payments, refunds and invoice
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrderDetail {
+    pub order: Order,
+    /// Orders paid by bank transfer only
+    pub invoice: Option<Invoice>,
+    pub items: Vec<OrderItem>,
+    /// Oldest first
+    pub status_history: Vec<OrderStatusChange>,
+    /// Oldest first
+    pub payments: Vec<Payment>,
+    /// Oldest first, including failed ones
+    pub refunds: Vec<Refund>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateOrderStatusRequest {
+    /// One of the production statuses, from `in_prep` to `shipped`
+    pub status: OrderStatus,
+    /// Kept in the status history, e.g. why parts went back to coating
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CancelOrderRequest {
+    /// Why the order is cancelled; kept in the status history
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefundOrderRequest {
+    /// Minor units (cents); everything not yet refunded when absent
+    pub amount: Option<i64>,
+    /// Why the money is returned, e.g. "two panels damaged in transit"
+    pub reason: String,
+}
+
+/// List Orders
+///
+/// Searches orders, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/admin/orders",
+    params(OrderSearchParams),
+    responses(
+        (status = 200, description = "Matching orders", body = Vec<Order>),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn list_orders(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Query(params): Query<OrderSearchParams>,
+) -> Result<Json<Vec<Order>>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::ViewOrders)?;
+
+    let filter = OrderFilter {
+        status: params.status,
+        query: params.q.as_deref().map(str::trim).filter(|q| !q.is_empty()),
+        limit: params
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+
+    db::orders::search_orders(&state.db, &filter)
+        .await
+        .map(Json)
+        .map_err(database_error)
+}
+
+/// Get Order
+///
+/// Returns an order with its priced lines, status history, payments,
+/// refunds and, for orders paid by bank transfer, its invoice.
+#[utoipa::path(
+    get,
+    path = "/api/admin/orders/{order_id}",
+    params(
+        ("order_id" = Uuid, Path, description = "Order ID")
+    ),
+    responses(
+        (status = 200, description = "Order found", body = OrderDetail),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn get_order(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(order_id): Path<Uuid>,
+) -> Result<Json<OrderDetail>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::ViewOrders)?;
+
+    let order = find_order(&state, order_id).await?;
+    let items = db::orders::list_order_items(&state.db, order_id)
+        .await
+        .map_err(database_error)?;
+    let status_history = db::orders::list_status_changes(&state.db, order_id)
+        .await
+        .map_err(database_error)?;
+    let payments = db::payments::list_payments_for_order(&state.db, order_id)
+        .await
+        .map_err(database_error)?;
+    let refunds = db::refunds::list_refunds_for_order(&state.db, order_id)
+        .await
+        .map_err(database_error)?;
+    let invoice = db::invoices::get_invoice_for_order(&state.db, order_id)
+        .await
+        .map_err(database_error)?;
+
+    Ok(Json(OrderDetail {
+        order,
+        invoice,
+        items,
+        status_history,
+        payments,
+        refunds,
+    }))
+}
+
+/// Update Order Status
+///
+/// Moves an order through production, e.g. `paid → in_prep` or `qc →
+/// coating` for parts that failed inspection. Only legal transitions are
+/// accepted. Orders that paid a deposit get a balance payment link when they
+/// reach `ready_for_pickup`, and cannot be `shipped` until the balance is paid.
+#[utoipa::path(
+    post,
+    path = "/api/admin/orders/{order_id}/status",
+    params(
+        ("order_id" = Uuid, Path, description = "Order ID")
+    ),
+    request_body = UpdateOrderStatusRequest,
+    responses(
+        (status = 200, description = "Status changed", body = Order),
+        (status = 400, description = "Not a production status", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not change order status", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+        (status = 409, description = "Transition not allowed from the current status, or balance unpaid", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn update_order_status(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(order_id): Path<Uuid>,
+    Json(request): Json<UpdateOrderStatusRequest>,
+) -> Result<Json<Order>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::UpdateOrderStatus)?;
+
+    if !PRODUCTION_STATUSES.contains(&request.status) {
+        return Err(client_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_status",
+            format!(
+                "Orders cannot be moved to {} by hand; payment statuses come from the payment provider",
+                request.status
+            ),
+        ));
+    }
+
+    let order = find_order(&state, order_id).await?;
+    if request.status == OrderStatus::Shipped
+        && deposits::balance_due(&state, &order)
+            .await
+            .map_err(database_error)?
+    {
+        return Err(client_error(
+            StatusCode::CONFLICT,
+            "balance_unpaid",
+            "The balance must be paid before the order ships",
+        ));
+    }
+    let note = request
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    let order = change_status(&state, &staff, &order, request.status, note).await?;
+
+    // The status change stands even if the provider is down; the link can
+    // be requested again through the balance payment endpoint
+    if order.status == OrderStatus::ReadyForPickup && order.deposit_amount.is_some() {
+        if let Err(e) = deposits::request_balance(&state, &order).await {
+            tracing::error!(
+                "Balance payment for order {} not requested: {}",
+                order.id,
+                e
+            );
+        }
+    }
+
+    Ok(order)
+}
+
+/// Cancel Order
+///
+/// Cancels an order that has not gone into the oven yet and releases its
+/// booked oven time. Cancelling a paid order does not refund it.
+#[utoipa::path(
+    post,
+    path = "/api/admin/orders/{order_id}/cancel",
+    params(
+        ("order_id" = Uuid, Path, description = "Order ID")
+    ),
+    request_body = CancelOrderRequest,
+    responses(
+        (status = 200, description = "Order cancelled", body = Order),
+        (status = 400, description = "No reason given", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not cancel orders", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+        (status = 409, description = "Order is past the point it can be cancelled", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn cancel_order(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(order_id): Path<Uuid>,
+    Json(request): Json<CancelOrderRequest>,
+) -> Result<Json<Order>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::CancelOrders)?;
+
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(client_error(
+            StatusCode::BAD_REQUEST,
+            "reason_required",
+            "A reason is required to cancel an order",
+        ));
+    }
+
+    let order = find_order(&state, order_id).await?;
+    change_status(&state, &staff, &order, OrderStatus::Cancelled, Some(reason)).await
+}
+
+/// Request Balance Payment
+///
+/// Creates a payment link for the balance of an order that paid a deposit,
+/// replacing any unpaid earlier link. Links are created when the order
+/// reaches `ready_for_pickup`; this makes a new one, e.g. after the old one
+/// expired. Send the returned `checkout_url` to the customer.
+#[utoipa::path(
+    post,
+    path = "/api/admin/orders/{order_id}/balance-payment",
+    params(
+        ("order_id" = Uuid, Path, description = "Order ID")
+    ),
+    responses(
+        (status = 201, description = "Balance payment link created", body = Payment),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not request payments", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+        (status = 409, description = "Order paid in full, not ready for pickup, or balance already paid", body = ErrorResponse),
+        (status = 500, description = "Payment provider or internal error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn request_balance_payment(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(order_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<Payment>), (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::RequestPayments)?;
+
+    let order = find_order(&state, order_id).await?;
+    let payment = deposits::request_balance(&state, &order)
+        .await
+        .map_err(|e| match e {
+            BalanceError::NoDeposit => {
+                client_error(StatusCode::CONFLICT, "no_deposit", "Order was paid in full")
+            }
+            BalanceError::NotReady(_) => {
+                client_error(StatusCode::CONFLICT, "not_ready", e.to_string())
+            }
+            BalanceError::AlreadyPaid => client_error(
+                StatusCode::CONFLICT,
+                "balance_paid",
+                "Balance has been paid already",
+            ),
+            BalanceError::Provider(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "stripe_error".to_string(),
+                    message: format!("Failed to create checkout session: {}", e),
+                }),
+            ),
+            BalanceError::Database(e) => database_error(e),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(payment)))
+}
+
+/// Refund Order
+///
+/// Returns money from an order's captured payment through the payment
+/// provider, in full or in part. Refunds never add up to more than was
+/// captured. Orders paid as a deposit and a balance are refunded one payment
+/// at a time, the balance first. Card refunds usually succeed at once; others stay `pending`
+/// until the provider reports back. The order moves to `refunded` once
+/// everything has been returned.
+#[utoipa::path(
+    post,
+    path = "/api/admin/orders/{order_id}/refunds",
+    params(
+        ("order_id" = Uuid, Path, description = "Order ID")
+    ),
+    request_body = RefundOrderRequest,
+    responses(
+        (status = 201, description = "Refund issued", body = Refund),
+        (status = 400, description = "Amount not positive or no reason given", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not refund orders", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+        (status = 409, description = "Order not paid, or amount exceeds what is left to refund", body = ErrorResponse),
+        (status = 500, description = "Payment provider or internal error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn refund_order(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(order_id): Path<Uuid>,
+    Json(request): Json<RefundOrderRequest>,
+) -> Result<(StatusCode, Json<Refund>), (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::RefundOrders)?;
+
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(client_error(
+            StatusCode::BAD_REQUEST,
+            "reason_required",
+            "A reason is required to refund an order",
+        ));
+    }
+
+    let order = find_order(&state, order_id).await?;
+    let refund = refunds::issue_refund(&state, &order, request.amount, reason, staff.id)
+        .await
+        .map_err(|e| match e {
+            RefundError::NotPaid => client_error(
+                StatusCode::CONFLICT,
+                "not_paid",
+                "Order has no payment to refund",
+            ),
+            RefundError::InvalidAmount => {
+                client_error(StatusCode::BAD_REQUEST, "invalid_amount", e.to_string())
+            }
+            RefundError::ExceedsRefundable { refundable } => client_error(
+                StatusCode::CONFLICT,
+                "exceeds_refundable",
+                format!("At most {} can still be refunded", refundable),
+            ),
+            RefundError::Provider(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "stripe_error".to_string(),
+                    message: format!("Failed to refund payment: {}", e),
+                }),
+            ),
+            RefundError::Database(e) => database_error(e),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(refund)))
+}
+
+async fn find_order(
+    state: &AppState,
+    order_id: Uuid,
+) -> Result<Order, (StatusCode, Json<ErrorResponse>)> {
+    db::orders::get_order(&state.db, order_id)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            client_error(
+                StatusCode::NOT_FOUND,
+                "order_not_found",
+                format!("Order {} does not exist", order_id),
+            )
+        })
+}
+
+/// Apply a staff status change and record it in the history
+async fn change_status(
+    state: &AppState,
+    staff: &AuthStaff,
+    order: &Order,
+    next: OrderStatus,
+    note: Option<&str>,
+) -> Result<Json<Order>, (StatusCode, Json<ErrorResponse>)> {
+    order
+        .status
+        .transition_to(next)
+        .map_err(|e| client_error(StatusCode::CONFLICT, "invalid_transition", e.to_string()))?;
+
+    let mut tx = state.db.begin().await.map_err(database_error)?;
+
+    if !db::orders::update_order_status(&mut tx, order.id, order.status, next)
+        .await
+        .map_err(database_error)?
+    {
+        return Err(client_error(
+            StatusCode::CONFLICT,
+            "order_changed",
+            "Order status changed meanwhile, reload and try again",
+        ));
+    }
+
+    db::orders::insert_status_change(&mut tx, order.id, order.status, next, Some(staff.id), note)
+        .await
+        .map_err(database_error)?;
+
+    if next == OrderStatus::Cancelled {
+        db::oven_bookings::release_bookings(&mut tx, order.id)
+            .await
+            .map_err(database_error)?;
+    }
+
+    tx.commit().await.map_err(database_error)?;
+
+    tracing::info!(
+        "Staff {} moved order {} {} -> {}",
+        staff.id,
+        order.id,
+        order.status,
+        next
+    );
+
+    find_order(state, order.id).await.map(Json)
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use quote_core::Money;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::client_error;
+use crate::auth::AuthStaff;
+use crate::db;
+use crate::handlers::{checkout::ErrorResponse, database_error};
+use crate::models::{Permission, StoredQuote};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PriceOverrideRequest {
+    /// Agreed gross amount in minor units (cents), VAT included, in the quote's currency
+    pub amount: i64,
+    /// Why the quoted price does not apply, e.g. "repeat customer, 10% agreed by phone"
+    pub reason: String,
+}
+
+/// Override Quote Price
+///
+/// Agrees a price for a quote that checkout then charges in place of the
+/// quoted amount. The quoted price and breakdown are kept for reference; a
+/// later override replaces an earlier one. Quotes that have been paid or
+/// have expired cannot be changed.
+#[utoipa::path(
+    post,
+    path = "/api/admin/quotes/{quote_id}/price-override",
+    params(
+        ("quote_id" = Uuid, Path, description = "Quote ID")
+    ),
+    request_body = PriceOverrideRequest,
+    responses(
+        (status = 200, description = "Quote with the agreed price", body = StoredQuote),
+        (status = 400, description = "Amount not positive or no reason given", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not override prices", body = ErrorResponse),
+        (status = 404, description = "Quote not found", body = ErrorResponse),
+        (status = 409, description = "Quote already paid", body = ErrorResponse),
+        (status = 410, description = "Quote expired", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn override_quote_price(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(quote_id): Path<Uuid>,
+    Json(request): Json<PriceOverrideRequest>,
+) -> Result<Json<StoredQuote>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::OverridePrices)?;
+
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(client_error(
+            StatusCode::BAD_REQUEST,
+            "reason_required",
+            "A reason is required to override a price",
+        ));
+    }
+    if request.amount <= 0 {
+        return Err(client_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_amount",
+            "Amount must be greater than zero",
+        ));
+    }
+
+    let quote = find_quote(&state, quote_id).await?;
+    if quote.expires_at <= Utc::now() {
+        return Err(client_error(
+            StatusCode::GONE,
+            "quote_expired",
+            "Quote has expired, the customer needs a new quote",
+        ));
+    }
+    if db::orders::quote_has_paid_order(&state.db, quote_id)
+        .await
+        .map_err(database_error)?
+    {
+        return Err(client_error(
+            StatusCode::CONFLICT,
+            "quote_paid",
+            "Quote has already been paid",
+        ));
+    }
+
+    let amount = Money::new(request.amount, quote.output.currency);
+    db::quotes::insert_price_override(&state.db, quote_id, amount, reason, staff.id)
+        .await
+        .map_err(database_error)?;
+    tracing::info!(
+        "Staff {} set quote {} to {} (quoted {}): {}",
+        staff.id,
+        quote_id,
+        amount,
+        quote.output.amount_due(),
+        reason
+    );
+
+    find_quote(&state, quote_id).await.map(Json)
+}
+
+async fn find_quote(
+    state: &AppState,
+    quote_id: Uuid,
+) -> Result<StoredQuote, (StatusCode, Json<ErrorResponse>)> {
+    db::quotes::get_quote(&state.db, quote_id)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            client_error(
+                StatusCode::NOT_FOUND,
+                "quote_not_found",
+                format!("Quote {} does not exist", quote_id),
+            )
+        })
+}
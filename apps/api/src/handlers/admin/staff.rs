@@ -0,0 +1,130 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::client_error;
+use crate::auth::AuthStaff;
+use crate::db;
+use crate::handlers::{
+    auth::{hash_password, normalize_email, validate_credentials},
+    checkout::ErrorResponse,
+    database_error,
+    quotes::ValidationErrorResponse,
+};
+use crate::models::{Permission, Staff, StaffRole};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateStaffRequest {
+    pub email: String,
+    /// At least 10 characters
+    pub password: String,
+    pub name: Option<String>,
+    pub role: StaffRole,
+}
+
+/// List Staff
+///
+/// Every staff account and its role. Owners only.
+#[utoipa::path(
+    get,
+    path = "/api/admin/staff",
+    responses(
+        (status = 200, description = "Staff accounts", body = Vec<Staff>),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not manage staff", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn list_staff(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+) -> Result<Json<Vec<Staff>>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::ManageStaff)?;
+
+    db::staff::list_staff(&state.db)
+        .await
+        .map(Json)
+        .map_err(database_error)
+}
+
+/// Create Staff
+///
+/// Adds a staff account with a role. Owners only.
+#[utoipa::path(
+    post,
+    path = "/api/admin/staff",
+    request_body = CreateStaffRequest,
+    responses(
+        (status = 201, description = "Staff account created", body = Staff),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not manage staff", body = ErrorResponse),
+        (status = 409, description = "Email already registered", body = ErrorResponse),
+        (status = 422, description = "Email or password failed validation", body = ValidationErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn create_staff(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Json(request): Json<CreateStaffRequest>,
+) -> Result<(StatusCode, Json<Staff>), Response> {
+    staff
+        .require(Permission::ManageStaff)
+        .map_err(IntoResponse::into_response)?;
+
+    let email = normalize_email(&request.email);
+    let field_errors = validate_credentials(&email, &request.password);
+    if !field_errors.is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ValidationErrorResponse {
+                error: "invalid_input".to_string(),
+                message: "Staff account failed validation".to_string(),
+                field_errors,
+            }),
+        )
+            .into_response());
+    }
+
+    let password_hash = hash_password(request.password)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let name = request
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+
+    let created = db::staff::insert_staff(&state.db, &email, &password_hash, name, request.role)
+        .await
+        .map_err(|e| {
+            if db::is_unique_violation(&e) {
+                client_error(
+                    StatusCode::CONFLICT,
+                    "email_taken",
+                    "A staff account with this email already exists",
+                )
+                .into_response()
+            } else {
+                database_error(e).into_response()
+            }
+        })?;
+    tracing::info!(
+        "Staff {} added {} as {}",
+        staff.id,
+        created.id,
+        created.role
+    );
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
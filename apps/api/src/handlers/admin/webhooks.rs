@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::auth::AuthStaff;
+use crate::db;
+use crate::handlers::{checkout::ErrorResponse, database_error, webhooks};
+use crate::models::Permission;
+use crate::AppState;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookReplayResponse {
+    pub event_id: String,
+    pub event_type: String,
+    pub replayed: bool,
+}
+
+/// Replay Webhook Event
+///
+/// Re-applies a stored webhook event through the same handler used for live
+/// deliveries, whether or not it was processed before. Use it to repair
+/// orders after a processing bug has been fixed. Owners only.
+#[utoipa::path(
+    post,
+    path = "/api/admin/webhooks/{event_id}/replay",
+    params(
+        ("event_id" = String, Path, description = "Provider event ID")
+    ),
+    responses(
+        (status = 200, description = "Event replayed", body = WebhookReplayResponse),
+        (status = 401, description = "Missing or invalid staff token", body = ErrorResponse),
+        (status = 403, description = "Role may not replay webhooks", body = ErrorResponse),
+        (status = 404, description = "Event not in the ledger", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer" = [])),
+    tag = "admin"
+)]
+pub async fn replay_webhook(
+    State(state): State<AppState>,
+    staff: AuthStaff,
+    Path(event_id): Path<String>,
+) -> Result<Json<WebhookReplayResponse>, (StatusCode, Json<ErrorResponse>)> {
+    staff.require(Permission::ReplayWebhooks)?;
+
+    let event = db::webhook_events::get_event(&state.db, &event_id)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "event_not_found".to_string(),
+                    message: format!("Webhook event {} is not in the ledger", event_id),
+                }),
+            )
+        })?;
+
+    tracing::info!(
+        "Staff {} replaying webhook {} ({})",
+        staff.id,
+        event.id,
+        event.event_type
+    );
+
+    webhooks::handle_event(&state, &event.event_type, &event.payload)
+        .await
+        .map_err(|status| {
+            (
+                status,
+                Json(ErrorResponse {
+                    error: "replay_failed".to_string(),
+                    message: format!("Replaying webhook event {} failed", event.id),
+                }),
+            )
+        })?;
+
+    db::webhook_events::mark_replayed(&state.db, &event.id)
+        .await
+        .map_err(database_error)?;
+
+    Ok(Json(WebhookReplayResponse {
+        event_id: event.id,
+        event_type: event.event_type,
+        replayed: true,
+    }))
+}
@@ -0,0 +1,349 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use quote_core::FieldError;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::auth::{self, unauthorized, TokenType, ACCESS_TOKEN_TTL, REFRESH_TOKEN_TTL};
+use crate::db;
+use crate::handlers::{checkout::ErrorResponse, database_error, quotes::ValidationErrorResponse};
+use crate::models::Account;
+use crate::AppState;
+
+/// Shortest password accepted at registration
+const MIN_PASSWORD_CHARS: usize = 10;
+/// Longest password accepted, to bound hashing work
+const MAX_PASSWORD_CHARS: usize = 128;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterRequest {
+    pub email: String,
+    /// At least 10 characters
+    pub password: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    /// Refresh token from register, login or a previous refresh
+    pub refresh_token: String,
+}
+
+/// Tokens for a signed-in customer
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TokenResponse {
+    /// Send as `Authorization: Bearer <access_token>`
+    pub access_token: String,
+    /// Exchange at `/api/auth/refresh` for new tokens; usable once
+    pub refresh_token: String,
+    /// Always `"Bearer"`
+    pub token_type: String,
+    /// Seconds until the access token expires
+    pub expires_in: i64,
+    pub account: Account,
+}
+
+/// Register
+///
+/// Creates a customer account and signs it in. Emails are case-insensitive.
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, description = "Account created", body = TokenResponse),
+        (status = 409, description = "Email already registered", body = ErrorResponse),
+        (status = 422, description = "Email or password failed validation", body = ValidationErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn register(
+    State(state): State<AppState>,
+    Json(request): Json<RegisterRequest>,
+) -> Result<(StatusCode, Json<TokenResponse>), Response> {
+    let email = normalize_email(&request.email);
+    let name = request
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+
+    let field_errors = validate_credentials(&email, &request.password);
+    if !field_errors.is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ValidationErrorResponse {
+                error: "invalid_input".to_string(),
+                message: "Registration failed validation".to_string(),
+                field_errors,
+            }),
+        )
+            .into_response());
+    }
+
+    let password_hash = hash_password(request.password)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    let account =
+        match db::customers::insert_customer(&state.db, &email, &password_hash, name).await {
+            Ok(account) => account,
+            Err(e) if db::is_unique_violation(&e) => {
+                return Err((
+                    StatusCode::CONFLICT,
+                    Json(ErrorResponse {
+                        error: "email_taken".to_string(),
+                        message: "An account with this email already exists".to_string(),
+                    }),
+                )
+                    .into_response())
+            }
+            Err(e) => return Err(database_error(e).into_response()),
+        };
+    tracing::info!("Registered customer {}", account.id);
+
+    let tokens = issue_tokens(&state, account)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    Ok((StatusCode::CREATED, Json(tokens)))
+}
+
+/// Log In
+///
+/// Exchanges an email and password for an access and refresh token.
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Signed in", body = TokenResponse),
+        (status = 401, description = "Wrong email or password", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn login(
+    State(state): State<AppState>,
+    Json(request): Json<LoginRequest>,
+) -> Result<Json<TokenResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let credentials = db::customers::get_credentials(&state.db, &normalize_email(&request.email))
+        .await
+        .map_err(database_error)?;
+
+    let (account, hash) = credentials.map(|c| (c.account, c.password_hash)).unzip();
+    if !auth::check_password(request.password, hash).await {
+        return Err(invalid_credentials());
+    }
+    let account = account.ok_or_else(invalid_credentials)?;
+
+    issue_tokens(&state, account).await.map(Json)
+}
+
+/// Refresh Tokens
+///
+/// Exchanges a refresh token for a new access and refresh token. Each
+/// refresh token works once; the one sent is revoked.
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New tokens", body = TokenResponse),
+        (status = 401, description = "Refresh token is invalid, expired or already used", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(request): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let (customer_id, token_id) = revoke(&state, &request.refresh_token).await?;
+
+    let account = db::customers::get_customer(&state.db, customer_id)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| unauthorized("Account no longer exists"))?;
+    tracing::debug!("Rotated refresh token {} of {}", token_id, customer_id);
+
+    issue_tokens(&state, account).await.map(Json)
+}
+
+/// Log Out
+///
+/// Revokes a refresh token. Access tokens already issued stay valid until
+/// they expire.
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    request_body = RefreshRequest,
+    responses(
+        (status = 204, description = "Refresh token revoked"),
+        (status = 401, description = "Refresh token is invalid, expired or already used", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn logout(
+    State(state): State<AppState>,
+    Json(request): Json<RefreshRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    revoke(&state, &request.refresh_token).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Check a refresh token and revoke it, returning its customer and ID
+async fn revoke(
+    state: &AppState,
+    refresh_token: &str,
+) -> Result<(Uuid, Uuid), (StatusCode, Json<ErrorResponse>)> {
+    let rejected = || unauthorized("Refresh token is invalid, expired or already used");
+
+    let claims = state
+        .tokens
+        .decode(refresh_token, TokenType::Refresh)
+        .map_err(|_| rejected())?;
+    let token_id = claims.jti.ok_or_else(rejected)?;
+
+    if !db::customers::revoke_refresh_token(&state.db, token_id, claims.sub)
+        .await
+        .map_err(database_error)?
+    {
+        tracing::warn!(
+            "Refresh token {} of {} reused or revoked",
+            token_id,
+            claims.sub
+        );
+        return Err(rejected());
+    }
+
+    Ok((claims.sub, token_id))
+}
+
+/// Issue a new token pair, recording the refresh token
+async fn issue_tokens(
+    state: &AppState,
+    account: Account,
+) -> Result<TokenResponse, (StatusCode, Json<ErrorResponse>)> {
+    let now = Utc::now();
+    let refresh_id = Uuid::new_v4();
+    let refresh_expires_at = now + REFRESH_TOKEN_TTL;
+
+    db::customers::insert_refresh_token(&state.db, refresh_id, account.id, refresh_expires_at)
+        .await
+        .map_err(database_error)?;
+
+    Ok(TokenResponse {
+        access_token: state.tokens.access_token(account.id, now),
+        refresh_token: state
+            .tokens
+            .refresh_token(account.id, refresh_id, refresh_expires_at),
+        token_type: "Bearer".to_string(),
+        expires_in: ACCESS_TOKEN_TTL.num_seconds(),
+        account,
+    })
+}
+
+/// Field errors for a new account's normalized email and password
+pub(crate) fn validate_credentials(email: &str, password: &str) -> Vec<FieldError> {
+    let mut field_errors = Vec::new();
+    if !is_valid_email(email) {
+        field_errors.push(FieldError::new("email", "Must be a valid email address"));
+    }
+    let password_chars = password.chars().count();
+    if !(MIN_PASSWORD_CHARS..=MAX_PASSWORD_CHARS).contains(&password_chars) {
+        field_errors.push(FieldError::new(
+            "password",
+            format!(
+                "Must be {} to {} characters",
+                MIN_PASSWORD_CHARS, MAX_PASSWORD_CHARS
+            ),
+        ));
+    }
+    field_errors
+}
+
+pub(crate) fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// A plausibility check only; ownership is never verified here
+fn is_valid_email(email: &str) -> bool {
+    email.len() <= 254
+        && !email.chars().any(char::is_whitespace)
+        && email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && !domain.contains('@') && domain.contains('.')
+        })
+}
+
+/// Hash a new password; Argon2 is deliberately slow, so off the async workers
+pub(crate) async fn hash_password(
+    password: String,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    tokio::task::spawn_blocking(move || auth::hash_password(&password))
+        .await
+        .ok()
+        .and_then(Result::ok)
+        .ok_or_else(|| {
+            tracing::error!("Failed to hash password");
+            internal_error()
+        })
+}
+
+/// The same 401 for an unknown email and a wrong password
+pub(crate) fn invalid_credentials() -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ErrorResponse {
+            error: "invalid_credentials".to_string(),
+            message: "Email or password is incorrect".to_string(),
+        }),
+    )
+}
+
+fn internal_error() -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: "internal_error".to_string(),
+            message: "An internal error occurred".to_string(),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_validation() {
+        assert_eq!(normalize_email("  Jo@Example.COM "), "jo@example.com");
+
+        for email in ["jo@example.com", "jo.smith+quotes@shop.co.uk"] {
+            assert!(is_valid_email(email), "{}", email);
+        }
+        for email in [
+            "",
+            "jo",
+            "@example.com",
+            "jo@example",
+            "jo@@example.com",
+            "jo @x.com",
+        ] {
+            assert!(!is_valid_email(email), "{}", email);
+        }
+    }
+}
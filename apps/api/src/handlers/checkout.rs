@@ -1,52 +1,69 @@
 use axum::{extract::State, http::StatusCode, Json};
-use serde::{Deserialize, Serialize};
-use stripe::{
-    CheckoutSession, CheckoutSessionMode, Client, CreateCheckoutSession,
-    CreateCheckoutSessionLineItems, CreateCheckoutSessionLineItemsPriceData,
-    CreateCheckoutSessionLineItemsPriceDataProductData, Currency,
+use chrono::Utc;
+use quote_core::{
+    price_quote_with_lead_time, Currency, CustomerType, Money, PriceBook, PricedQuote,
 };
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
 use utoipa::ToSchema;
+use uuid::Uuid;
 
+use crate::auth::{unauthorized, AuthCustomer, OptionalCustomer};
+use crate::db::{
+    self,
+    invoices::NewInvoice,
+    orders::{NewOrder, NewOrderItem},
+    payments::NewPayment,
+};
+use crate::handlers::database_error;
+use crate::invoices::BANK_TRANSFER;
+use crate::models::{Invoice, OrderStatus, PaymentKind, StoredQuote};
+use crate::payments::{CheckoutItem, CheckoutRequest};
 use crate::AppState;
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateCheckoutSessionRequest {
-    /// Quote ID for tracking
-    pub quote_id: String,
-    /// Total amount in cents (e.g., 5000 = $50.00)
-    pub total_amount: i64,
-    /// Currency code (e.g., "usd") - TODO: Use for multi-currency support
-    #[allow(dead_code)]
+    /// Quote ID returned by `POST /api/quotes`
+    pub quote_id: Uuid,
+    /// Currency code (e.g., "eur"); must be the currency the quote was priced in
     pub currency: String,
-    /// Customer email
+    /// Customer email; defaults to the account email when signed in
     pub customer_email: Option<String>,
     /// Success URL to redirect after payment
     pub success_url: Option<String>,
     /// Cancel URL to redirect if payment cancelled
     pub cancel_url: Option<String>,
-    /// Quote details for line items
-    pub quote_details: QuoteDetails,
-}
-
-#[derive(Debug, Deserialize, Serialize, ToSchema)]
-pub struct QuoteDetails {
-    pub base_price: i64,
-    pub prep_surcharge: i64,
-    pub rush_surcharge: i64,
-    pub material: String,
-    pub prep_level: String,
-    pub quantity: i32,
 }
 
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateCheckoutSessionResponse {
     /// Stripe checkout session ID
     pub session_id: String,
     /// Checkout URL to redirect user to
     pub url: String,
+    /// Amount charged now, in minor units (cents), when the order takes a
+    /// deposit; the balance is due once the parts are ready for pickup
+    pub deposit_amount: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateInvoiceCheckoutRequest {
+    /// Quote ID returned by `POST /api/quotes`; the quote must be for a business customer
+    pub quote_id: Uuid,
+    /// Currency code (e.g., "eur"); must be the currency the quote was priced in
+    pub currency: String,
+    /// Where the invoice is sent; defaults to the account email when signed in
+    pub customer_email: Option<String>,
 }
 
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvoiceCheckoutResponse {
+    pub order_id: Uuid,
+    /// Number to quote as the transfer reference, bank details and due date
+    pub invoice: Invoice,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
     pub message: String,
@@ -55,7 +72,14 @@ pub struct ErrorResponse {
 /// Create Stripe Checkout Session
 ///
 /// Creates a Stripe Checkout session for processing the powder coating quote payment.
+/// The quote is re-priced from its stored input, so the client only sends the quote ID.
+/// A price agreed with staff is charged as a single line in place of the quoted lines.
+/// Orders at or above the price book's deposit threshold are charged only the
+/// deposit now; a balance payment link follows when the parts are ready for pickup.
 /// Returns a session ID and checkout URL to redirect the user to complete payment.
+/// Quotes made from an account can only be checked out by that account; the
+/// order is kept in the history of the quote's account, or of the signed-in
+/// customer for anonymous quotes.
 #[utoipa::path(
     post,
     path = "/api/checkout/create-session",
@@ -63,12 +87,19 @@ pub struct ErrorResponse {
     responses(
         (status = 200, description = "Checkout session created successfully", body = CreateCheckoutSessionResponse),
         (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Quote belongs to an account; sign in to check it out", body = ErrorResponse),
+        (status = 403, description = "Quote belongs to another account", body = ErrorResponse),
+        (status = 404, description = "Quote not found", body = ErrorResponse),
+        (status = 409, description = "Quote price no longer matches", body = ErrorResponse),
+        (status = 410, description = "Quote expired", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
+    security((), ("bearer" = [])),
     tag = "checkout"
 )]
 pub async fn create_checkout_session(
     State(state): State<AppState>,
+    OptionalCustomer(customer): OptionalCustomer,
     Json(payload): Json<CreateCheckoutSessionRequest>,
 ) -> Result<Json<CreateCheckoutSessionResponse>, (StatusCode, Json<ErrorResponse>)> {
     tracing::info!(
@@ -76,134 +107,662 @@ pub async fn create_checkout_session(
         payload.quote_id
     );
 
-    // Validate amount
-    if payload.total_amount <= 0 {
+    let PreparedOrder {
+        quote,
+        output,
+        book,
+        customer_id,
+        currency,
+        total_amount,
+        items,
+    } = prepare_order(&state, customer, payload.quote_id, &payload.currency).await?;
+
+    // Large orders pay a share now; the order keeps its full itemized lines
+    let deposit = book.deposit_for(quote.amount_due());
+    let charged_items = match deposit {
+        Some(deposit) => vec![CheckoutItem {
+            name: format!(
+                "Powder Coating - Deposit ({}%)",
+                (deposit.amount as f64 * 100.0 / total_amount as f64).round()
+            ),
+            description: Some(format!(
+                "Of {} total; balance of {} due when ready for pickup",
+                quote.amount_due(),
+                Money::new(total_amount - deposit.amount, currency)
+            )),
+            amount: deposit.amount,
+        }],
+        None => items.clone(),
+    };
+
+    // Signed-in customers get receipts at their account email by default
+    let customer_email = customer_email(&state, payload.customer_email, customer_id).await?;
+
+    let (success_url, cancel_url) = return_urls(payload.success_url, payload.cancel_url);
+
+    // Add metadata for tracking
+    let order_id = Uuid::new_v4();
+    let payment_id = Uuid::new_v4();
+    let payment_kind = match deposit {
+        Some(_) => PaymentKind::Deposit,
+        None => PaymentKind::Full,
+    };
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert("order_id".to_string(), order_id.to_string());
+    metadata.insert("payment_id".to_string(), payment_id.to_string());
+    metadata.insert(
+        "payment_kind".to_string(),
+        payment_kind.as_str().to_string(),
+    );
+    metadata.insert("quote_id".to_string(), quote.id.to_string());
+    metadata.insert("lines".to_string(), quote.input.lines.len().to_string());
+    metadata.insert(
+        "quantity".to_string(),
+        quote
+            .input
+            .lines
+            .iter()
+            .map(|line| line.quantity)
+            .sum::<u32>()
+            .to_string(),
+    );
+    metadata.insert("total_amount".to_string(), total_amount.to_string());
+    metadata.insert("currency".to_string(), currency.code().to_string());
+    if let Some(deposit) = deposit {
+        metadata.insert("deposit_amount".to_string(), deposit.amount.to_string());
+    }
+    if quote.price_override.is_some() {
+        metadata.insert(
+            "quoted_amount".to_string(),
+            quote.total_amount().to_string(),
+        );
+    }
+    if let Some(vat) = &output.vat {
+        metadata.insert("net_amount".to_string(), vat.net.amount.to_string());
+        metadata.insert("vat_amount".to_string(), vat.vat.amount.to_string());
+        metadata.insert("vat_rate".to_string(), vat.rate.to_string());
+        metadata.insert("vat_country".to_string(), vat.country.clone());
+        metadata.insert(
+            "vat_treatment".to_string(),
+            vat.treatment.as_str().to_string(),
+        );
+        if let Some(vat_id) = &vat.vat_id {
+            metadata.insert("customer_vat_id".to_string(), vat_id.clone());
+        }
+        if let Some(note) = &vat.note {
+            metadata.insert("vat_note".to_string(), note.clone());
+        }
+    }
+
+    let session = state
+        .payments
+        .create_checkout_session(CheckoutRequest {
+            currency,
+            items: charged_items,
+            customer_email: customer_email.clone(),
+            success_url,
+            cancel_url,
+            metadata,
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create checkout session: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "stripe_error".to_string(),
+                    message: format!("Failed to create checkout session: {}", e),
+                }),
+            )
+        })?;
+
+    tracing::info!("Created checkout session: {}", session.id);
+
+    // Record the order together with its items and the pending payment
+    let session_id = session.id;
+    let mut tx = state.db.begin().await.map_err(database_error)?;
+
+    record_order(
+        &mut tx,
+        &NewOrder {
+            id: order_id,
+            quote_id: quote.id,
+            status: OrderStatus::AwaitingPayment,
+            customer_email: customer_email.as_deref(),
+            customer_id,
+            total_amount,
+            deposit_amount: deposit.map(|deposit| deposit.amount),
+            currency: currency.code(),
+            stripe_checkout_session_id: Some(&session_id),
+        },
+        &items,
+    )
+    .await
+    .map_err(database_error)?;
+
+    db::payments::insert_payment(
+        &mut tx,
+        &NewPayment {
+            id: payment_id,
+            order_id,
+            kind: payment_kind,
+            provider: state.payments.name(),
+            provider_reference: &session_id,
+            checkout_url: Some(&session.url),
+            amount: deposit.map_or(total_amount, |deposit| deposit.amount),
+            currency: currency.code(),
+            status: "pending",
+        },
+    )
+    .await
+    .map_err(database_error)?;
+
+    tx.commit().await.map_err(database_error)?;
+
+    tracing::info!("Created order {} for quote {}", order_id, quote.id);
+
+    Ok(Json(CreateCheckoutSessionResponse {
+        session_id,
+        url: session.url,
+        deposit_amount: deposit.map(|deposit| deposit.amount),
+    }))
+}
+
+/// Check Out With Invoice
+///
+/// Places an order paid by bank transfer instead of at checkout, for quotes
+/// made for a business customer. The quote is checked and re-priced as for
+/// card checkout. The order awaits payment until staff record the transfer;
+/// the returned invoice carries the number to quote as the transfer
+/// reference, the bank details and the due date. The whole amount is
+/// invoiced, large orders included.
+#[utoipa::path(
+    post,
+    path = "/api/checkout/invoice",
+    request_body = CreateInvoiceCheckoutRequest,
+    responses(
+        (status = 201, description = "Order placed and invoice issued", body = InvoiceCheckoutResponse),
+        (status = 400, description = "Invalid request, no email, or not a business quote", body = ErrorResponse),
+        (status = 401, description = "Quote belongs to an account; sign in to check it out", body = ErrorResponse),
+        (status = 403, description = "Quote belongs to another account", body = ErrorResponse),
+        (status = 404, description = "Quote not found", body = ErrorResponse),
+        (status = 409, description = "Quote price no longer matches", body = ErrorResponse),
+        (status = 410, description = "Quote expired", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Paying by invoice is not set up", body = ErrorResponse)
+    ),
+    security((), ("bearer" = [])),
+    tag = "checkout"
+)]
+pub async fn create_invoice_checkout(
+    State(state): State<AppState>,
+    OptionalCustomer(customer): OptionalCustomer,
+    Json(payload): Json<CreateInvoiceCheckoutRequest>,
+) -> Result<(StatusCode, Json<InvoiceCheckoutResponse>), (StatusCode, Json<ErrorResponse>)> {
+    tracing::info!("Creating invoice order for quote_id: {}", payload.quote_id);
+
+    let Some(settings) = state.invoicing.clone() else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "invoicing_unavailable".to_string(),
+                message: "Paying by invoice is not available, please pay by card".to_string(),
+            }),
+        ));
+    };
+
+    let PreparedOrder {
+        quote,
+        customer_id,
+        currency,
+        total_amount,
+        items,
+        ..
+    } = prepare_order(&state, customer, payload.quote_id, &payload.currency).await?;
+
+    let business = quote
+        .input
+        .customer
+        .as_ref()
+        .is_some_and(|customer| customer.customer_type == CustomerType::Business);
+    if !business {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: "invalid_amount".to_string(),
-                message: "Total amount must be greater than zero".to_string(),
+                error: "invoice_not_available".to_string(),
+                message: "Paying by invoice is for business customers; quote as a business or pay by card"
+                    .to_string(),
             }),
         ));
     }
 
-    // Set default URLs if not provided
-    let success_url = payload.success_url.unwrap_or_else(|| {
-        format!(
-            "{}/checkout/success?session_id={{CHECKOUT_SESSION_ID}}",
-            std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string())
-        )
-    });
+    let customer_email = customer_email(&state, payload.customer_email, customer_id)
+        .await?
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "email_required".to_string(),
+                    message: "An email is required to send the invoice to".to_string(),
+                }),
+            )
+        })?;
+
+    let order_id = Uuid::new_v4();
+    let payment_id = Uuid::new_v4();
+    let issued_on = Utc::now().date_naive();
+    let mut tx = state.db.begin().await.map_err(database_error)?;
+
+    let number = db::invoices::next_invoice_number(&mut tx, issued_on)
+        .await
+        .map_err(database_error)?;
+
+    record_order(
+        &mut tx,
+        &NewOrder {
+            id: order_id,
+            quote_id: quote.id,
+            status: OrderStatus::AwaitingPayment,
+            customer_email: Some(&customer_email),
+            customer_id,
+            total_amount,
+            deposit_amount: None,
+            currency: currency.code(),
+            stripe_checkout_session_id: None,
+        },
+        &items,
+    )
+    .await
+    .map_err(database_error)?;
+
+    db::payments::insert_payment(
+        &mut tx,
+        &NewPayment {
+            id: payment_id,
+            order_id,
+            kind: PaymentKind::Full,
+            provider: BANK_TRANSFER,
+            provider_reference: &number,
+            checkout_url: None,
+            amount: total_amount,
+            currency: currency.code(),
+            status: "pending",
+        },
+    )
+    .await
+    .map_err(database_error)?;
+
+    let invoice = db::invoices::insert_invoice(
+        &mut tx,
+        &NewInvoice {
+            order_id,
+            payment_id,
+            number: &number,
+            amount: total_amount,
+            currency: currency.code(),
+            customer_email: &customer_email,
+            bank: &settings.bank,
+            issued_on,
+            due_on: settings.due_on(issued_on),
+        },
+    )
+    .await
+    .map_err(database_error)?;
 
-    let cancel_url = payload.cancel_url.unwrap_or_else(|| {
-        format!(
-            "{}/checkout/cancel",
-            std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string())
+    tx.commit().await.map_err(database_error)?;
+
+    tracing::info!(
+        "Created order {} for quote {} with invoice {}",
+        order_id,
+        quote.id,
+        invoice.number
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(InvoiceCheckoutResponse { order_id, invoice }),
+    ))
+}
+
+/// Insert an order together with its priced lines
+async fn record_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order: &NewOrder<'_>,
+    items: &[CheckoutItem],
+) -> Result<(), sqlx::Error> {
+    db::orders::insert_order(tx, order).await?;
+
+    for item in items {
+        db::orders::insert_order_item(
+            tx,
+            order.id,
+            &NewOrderItem {
+                name: &item.name,
+                description: item.description.as_deref(),
+                quantity: 1,
+                unit_amount: item.amount,
+            },
         )
-    });
+        .await?;
+    }
 
-    // Create Stripe checkout session
-    let client = Client::new(state.stripe_secret_key);
+    Ok(())
+}
 
-    // Build line items
-    let mut line_items = Vec::new();
+/// A quote checked and re-priced for checkout, with the lines its order records
+pub(crate) struct PreparedOrder<'a> {
+    pub quote: StoredQuote,
+    pub output: PricedQuote,
+    /// Book the quote was priced with
+    pub book: &'a PriceBook,
+    /// Account the order belongs to: the quote's, or the signed-in customer's
+    pub customer_id: Option<Uuid>,
+    pub currency: Currency,
+    /// Gross amount due, VAT included, or the price agreed with staff
+    pub total_amount: i64,
+    /// Priced lines; they become both payment line items and order items
+    pub items: Vec<CheckoutItem>,
+}
 
-    // Base price
-    line_items.push(CreateCheckoutSessionLineItems {
-        price_data: Some(CreateCheckoutSessionLineItemsPriceData {
-            currency: Currency::USD,
-            unit_amount: Some(payload.quote_details.base_price),
-            product_data: Some(CreateCheckoutSessionLineItemsPriceDataProductData {
-                name: format!(
-                    "Powder Coating - {} ({})",
-                    payload.quote_details.material, payload.quote_details.prep_level
-                ),
-                description: Some(format!("Quantity: {}", payload.quote_details.quantity)),
-                ..Default::default()
-            }),
-            ..Default::default()
-        }),
-        quantity: Some(1),
-        ..Default::default()
-    });
-
-    // Prep surcharge if applicable
-    if payload.quote_details.prep_surcharge > 0 {
-        line_items.push(CreateCheckoutSessionLineItems {
-            price_data: Some(CreateCheckoutSessionLineItemsPriceData {
-                currency: Currency::USD,
-                unit_amount: Some(payload.quote_details.prep_surcharge),
-                product_data: Some(CreateCheckoutSessionLineItemsPriceDataProductData {
-                    name: "Surface Preparation Surcharge".to_string(),
-                    ..Default::default()
+/// Check that `customer` may check out a quote in `currency_code` and that
+/// it still prices as quoted, and build the lines of its order
+pub(crate) async fn prepare_order<'a>(
+    state: &'a AppState,
+    customer: Option<AuthCustomer>,
+    quote_id: Uuid,
+    currency_code: &str,
+) -> Result<PreparedOrder<'a>, (StatusCode, Json<ErrorResponse>)> {
+    let quote = db::quotes::get_quote(&state.db, quote_id)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "quote_not_found".to_string(),
+                    message: format!("Quote {} does not exist", quote_id),
+                }),
+            )
+        })?;
+
+    match (quote.customer_id, customer) {
+        (Some(_), None) => {
+            return Err(unauthorized(
+                "Quote belongs to an account, sign in to check it out",
+            ))
+        }
+        (Some(owner), Some(customer)) if owner != customer.id => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse {
+                    error: "forbidden".to_string(),
+                    message: "Quote belongs to another account".to_string(),
                 }),
-                ..Default::default()
+            ));
+        }
+        _ => {}
+    }
+    let customer_id = quote.customer_id.or(customer.map(|customer| customer.id));
+
+    if quote.expires_at <= Utc::now() {
+        return Err((
+            StatusCode::GONE,
+            Json(ErrorResponse {
+                error: "quote_expired".to_string(),
+                message: "Quote has expired, please request a new quote".to_string(),
             }),
-            quantity: Some(1),
-            ..Default::default()
-        });
+        ));
+    }
+
+    let currency: Currency = currency_code.parse().map_err(|message| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_currency".to_string(),
+                message,
+            }),
+        )
+    })?;
+
+    if currency != quote.output.currency {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "currency_mismatch".to_string(),
+                message: format!(
+                    "Quote is priced in {}, not {}; request a quote in {}",
+                    quote.output.currency, currency, currency
+                ),
+            }),
+        ));
     }
 
-    // Rush surcharge if applicable
-    if payload.quote_details.rush_surcharge > 0 {
-        line_items.push(CreateCheckoutSessionLineItems {
-            price_data: Some(CreateCheckoutSessionLineItemsPriceData {
-                currency: Currency::USD,
-                unit_amount: Some(payload.quote_details.rush_surcharge),
-                product_data: Some(CreateCheckoutSessionLineItemsPriceDataProductData {
-                    name: "Rush Order Surcharge (+50%)".to_string(),
-                    ..Default::default()
+    // Never trust stored amounts blindly: re-price from the stored input with
+    // the book and lead time the quote was priced with, and refuse the quote
+    // if the result no longer matches what the customer saw
+    let book = quote_price_book(state, &quote).ok_or_else(|| {
+        tracing::warn!(
+            "Quote {} was priced with unknown price book {:?}",
+            quote.id,
+            quote.output.price_book_version
+        );
+        (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: "price_book_unavailable".to_string(),
+                message: "Quote was priced with a retired price book, please request a new quote"
+                    .to_string(),
+            }),
+        )
+    })?;
+    let output =
+        price_quote_with_lead_time(&quote.input, book, quote.output.lead_days).map_err(|e| {
+            tracing::warn!("Quote {} can no longer be priced: {}", quote.id, e);
+            (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse {
+                    error: "quote_changed".to_string(),
+                    message: format!(
+                        "Quote can no longer be priced ({}), please request a new quote",
+                        e
+                    ),
                 }),
-                ..Default::default()
+            )
+        })?;
+    if output.amount_due() != quote.output.amount_due() {
+        tracing::warn!(
+            "Quote {} re-priced to {} cents, stored total was {} cents",
+            quote.id,
+            output.amount_due().amount,
+            quote.total_amount()
+        );
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: "quote_changed".to_string(),
+                message: "Quote price no longer matches, please request a new quote".to_string(),
             }),
-            quantity: Some(1),
-            ..Default::default()
-        });
+        ));
     }
 
-    // Create the session
-    let mut params = CreateCheckoutSession::new();
-    params.line_items = Some(line_items);
-    params.mode = Some(CheckoutSessionMode::Payment);
-    params.success_url = Some(&success_url);
-    params.cancel_url = Some(&cancel_url);
+    // The customer pays the gross amount, VAT included, or the price agreed with staff
+    let total_amount = quote.amount_due().amount;
 
-    if let Some(email) = &payload.customer_email {
-        params.customer_email = Some(email);
+    if total_amount <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_amount".to_string(),
+                message: "Total amount must be greater than zero".to_string(),
+            }),
+        ));
     }
 
-    // Add metadata for tracking
-    let mut metadata = std::collections::HashMap::new();
-    metadata.insert("quote_id".to_string(), payload.quote_id.clone());
-    metadata.insert(
-        "material".to_string(),
-        payload.quote_details.material.clone(),
-    );
-    metadata.insert(
-        "quantity".to_string(),
-        payload.quote_details.quantity.to_string(),
-    );
-    params.metadata = Some(metadata);
+    // Build the priced lines once; they become both Stripe line items and order items
+    let multi_line = quote.input.lines.len() > 1;
+    let mut items = Vec::new();
+    for (i, (line, priced)) in quote.input.lines.iter().zip(&output.lines).enumerate() {
+        // Name the part on multi-line orders so its items can be told apart
+        let part = match (&line.reference, multi_line) {
+            (Some(reference), _) => format!("{}: ", reference),
+            (None, true) => format!("Part {}: ", i + 1),
+            (None, false) => String::new(),
+        };
 
-    // Create session via Stripe API
-    match CheckoutSession::create(&client, params).await {
-        Ok(session) => {
-            tracing::info!("Created Stripe checkout session: {}", session.id);
+        // Stripe has no negative line items, so the volume discount comes off the base line
+        let mut description = format!("Quantity: {}, RAL {}", line.quantity, line.color);
+        if let Some(tier) = &priced.quantity_tier {
+            description.push_str(&format!(
+                ", incl. {}% volume discount ({}+ parts)",
+                (tier.discount * 10_000.0).round() / 100.0,
+                tier.min_quantity
+            ));
+        }
+        items.push(CheckoutItem {
+            name: format!(
+                "{}Powder Coating - {:?} ({:?})",
+                part, line.material, line.prep_level
+            ),
+            description: Some(description),
+            amount: priced
+                .base_price
+                .amount
+                .saturating_sub(priced.volume_discount.amount),
+        });
 
-            Ok(Json(CreateCheckoutSessionResponse {
-                session_id: session.id.to_string(),
-                url: session.url.unwrap_or_default(),
-            }))
+        // Prep surcharge if applicable
+        if priced.prep_surcharge.is_positive() {
+            items.push(CheckoutItem {
+                name: format!("{}Surface Preparation Surcharge", part),
+                description: None,
+                amount: priced.prep_surcharge.amount,
+            });
         }
-        Err(e) => {
-            tracing::error!("Failed to create Stripe checkout session: {:?}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "stripe_error".to_string(),
-                    message: format!("Failed to create checkout session: {}", e),
-                }),
-            ))
+
+        // Color surcharge for special finishes and powders not in stock
+        if priced.color_surcharge.is_positive() {
+            items.push(CheckoutItem {
+                name: format!("{}Color & Finish Surcharge", part),
+                description: Some(format!("RAL {}, {:?} finish", line.color, line.finish)),
+                amount: priced.color_surcharge.amount,
+            });
+        }
+
+        // One line per additional coat: primer, second color or clear
+        for coat in &priced.coats {
+            let mut description = format!("{:.2} m²", coat.area_m2);
+            if let Some(color) = &coat.color {
+                description.push_str(&format!(", RAL {}, {:?} finish", color, coat.finish));
+            }
+            if coat.masking.is_positive() {
+                description.push_str(", masked");
+            }
+            description.push_str(", incl. oven cycle");
+
+            items.push(CheckoutItem {
+                name: format!("{}{} Coat", part, coat.kind.label()),
+                description: Some(description),
+                amount: coat.total.amount,
+            });
+        }
+
+        // Rush surcharge if applicable
+        if priced.rush_surcharge.is_positive() {
+            items.push(CheckoutItem {
+                name: format!(
+                    "{}Rush Order Surcharge (+{}%)",
+                    part,
+                    (output.rush_rate * 100.0).round()
+                ),
+                description: None,
+                amount: priced.rush_surcharge.amount,
+            });
         }
     }
+
+    // Setup and color changes are charged once for the whole order
+    for fee in &output.batch_fees {
+        items.push(CheckoutItem {
+            name: format!("{} Fee", fee.kind.label()),
+            description: (fee.count > 1).then(|| format!("{} times", fee.count)),
+            amount: fee.amount.amount,
+        });
+    }
+
+    // VAT as its own line so Stripe charges exactly the quoted gross amount
+    if let Some(vat) = output.vat.as_ref().filter(|vat| vat.vat.is_positive()) {
+        items.push(CheckoutItem {
+            name: format!("VAT {}% ({})", vat.rate_percent(), vat.country),
+            description: Some(format!("On net amount {}", vat.net)),
+            amount: vat.vat.amount,
+        });
+    }
+
+    // An agreed price replaces the itemized lines, which no longer add up to it
+    if let Some(agreed) = &quote.price_override {
+        items = vec![CheckoutItem {
+            name: "Powder Coating - Agreed Price".to_string(),
+            description: Some(format!("Quoted at {}", quote.output.amount_due())),
+            amount: agreed.amount.amount,
+        }];
+    }
+
+    Ok(PreparedOrder {
+        quote,
+        output,
+        book,
+        customer_id,
+        currency,
+        total_amount,
+        items,
+    })
+}
+
+/// The email an order is confirmed to: the one given, or the account's
+pub(crate) async fn customer_email(
+    state: &AppState,
+    email: Option<String>,
+    customer_id: Option<Uuid>,
+) -> Result<Option<String>, (StatusCode, Json<ErrorResponse>)> {
+    match (email, customer_id) {
+        (Some(email), _) => Ok(Some(email)),
+        (None, Some(id)) => Ok(db::customers::get_customer(&state.db, id)
+            .await
+            .map_err(database_error)?
+            .map(|account| account.email)),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Where the payment provider sends the customer back to, defaulting to the
+/// storefront's checkout pages
+pub(crate) fn return_urls(
+    success_url: Option<String>,
+    cancel_url: Option<String>,
+) -> (String, String) {
+    let frontend_url =
+        || std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
+
+    (
+        success_url.unwrap_or_else(|| {
+            format!(
+                "{}/checkout/success?session_id={{CHECKOUT_SESSION_ID}}",
+                frontend_url()
+            )
+        }),
+        cancel_url.unwrap_or_else(|| format!("{}/checkout/cancel", frontend_url())),
+    )
+}
+
+/// The price book a stored quote was priced with
+///
+/// Quotes stored before price books were versioned carry no version; they
+/// fall back to the book that was in effect on their creation date.
+fn quote_price_book<'a>(state: &'a AppState, quote: &StoredQuote) -> Option<&'a PriceBook> {
+    if quote.output.price_book_version.is_empty() {
+        let created = quote.created_at.format("%Y-%m-%d").to_string();
+        state.price_books.effective_at(&created)
+    } else {
+        state.price_books.get(&quote.output.price_book_version)
+    }
 }
@@ -0,0 +1,18 @@
+use axum::Json;
+use quote_core::{ral_catalog, RalColor};
+
+/// List Colors
+///
+/// The RAL Classic catalog quotes accept, with the finishes each powder is
+/// stocked in. Other finishes are ordered in and carry a surcharge.
+#[utoipa::path(
+    get,
+    path = "/api/colors",
+    responses(
+        (status = 200, description = "RAL Classic catalog", body = [RalColor])
+    ),
+    tag = "pricing"
+)]
+pub async fn list_colors() -> Json<Vec<RalColor>> {
+    Json(ral_catalog())
+}
@@ -1,3 +1,25 @@
+pub mod account;
+pub mod admin;
+pub mod auth;
 pub mod checkout;
+pub mod colors;
 pub mod health;
+pub mod price_books;
+pub mod quotes;
 pub mod webhooks;
+
+use axum::{http::StatusCode, Json};
+
+use checkout::ErrorResponse;
+
+/// Map a database failure to a 500 response, logging the cause
+pub(crate) fn database_error(e: sqlx::Error) -> (StatusCode, Json<ErrorResponse>) {
+    tracing::error!("Database error: {:?}", e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: "database_error".to_string(),
+            message: "A database error occurred".to_string(),
+        }),
+    )
+}
@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use quote_core::PriceBook;
+
+use crate::handlers::checkout::ErrorResponse;
+use crate::AppState;
+
+/// List Price Books
+///
+/// All configured price books, oldest first. The last one already in effect
+/// prices new quotes.
+#[utoipa::path(
+    get,
+    path = "/api/price-books",
+    responses(
+        (status = 200, description = "Configured price books", body = [PriceBook])
+    ),
+    tag = "pricing"
+)]
+pub async fn list_price_books(State(state): State<AppState>) -> Json<Vec<PriceBook>> {
+    Json(state.price_books.books().to_vec())
+}
+
+/// Get Price Book
+///
+/// Returns a price book by version, e.g. the `price_book_version` recorded on
+/// a quote, so its prices can be explained later.
+#[utoipa::path(
+    get,
+    path = "/api/price-books/{version}",
+    params(
+        ("version" = String, Path, description = "Price book version")
+    ),
+    responses(
+        (status = 200, description = "Price book found", body = PriceBook),
+        (status = 404, description = "Unknown price book version", body = ErrorResponse)
+    ),
+    tag = "pricing"
+)]
+pub async fn get_price_book(
+    State(state): State<AppState>,
+    Path(version): Path<String>,
+) -> Result<Json<PriceBook>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .price_books
+        .get(&version)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "price_book_not_found".to_string(),
+                    message: format!("Price book {} does not exist", version),
+                }),
+            )
+        })
+}
@@ -0,0 +1,271 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use quote_core::{price_quote_with_lead_time, FieldError, Quote, QuoteError, QuoteInput};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::auth::OptionalCustomer;
+use crate::capacity::{completion_date, shop_load};
+use crate::db;
+use crate::handlers::{checkout::ErrorResponse, database_error};
+use crate::models::StoredQuote;
+use crate::AppState;
+
+/// How long a quote can be taken to checkout
+const QUOTE_VALIDITY_DAYS: i64 = 30;
+
+/// Body of `POST /api/quotes`: several parts, or a single part as before
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum QuoteRequest {
+    Lines(Quote),
+    Part(QuoteInput),
+}
+
+/// Error body for quote input that fails validation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidationErrorResponse {
+    pub error: String,
+    pub message: String,
+    /// One entry per invalid field
+    pub field_errors: Vec<FieldError>,
+}
+
+/// Create Quote
+///
+/// Prices the parts with `quote_core` and the price book in effect today, and
+/// stores the result. Setup and color change fees are charged once per quote,
+/// and rush orders are surcharged by how far they undercut the shop's current
+/// lead time. The returned quote ID is what checkout accepts. Quotes created
+/// with an access token are kept in the customer's history.
+#[utoipa::path(
+    post,
+    path = "/api/quotes",
+    request_body = QuoteRequest,
+    responses(
+        (status = 201, description = "Quote created", body = StoredQuote),
+        (status = 400, description = "Quote cannot be priced (currency, country or VAT number)", body = ErrorResponse),
+        (status = 401, description = "Access token is invalid or expired", body = ErrorResponse),
+        (status = 422, description = "Quote input failed validation", body = ValidationErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security((), ("bearer" = [])),
+    tag = "quotes"
+)]
+pub async fn create_quote(
+    State(state): State<AppState>,
+    OptionalCustomer(customer): OptionalCustomer,
+    Json(request): Json<QuoteRequest>,
+) -> Result<(StatusCode, Json<StoredQuote>), Response> {
+    // Validate before the lead time is worked out, and as sent so field
+    // errors keep their single-part names
+    let input = match request {
+        QuoteRequest::Lines(quote) => {
+            quote.validate().map_err(quote_error)?;
+            quote
+        }
+        QuoteRequest::Part(part) => {
+            part.validate().map_err(quote_error)?;
+            Quote::from(part)
+        }
+    };
+
+    let today = Utc::now().date_naive();
+    let book = state
+        .price_books
+        .effective_at(&today.to_string())
+        .ok_or_else(|| {
+            tracing::error!("No price book is effective on {}", today);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "price_book_unavailable".to_string(),
+                    message: "No price book is in effect".to_string(),
+                }),
+            )
+                .into_response()
+        })?;
+    let load = shop_load(&state.db, today)
+        .await
+        .map_err(|e| database_error(e).into_response())?;
+    let lead_days = book.lead_days(&input, &load);
+    let output = price_quote_with_lead_time(&input, book, Some(lead_days)).map_err(quote_error)?;
+
+    if !output.coated_area_m2().is_finite() || !output.total_price.is_positive() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_quote".to_string(),
+                message: "Quote input must produce a positive price".to_string(),
+            }),
+        )
+            .into_response());
+    }
+
+    let created_at = Utc::now();
+    let quote = StoredQuote {
+        id: Uuid::new_v4(),
+        input,
+        output,
+        customer_id: customer.map(|customer| customer.id),
+        price_override: None,
+        created_at,
+        expires_at: created_at + Duration::days(QUOTE_VALIDITY_DAYS),
+    };
+
+    db::quotes::insert_quote(&state.db, &quote)
+        .await
+        .map_err(|e| database_error(e).into_response())?;
+    tracing::info!(
+        "Created quote {} for {}",
+        quote.id,
+        quote.output.total_price
+    );
+
+    Ok((StatusCode::CREATED, Json(quote)))
+}
+
+/// Get Quote
+///
+/// Returns a previously created quote by ID. Quotes made from an account are
+/// only returned to that account; to anyone else they do not exist.
+#[utoipa::path(
+    get,
+    path = "/api/quotes/{quote_id}",
+    params(
+        ("quote_id" = Uuid, Path, description = "Quote ID")
+    ),
+    responses(
+        (status = 200, description = "Quote found", body = StoredQuote),
+        (status = 401, description = "Access token is invalid or expired", body = ErrorResponse),
+        (status = 404, description = "Quote not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security((), ("bearer" = [])),
+    tag = "quotes"
+)]
+pub async fn get_quote(
+    State(state): State<AppState>,
+    OptionalCustomer(customer): OptionalCustomer,
+    Path(quote_id): Path<Uuid>,
+) -> Result<Json<StoredQuote>, (StatusCode, Json<ErrorResponse>)> {
+    db::quotes::get_quote(&state.db, quote_id)
+        .await
+        .map_err(database_error)?
+        // Account quotes carry the customer's country and VAT number
+        .filter(|quote| {
+            quote
+                .customer_id
+                .is_none_or(|owner| customer.is_some_and(|customer| customer.id == owner))
+        })
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "quote_not_found".to_string(),
+                    message: format!("Quote {} does not exist", quote_id),
+                }),
+            )
+        })
+}
+
+/// When a quote's parts could be ready at the earliest
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuoteAvailability {
+    pub quote_id: Uuid,
+    /// Oven time the parts need, absent when the price book does not track capacity
+    pub oven_hours: Option<f64>,
+    /// Days from today until the order could be completed
+    pub lead_days: u32,
+    /// Earliest completion date if the order is paid today
+    pub earliest_completion: NaiveDate,
+    /// Completion date the customer asked for with the quote's turnaround
+    pub requested_completion: NaiveDate,
+}
+
+/// Get Quote Availability
+///
+/// Schedules the quote's parts into the oven time not yet booked by paid
+/// orders and returns the earliest completion date. The answer changes as
+/// other orders are paid, so it is not stored with the quote. Account quotes
+/// are only scheduled for their account, as with `GET /api/quotes/{quote_id}`.
+#[utoipa::path(
+    get,
+    path = "/api/quotes/{quote_id}/availability",
+    params(
+        ("quote_id" = Uuid, Path, description = "Quote ID")
+    ),
+    responses(
+        (status = 200, description = "Earliest completion date", body = QuoteAvailability),
+        (status = 401, description = "Access token is invalid or expired", body = ErrorResponse),
+        (status = 404, description = "Quote not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security((), ("bearer" = [])),
+    tag = "quotes"
+)]
+pub async fn get_quote_availability(
+    State(state): State<AppState>,
+    customer: OptionalCustomer,
+    Path(quote_id): Path<Uuid>,
+) -> Result<Json<QuoteAvailability>, (StatusCode, Json<ErrorResponse>)> {
+    let Json(quote) = get_quote(State(state.clone()), customer, Path(quote_id)).await?;
+
+    let today = Utc::now().date_naive();
+    let book = state
+        .price_books
+        .effective_at(&today.to_string())
+        .ok_or_else(|| {
+            tracing::error!("No price book is effective on {}", today);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "price_book_unavailable".to_string(),
+                    message: "No price book is in effect".to_string(),
+                }),
+            )
+        })?;
+    let load = shop_load(&state.db, today).await.map_err(database_error)?;
+    let lead_days = book.lead_days(&quote.input, &load);
+
+    Ok(Json(QuoteAvailability {
+        quote_id,
+        oven_hours: book
+            .capacity
+            .as_ref()
+            .map(|capacity| capacity.oven_hours(&quote.input)),
+        lead_days,
+        earliest_completion: completion_date(today, lead_days),
+        requested_completion: completion_date(today, quote.input.turnaround_days),
+    }))
+}
+
+/// Map a pricing failure to a 422 (validation) or 400 response
+fn quote_error(e: QuoteError) -> Response {
+    match e {
+        QuoteError::Invalid(field_errors) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ValidationErrorResponse {
+                error: "invalid_input".to_string(),
+                message: "Quote input failed validation".to_string(),
+                field_errors,
+            }),
+        )
+            .into_response(),
+        e => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.code().to_string(),
+                message: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -3,89 +3,28 @@ use axum::{
     http::{HeaderMap, StatusCode},
     Json,
 };
-use hmac::{Hmac, Mac};
 use serde::Serialize;
-use sha2::Sha256;
 use utoipa::ToSchema;
+use uuid::Uuid;
 
+use crate::capacity;
+use crate::db;
+use crate::models::{Order, OrderStatus, Payment, PaymentKind, RefundStatus};
+use crate::refunds::{self, ProviderRefundEvent};
+use crate::signature::SignatureError;
 use crate::AppState;
 
-type HmacSha256 = Hmac<Sha256>;
-
 #[derive(Debug, Serialize, ToSchema)]
 pub struct WebhookResponse {
     pub received: bool,
 }
 
-/// Verify Stripe webhook signature
-fn verify_signature(payload: &str, signature: &str, secret: &str) -> Result<(), StatusCode> {
-    // Parse signature header: "t=timestamp,v1=signature"
-    let mut timestamp: Option<i64> = None;
-    let mut signatures = Vec::new();
-
-    for part in signature.split(',') {
-        let parts: Vec<&str> = part.split('=').collect();
-        if parts.len() == 2 {
-            match parts[0] {
-                "t" => {
-                    timestamp = parts[1].parse::<i64>().ok();
-                }
-                "v1" => {
-                    signatures.push(parts[1]);
-                }
-                _ => {}
-            }
-        }
-    }
-
-    let timestamp = timestamp.ok_or_else(|| {
-        tracing::error!("Missing timestamp in signature");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    if signatures.is_empty() {
-        tracing::error!("No v1 signatures found");
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    // Check timestamp is within 5 minutes
-    let current_time = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    if (current_time - timestamp).abs() > 300 {
-        tracing::error!(
-            "Webhook timestamp too old: {} seconds",
-            current_time - timestamp
-        );
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    // Compute expected signature
-    let signed_payload = format!("{}.{}", timestamp, payload);
-    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).map_err(|e| {
-        tracing::error!("Invalid HMAC key: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    mac.update(signed_payload.as_bytes());
-    let expected_signature = hex::encode(mac.finalize().into_bytes());
-
-    // Compare with provided signatures
-    for sig in signatures {
-        if sig == expected_signature {
-            return Ok(());
-        }
-    }
-
-    tracing::error!("Signature verification failed");
-    Err(StatusCode::BAD_REQUEST)
-}
-
 /// Stripe Webhook Handler
 ///
 /// Receives webhook events from Stripe to handle payment status updates.
-/// Verifies webhook signatures to ensure authenticity.
+/// Verifies webhook signatures to ensure authenticity, records the event in
+/// the ledger and advances the matching order through its lifecycle.
+/// Redelivered events are acknowledged without being applied again.
 #[utoipa::path(
     post,
     path = "/api/webhooks/stripe",
@@ -98,18 +37,12 @@ fn verify_signature(payload: &str, signature: &str, secret: &str) -> Result<(),
     tag = "webhooks"
 )]
 pub async fn stripe_webhook(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     headers: HeaderMap,
     body: String,
 ) -> Result<Json<WebhookResponse>, StatusCode> {
     tracing::info!("Received Stripe webhook");
 
-    // Get webhook secret from environment
-    let webhook_secret = std::env::var("STRIPE_WEBHOOK_SECRET").map_err(|_| {
-        tracing::error!("STRIPE_WEBHOOK_SECRET not set in environment");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
     // Get Stripe signature from headers
     let signature = headers
         .get("stripe-signature")
@@ -119,8 +52,17 @@ pub async fn stripe_webhook(
             StatusCode::BAD_REQUEST
         })?;
 
-    // Verify webhook signature
-    verify_signature(&body, signature, &webhook_secret)?;
+    // Verify webhook signature against every active secret
+    state
+        .webhook_signatures
+        .verify(body.as_bytes(), signature)
+        .map_err(|e| {
+            tracing::error!("Webhook signature verification failed: {}", e);
+            match e {
+                SignatureError::NoSecrets => StatusCode::INTERNAL_SERVER_ERROR,
+                _ => StatusCode::BAD_REQUEST,
+            }
+        })?;
 
     // Parse event manually after verification
     let event: serde_json::Value = serde_json::from_str(&body).map_err(|e| {
@@ -137,71 +79,350 @@ pub async fn stripe_webhook(
     let event_id = event
         .get("id")
         .and_then(|v| v.as_str())
-        .unwrap_or("unknown");
+        .ok_or(StatusCode::BAD_REQUEST)?;
 
     tracing::info!("Verified webhook - Type: {}, ID: {}", event_type, event_id);
 
-    // Handle specific event types
+    // Stripe retries deliveries, so every event goes through the ledger first
+    let recorded = db::webhook_events::record_event(&state.db, event_id, event_type, &event)
+        .await
+        .map_err(db_failure)?;
+
+    if recorded.processed_at.is_some() {
+        tracing::info!(
+            "Duplicate webhook {} (delivery {}), already processed",
+            event_id,
+            recorded.delivery_count
+        );
+        return Ok(Json(WebhookResponse { received: true }));
+    }
+
+    handle_event(&state, event_type, &event).await?;
+
+    db::webhook_events::mark_processed(&state.db, event_id)
+        .await
+        .map_err(db_failure)?;
+
+    tracing::info!("Successfully processed webhook");
+
+    Ok(Json(WebhookResponse { received: true }))
+}
+
+/// Apply a verified Stripe event to the matching order
+///
+/// Shared by live deliveries and admin replays from the event ledger.
+pub(crate) async fn handle_event(
+    state: &AppState,
+    event_type: &str,
+    event: &serde_json::Value,
+) -> Result<(), StatusCode> {
+    let object = event
+        .get("data")
+        .and_then(|d| d.get("object"))
+        .ok_or_else(|| {
+            tracing::error!("Webhook event has no data.object");
+            StatusCode::BAD_REQUEST
+        })?;
+
     match event_type {
         "checkout.session.completed" => {
-            if let Some(session) = event.get("data").and_then(|d| d.get("object")) {
-                let session_id = session
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-                let payment_status = session
-                    .get("payment_status")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-                let customer_email = session
-                    .get("customer_details")
-                    .and_then(|d| d.get("email"))
-                    .and_then(|e| e.as_str());
-
-                tracing::info!("Checkout session completed: {}", session_id);
-                tracing::info!("Payment status: {}", payment_status);
-                tracing::info!("Customer email: {:?}", customer_email);
-
-                // TODO: Update database with order status
-                // - Create order record
-                // - Update payment status
-                // - Send confirmation email
-                tracing::warn!("TODO: Implement order creation in database");
+            let session_id = object
+                .get("id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            let payment_status = object
+                .get("payment_status")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            let customer_email = object
+                .get("customer_details")
+                .and_then(|d| d.get("email"))
+                .and_then(|e| e.as_str());
+
+            tracing::info!("Checkout session completed: {}", session_id);
+            tracing::info!("Payment status: {}", payment_status);
+            tracing::info!("Customer email: {:?}", customer_email);
+
+            // Delayed payment methods complete the session before the money arrives;
+            // payment_intent.succeeded settles those orders later
+            if payment_status != "paid" {
+                tracing::info!("Session {} completed without payment yet", session_id);
+                return Ok(());
+            }
+
+            if let Some(payment) = payment_for_session(state, session_id).await? {
+                db::payments::record_capture(
+                    &state.db,
+                    state.payments.name(),
+                    session_id,
+                    object.get("payment_intent").and_then(|v| v.as_str()),
+                    object.get("amount_total").and_then(|v| v.as_i64()),
+                )
+                .await
+                .map_err(db_failure)?;
+                apply_payment(state, &payment, OrderStatus::Paid, "succeeded").await?;
             }
         }
         "checkout.session.expired" => {
-            tracing::info!("Checkout session expired");
-            // TODO: Handle expired sessions (cleanup, notify user)
+            let session_id = object
+                .get("id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+
+            tracing::info!("Checkout session expired: {}", session_id);
+
+            if let Some(payment) = payment_for_session(state, session_id).await? {
+                apply_payment(state, &payment, OrderStatus::Expired, "expired").await?;
+            }
         }
         "payment_intent.succeeded" => {
             tracing::info!("Payment intent succeeded");
-            // TODO: Handle successful payment
+
+            if let Some(payment) = payment_for_intent(state, object).await? {
+                let intent_id = object.get("id").and_then(|v| v.as_str());
+                let received = object.get("amount_received").and_then(|v| v.as_i64());
+                if let (Some(intent_id), Some(received)) = (intent_id, received) {
+                    db::payments::record_intent_capture(&state.db, payment.id, intent_id, received)
+                        .await
+                        .map_err(db_failure)?;
+                }
+                apply_payment(state, &payment, OrderStatus::Paid, "succeeded").await?;
+            }
         }
         "payment_intent.payment_failed" => {
-            tracing::warn!("Payment intent failed");
-            // TODO: Handle failed payment (notify user, log reason)
+            let reason = object
+                .get("last_payment_error")
+                .and_then(|e| e.get("message"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown");
+
+            tracing::warn!("Payment intent failed: {}", reason);
+
+            if let Some(payment) = payment_for_intent(state, object).await? {
+                apply_payment(state, &payment, OrderStatus::PaymentFailed, "failed").await?;
+            }
+        }
+        "charge.refunded" => {
+            // Older API versions list the charge's refunds inline; newer ones
+            // only send refund.* events, handled below
+            let payment_intent = object.get("payment_intent").and_then(|v| v.as_str());
+            let refunds = object
+                .get("refunds")
+                .and_then(|r| r.get("data"))
+                .and_then(|d| d.as_array())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            tracing::info!("Charge refunded, {} refunds listed", refunds.len());
+
+            for refund in refunds {
+                record_refund(state, refund, payment_intent).await?;
+            }
+        }
+        "refund.created" | "refund.updated" | "refund.failed" | "charge.refund.updated" => {
+            record_refund(state, object, None).await?;
         }
         _ => {
             tracing::info!("Unhandled event type: {}", event_type);
         }
     }
 
-    tracing::info!("Successfully processed webhook");
+    Ok(())
+}
 
-    Ok(Json(WebhookResponse { received: true }))
+/// Apply a Stripe refund object, falling back to `payment_intent` from the
+/// charge it was listed under
+async fn record_refund(
+    state: &AppState,
+    refund: &serde_json::Value,
+    payment_intent: Option<&str>,
+) -> Result<(), StatusCode> {
+    let field = |name: &str| refund.get(name).and_then(|v| v.as_str());
+    let metadata = |name: &str| {
+        refund
+            .get("metadata")
+            .and_then(|m| m.get(name))
+            .and_then(|v| v.as_str())
+    };
+
+    let (Some(id), Some(payment_intent_id)) =
+        (field("id"), field("payment_intent").or(payment_intent))
+    else {
+        tracing::warn!("Refund event without a refund or payment intent ID");
+        return Ok(());
+    };
+
+    let event = ProviderRefundEvent {
+        id,
+        payment_intent_id,
+        amount: refund.get("amount").and_then(|v| v.as_i64()).unwrap_or(0),
+        currency: &field("currency").unwrap_or_default().to_uppercase(),
+        status: RefundStatus::from_provider(field("status").unwrap_or("pending")),
+        refund_id: metadata("refund_id").and_then(|v| Uuid::parse_str(v).ok()),
+        reason: metadata("reason"),
+    };
+
+    tracing::info!("Refund {} is {:?}", event.id, event.status);
+
+    refunds::record_provider_refund(state, &event)
+        .await
+        .map_err(db_failure)
 }
 
-// TODO: Implement event-specific handlers
-// async-stripe uses EventObject enum for type-safe event handling
-// Example implementation:
-// match event.data {
-//     stripe::EventObject::CheckoutSession(session) => {
-//         // Handle checkout session completed
-//     }
-//     stripe::EventObject::PaymentIntent(intent) => {
-//         // Handle payment intent events
-//     }
-//     _ => {
-//         // Handle other events or ignore
-//     }
-// }
+async fn payment_for_session(
+    state: &AppState,
+    session_id: &str,
+) -> Result<Option<Payment>, StatusCode> {
+    let payment =
+        db::payments::get_payment_by_reference(&state.db, state.payments.name(), session_id)
+            .await
+            .map_err(db_failure)?;
+
+    if payment.is_none() {
+        tracing::warn!("No payment found for checkout session {}", session_id);
+    }
+
+    Ok(payment)
+}
+
+/// Find the payment an intent belongs to via the metadata set at checkout
+///
+/// Intents created before payments were named in the metadata belong to
+/// the order's checkout payment.
+async fn payment_for_intent(
+    state: &AppState,
+    intent: &serde_json::Value,
+) -> Result<Option<Payment>, StatusCode> {
+    let metadata = |name: &str| {
+        intent
+            .get("metadata")
+            .and_then(|m| m.get(name))
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+    };
+
+    let payment = match (metadata("payment_id"), metadata("order_id")) {
+        (Some(payment_id), _) => db::payments::get_payment(&state.db, payment_id).await,
+        (None, Some(order_id)) => {
+            db::payments::get_checkout_payment(&state.db, order_id, state.payments.name()).await
+        }
+        (None, None) => {
+            tracing::warn!("Payment intent has no order_id metadata");
+            return Ok(None);
+        }
+    }
+    .map_err(db_failure)?;
+
+    if payment.is_none() {
+        tracing::warn!("No payment found for payment intent metadata");
+    }
+
+    Ok(payment)
+}
+
+/// Apply a payment outcome: checkout payments move their order to `next`,
+/// balance payments only record the outcome since the order is in
+/// production already
+async fn apply_payment(
+    state: &AppState,
+    payment: &Payment,
+    next: OrderStatus,
+    payment_status: &str,
+) -> Result<(), StatusCode> {
+    if payment.kind != PaymentKind::Balance {
+        let Some(order) = db::orders::get_order(&state.db, payment.order_id)
+            .await
+            .map_err(db_failure)?
+        else {
+            tracing::warn!("No order {} for payment {}", payment.order_id, payment.id);
+            return Ok(());
+        };
+        return advance_order(state, &order, payment.id, next, payment_status).await;
+    }
+
+    // Refunded or already settled balances are not reopened by late events
+    if !matches!(payment.status.as_str(), "pending" | "failed") {
+        tracing::info!(
+            "Balance payment {} is already {}",
+            payment.id,
+            payment.status
+        );
+        return Ok(());
+    }
+
+    let mut tx = state.db.begin().await.map_err(db_failure)?;
+    db::payments::update_payment_status(&mut tx, payment.id, payment_status)
+        .await
+        .map_err(db_failure)?;
+    tx.commit().await.map_err(db_failure)?;
+
+    tracing::info!(
+        "Balance payment {} of order {} is {}",
+        payment.id,
+        payment.order_id,
+        payment_status
+    );
+
+    Ok(())
+}
+
+/// Move an order to `next` and record the payment outcome alongside it
+///
+/// Paid orders also book their oven time in the same transaction.
+///
+/// Illegal transitions are logged and skipped instead of failing the webhook,
+/// since Stripe would otherwise keep retrying an event that can never apply.
+async fn advance_order(
+    state: &AppState,
+    order: &Order,
+    payment_id: Uuid,
+    next: OrderStatus,
+    payment_status: &str,
+) -> Result<(), StatusCode> {
+    if order.status == next {
+        tracing::info!("Order {} is already {}", order.id, next);
+        return Ok(());
+    }
+
+    if let Err(e) = order.status.transition_to(next) {
+        tracing::warn!("Rejected webhook update for order {}: {}", order.id, e);
+        return Ok(());
+    }
+
+    let mut tx = state.db.begin().await.map_err(db_failure)?;
+
+    let updated = db::orders::update_order_status(&mut tx, order.id, order.status, next)
+        .await
+        .map_err(db_failure)?;
+
+    if !updated {
+        // Another delivery moved the order first; let Stripe retry against the new status
+        tracing::warn!("Order {} changed status concurrently", order.id);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    db::orders::insert_status_change(&mut tx, order.id, order.status, next, None, None)
+        .await
+        .map_err(db_failure)?;
+
+    db::payments::update_payment_status(&mut tx, payment_id, payment_status)
+        .await
+        .map_err(db_failure)?;
+
+    if next == OrderStatus::Paid {
+        capacity::book_paid_order(state, &mut tx, order)
+            .await
+            .map_err(db_failure)?;
+    }
+
+    tx.commit().await.map_err(db_failure)?;
+
+    tracing::info!("Order {} moved {} -> {}", order.id, order.status, next);
+
+    Ok(())
+}
+
+fn db_failure(e: sqlx::Error) -> StatusCode {
+    tracing::error!("Database error while processing webhook: {:?}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
@@ -0,0 +1,100 @@
+//! Orders paid by bank transfer against an invoice
+//!
+//! Business customers may check out with an invoice instead of a card. The
+//! order waits for payment until staff record the transfer as received; only
+//! then is it paid and booked into the oven schedule.
+
+use std::fmt;
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::capacity;
+use crate::db;
+use crate::models::{Invoice, OrderStatus};
+use crate::AppState;
+
+/// Provider recorded on payments made by bank transfer
+pub const BANK_TRANSFER: &str = "bank_transfer";
+
+#[derive(Debug)]
+pub enum TransferError {
+    NotFound,
+    /// A transfer was recorded for the invoice already
+    AlreadyPaid,
+    /// The order no longer awaits payment, e.g. it was cancelled
+    NotAwaitingPayment(OrderStatus),
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::NotFound => f.write_str("invoice does not exist"),
+            TransferError::AlreadyPaid => f.write_str("invoice has been paid already"),
+            TransferError::NotAwaitingPayment(status) => {
+                write!(f, "order is {}, not awaiting payment", status)
+            }
+            TransferError::Database(e) => write!(f, "database error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for TransferError {
+    fn from(e: sqlx::Error) -> Self {
+        TransferError::Database(e)
+    }
+}
+
+/// Record the bank transfer for an invoice as received on `received_on`,
+/// moving its order to `paid`
+pub async fn record_transfer(
+    state: &AppState,
+    invoice_id: Uuid,
+    received_on: NaiveDate,
+    staff_id: Uuid,
+    note: Option<&str>,
+) -> Result<Invoice, TransferError> {
+    let mut tx = state.db.begin().await?;
+
+    let invoice = db::invoices::lock_invoice(&mut tx, invoice_id)
+        .await?
+        .ok_or(TransferError::NotFound)?;
+    if invoice.paid_on.is_some() {
+        return Err(TransferError::AlreadyPaid);
+    }
+
+    let order = db::orders::lock_order(&mut tx, invoice.order_id)
+        .await?
+        .ok_or(TransferError::NotFound)?;
+    if order.status.transition_to(OrderStatus::Paid).is_err() {
+        return Err(TransferError::NotAwaitingPayment(order.status));
+    }
+
+    let note = note
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Bank transfer for invoice {} received", invoice.number));
+    db::orders::update_order_status(&mut tx, order.id, order.status, OrderStatus::Paid).await?;
+    db::orders::insert_status_change(
+        &mut tx,
+        order.id,
+        order.status,
+        OrderStatus::Paid,
+        Some(staff_id),
+        Some(&note),
+    )
+    .await?;
+    db::payments::record_received(&mut tx, invoice.payment_id).await?;
+    let invoice = db::invoices::mark_paid(&mut tx, invoice.id, received_on, staff_id).await?;
+    capacity::book_paid_order(state, &mut tx, &order).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "Staff {} recorded transfer for invoice {} of order {}",
+        staff_id,
+        invoice.number,
+        order.id
+    );
+
+    Ok(invoice)
+}
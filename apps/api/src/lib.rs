@@ -0,0 +1,307 @@
+//! Powder coating shop API: customer accounts, quotes, checkout and payment
+//! webhooks
+//!
+//! The binary in `main.rs` reads the configuration and serves [`app`]; the
+//! integration tests build the same router around a fake payment provider.
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+use tower_http::cors::{Any, CorsLayer};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+pub mod auth;
+mod capacity;
+pub mod db;
+mod deposits;
+pub mod handlers;
+mod invoices;
+pub mod models;
+pub mod payments;
+pub mod pricing;
+mod refunds;
+mod routes;
+pub mod signature;
+
+use auth::TokenKeys;
+use payments::PaymentProvider;
+use signature::SignatureVerifier;
+
+#[derive(Clone)]
+pub struct AppState {
+    /// Where checkout sessions are created
+    pub payments: Arc<dyn PaymentProvider>,
+    /// Checks Stripe webhook signatures against the active webhook secrets
+    pub webhook_signatures: Arc<SignatureVerifier>,
+    pub db: sqlx::PgPool,
+    /// Signs and checks customer and staff tokens
+    pub tokens: Arc<TokenKeys>,
+    /// Price books quotes are priced and re-priced with
+    pub price_books: Arc<quote_core::PriceBookSet>,
+    /// Bank account and terms for orders paid by invoice; business
+    /// customers can only pay by card without them
+    pub invoicing: Option<Arc<models::InvoiceSettings>>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::health::health_check,
+        handlers::auth::register,
+        handlers::auth::login,
+        handlers::auth::refresh,
+        handlers::auth::logout,
+        handlers::account::get_account,
+        handlers::account::list_account_quotes,
+        handlers::account::list_account_orders,
+        handlers::quotes::create_quote,
+        handlers::quotes::get_quote,
+        handlers::quotes::get_quote_availability,
+        handlers::checkout::create_checkout_session,
+        handlers::checkout::create_invoice_checkout,
+        handlers::webhooks::stripe_webhook,
+        handlers::admin::auth::staff_login,
+        handlers::admin::staff::list_staff,
+        handlers::admin::staff::create_staff,
+        handlers::admin::orders::list_orders,
+        handlers::admin::orders::get_order,
+        handlers::admin::orders::update_order_status,
+        handlers::admin::orders::cancel_order,
+        handlers::admin::orders::request_balance_payment,
+        handlers::admin::orders::refund_order,
+        handlers::admin::invoices::record_transfer,
+        handlers::admin::invoices::list_overdue_invoices,
+        handlers::admin::quotes::override_quote_price,
+        handlers::admin::webhooks::replay_webhook,
+        handlers::price_books::list_price_books,
+        handlers::price_books::get_price_book,
+        handlers::colors::list_colors,
+    ),
+    components(
+        schemas(
+            models::StoredQuote,
+            models::Account,
+            models::Order,
+            models::OrderStatus,
+            handlers::auth::RegisterRequest,
+            handlers::auth::LoginRequest,
+            handlers::auth::RefreshRequest,
+            handlers::auth::TokenResponse,
+            quote_core::QuoteInput,
+            quote_core::QuoteOutput,
+            handlers::quotes::QuoteRequest,
+            handlers::quotes::QuoteAvailability,
+            quote_core::Quote,
+            quote_core::QuoteLine,
+            quote_core::PricedQuote,
+            quote_core::QuoteLineOutput,
+            quote_core::BatchFee,
+            quote_core::BatchFeeKind,
+            quote_core::PriceBreakdown,
+            quote_core::LineBreakdown,
+            quote_core::BreakdownStep,
+            quote_core::ReasonCode,
+            quote_core::BasisUnit,
+            quote_core::Material,
+            quote_core::PrepLevel,
+            quote_core::PartShape,
+            quote_core::Money,
+            quote_core::Currency,
+            quote_core::Customer,
+            quote_core::FieldError,
+            handlers::quotes::ValidationErrorResponse,
+            quote_core::CustomerType,
+            quote_core::VatBreakdown,
+            quote_core::VatTreatment,
+            quote_core::VatRules,
+            quote_core::PriceBook,
+            quote_core::MaterialMultipliers,
+            quote_core::PrepRates,
+            quote_core::RushRule,
+            quote_core::RushStep,
+            quote_core::Capacity,
+            quote_core::Deposit,
+            quote_core::BatchFees,
+            quote_core::QuantityTier,
+            quote_core::ColorPricing,
+            quote_core::Coat,
+            quote_core::CoatKind,
+            quote_core::CoatLine,
+            quote_core::CoatPricing,
+            quote_core::CoatRates,
+            quote_core::FinishRates,
+            quote_core::Finish,
+            quote_core::RalColor,
+            quote_core::Rounding,
+            quote_core::RoundingMode,
+            quote_core::RoundingScope,
+            handlers::checkout::CreateCheckoutSessionRequest,
+            handlers::checkout::CreateCheckoutSessionResponse,
+            handlers::checkout::CreateInvoiceCheckoutRequest,
+            handlers::checkout::InvoiceCheckoutResponse,
+            handlers::checkout::ErrorResponse,
+            handlers::webhooks::WebhookResponse,
+            models::OrderItem,
+            models::OrderStatusChange,
+            models::PriceOverride,
+            models::Payment,
+            models::PaymentKind,
+            models::Refund,
+            models::RefundStatus,
+            models::Invoice,
+            models::BankDetails,
+            models::Staff,
+            models::StaffRole,
+            handlers::admin::auth::StaffLoginRequest,
+            handlers::admin::auth::StaffTokenResponse,
+            handlers::admin::staff::CreateStaffRequest,
+            handlers::admin::orders::OrderDetail,
+            handlers::admin::orders::UpdateOrderStatusRequest,
+            handlers::admin::orders::CancelOrderRequest,
+            handlers::admin::orders::RefundOrderRequest,
+            handlers::admin::invoices::RecordTransferRequest,
+            handlers::admin::invoices::OverdueInvoice,
+            handlers::admin::quotes::PriceOverrideRequest,
+            handlers::admin::webhooks::WebhookReplayResponse,
+        )
+    ),
+    tags(
+        (name = "health", description = "Health check endpoints"),
+        (name = "auth", description = "Customer registration and sign-in"),
+        (name = "account", description = "The signed-in customer's account and history"),
+        (name = "quotes", description = "Server-side quote pricing"),
+        (name = "pricing", description = "Versioned price books"),
+        (name = "checkout", description = "Checkout and payment endpoints"),
+        (name = "webhooks", description = "Webhook handlers for external services"),
+        (name = "admin", description = "Shop staff operations, by role: owner, sales or operator")
+    ),
+    modifiers(&BearerAuth)
+)]
+struct ApiDoc;
+
+/// Declares the `bearer` scheme the `security` attributes refer to
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+    }
+}
+
+/// Staff routes, mounted at `/api/admin`; each handler checks the caller's role
+fn admin_router() -> Router<AppState> {
+    use handlers::admin;
+
+    Router::new()
+        .route("/auth/login", post(admin::auth::staff_login))
+        .route(
+            "/staff",
+            get(admin::staff::list_staff).post(admin::staff::create_staff),
+        )
+        .route("/orders", get(admin::orders::list_orders))
+        .route("/orders/:order_id", get(admin::orders::get_order))
+        .route(
+            "/orders/:order_id/status",
+            post(admin::orders::update_order_status),
+        )
+        .route(
+            "/orders/:order_id/cancel",
+            post(admin::orders::cancel_order),
+        )
+        .route(
+            "/orders/:order_id/balance-payment",
+            post(admin::orders::request_balance_payment),
+        )
+        .route(
+            "/orders/:order_id/refunds",
+            post(admin::orders::refund_order),
+        )
+        .route(
+            "/invoices/overdue",
+            get(admin::invoices::list_overdue_invoices),
+        )
+        .route(
+            "/invoices/:invoice_id/transfer",
+            post(admin::invoices::record_transfer),
+        )
+        .route(
+            "/quotes/:quote_id/price-override",
+            post(admin::quotes::override_quote_price),
+        )
+        .route(
+            "/webhooks/:event_id/replay",
+            post(admin::webhooks::replay_webhook),
+        )
+}
+
+/// The API router with every route, the OpenAPI docs and CORS
+pub fn app(state: AppState) -> Router {
+    Router::new()
+        .route("/health", get(handlers::health::health_check))
+        .route("/api/auth/register", post(handlers::auth::register))
+        .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        .route("/api/auth/logout", post(handlers::auth::logout))
+        .route("/api/account", get(handlers::account::get_account))
+        .route(
+            "/api/account/quotes",
+            get(handlers::account::list_account_quotes),
+        )
+        .route(
+            "/api/account/orders",
+            get(handlers::account::list_account_orders),
+        )
+        .route("/api/quotes", post(handlers::quotes::create_quote))
+        .route("/api/quotes/:quote_id", get(handlers::quotes::get_quote))
+        .route(
+            "/api/quotes/:quote_id/availability",
+            get(handlers::quotes::get_quote_availability),
+        )
+        .route("/api/colors", get(handlers::colors::list_colors))
+        .route(
+            "/api/price-books",
+            get(handlers::price_books::list_price_books),
+        )
+        .route(
+            "/api/price-books/:version",
+            get(handlers::price_books::get_price_book),
+        )
+        .route(
+            "/api/checkout/create-session",
+            post(handlers::checkout::create_checkout_session),
+        )
+        .route(
+            "/api/checkout/invoice",
+            post(handlers::checkout::create_invoice_checkout),
+        )
+        .route(
+            "/api/webhooks/stripe",
+            post(handlers::webhooks::stripe_webhook),
+        )
+        .nest("/api/admin", admin_router())
+        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .with_state(state)
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any),
+        )
+}
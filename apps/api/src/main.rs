@@ -1,47 +1,15 @@
-use axum::{
-    routing::{get, post},
-    Router,
-};
-use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
-
-mod db;
-mod handlers;
-mod models;
-mod routes;
-
-#[derive(Clone)]
-pub struct AppState {
-    pub stripe_secret_key: String,
-    pub stripe_webhook_secret: String,
-}
 
-#[derive(OpenApi)]
-#[openapi(
-    paths(
-        handlers::health::health_check,
-        handlers::checkout::create_checkout_session,
-        handlers::webhooks::stripe_webhook,
-    ),
-    components(
-        schemas(
-            handlers::checkout::CreateCheckoutSessionRequest,
-            handlers::checkout::CreateCheckoutSessionResponse,
-            handlers::checkout::QuoteDetails,
-            handlers::checkout::ErrorResponse,
-            handlers::webhooks::WebhookResponse,
-        )
-    ),
-    tags(
-        (name = "health", description = "Health check endpoints"),
-        (name = "checkout", description = "Checkout and payment endpoints"),
-        (name = "webhooks", description = "Webhook handlers for external services")
-    )
-)]
-struct ApiDoc;
+use api::{
+    auth::{self, TokenKeys},
+    db,
+    models::{BankDetails, InvoiceSettings, StaffRole},
+    payments::StripeProvider,
+    pricing,
+    signature::SignatureVerifier,
+    AppState,
+};
 
 #[tokio::main]
 async fn main() {
@@ -59,33 +27,29 @@ async fn main() {
 
     // Initialize app state
     let state = AppState {
-        stripe_secret_key: std::env::var("STRIPE_SECRET_KEY")
-            .expect("STRIPE_SECRET_KEY must be set"),
-        stripe_webhook_secret: std::env::var("STRIPE_WEBHOOK_SECRET").unwrap_or_else(|_| {
-            tracing::warn!("STRIPE_WEBHOOK_SECRET not set, webhooks will fail");
-            String::new()
-        }),
+        payments: Arc::new(StripeProvider::new(
+            &std::env::var("STRIPE_SECRET_KEY").expect("STRIPE_SECRET_KEY must be set"),
+        )),
+        webhook_signatures: Arc::new(webhook_signatures()),
+        db: db::connect(&std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"))
+            .await
+            .expect("Failed to connect to database"),
+        tokens: Arc::new(token_keys()),
+        price_books: Arc::new(
+            pricing::load_price_books(
+                std::env::var_os("PRICE_BOOK_DIR")
+                    .map(PathBuf::from)
+                    .as_deref(),
+            )
+            .expect("Failed to load price books"),
+        ),
+        invoicing: invoice_settings().map(Arc::new),
     };
 
+    bootstrap_owner(&state).await;
+
     // Build our application with routes
-    let app = Router::new()
-        .route("/health", get(handlers::health::health_check))
-        .route(
-            "/api/checkout/create-session",
-            post(handlers::checkout::create_checkout_session),
-        )
-        .route(
-            "/api/webhooks/stripe",
-            post(handlers::webhooks::stripe_webhook),
-        )
-        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .with_state(state)
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        );
+    let app = api::app(state);
 
     // Run it
     let addr = SocketAddr::from(([0, 0, 0, 0], 8000));
@@ -93,3 +57,95 @@ async fn main() {
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
 }
+
+/// Webhook secrets from `STRIPE_WEBHOOK_SECRET`, comma-separated while a
+/// secret is being rolled, with `STRIPE_WEBHOOK_TOLERANCE_SECS` if set
+fn webhook_signatures() -> SignatureVerifier {
+    let secrets = std::env::var("STRIPE_WEBHOOK_SECRET").unwrap_or_default();
+    if secrets.trim().is_empty() {
+        tracing::warn!("STRIPE_WEBHOOK_SECRET not set, webhooks will fail");
+    }
+
+    let verifier = SignatureVerifier::from_list(&secrets);
+    match std::env::var("STRIPE_WEBHOOK_TOLERANCE_SECS") {
+        Ok(secs) => verifier.with_tolerance(
+            secs.parse()
+                .expect("STRIPE_WEBHOOK_TOLERANCE_SECS must be a number of seconds"),
+        ),
+        Err(_) => verifier,
+    }
+}
+
+/// Token signing keys from `JWT_SECRET`
+fn token_keys() -> TokenKeys {
+    let secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+    // HS256 is only as strong as its key; refuse guessable secrets
+    assert!(
+        secret.len() >= 32,
+        "JWT_SECRET must be at least 32 bytes, e.g. `openssl rand -hex 32`"
+    );
+    TokenKeys::new(secret.as_bytes())
+}
+
+/// Bank account invoices are paid to, from `INVOICE_ACCOUNT_HOLDER`,
+/// `INVOICE_IBAN` and `INVOICE_BIC`, with `INVOICE_PAYMENT_TERMS_DAYS` (default
+/// 14); paying by invoice stays off until all three are set
+fn invoice_settings() -> Option<InvoiceSettings> {
+    let var = |name| {
+        std::env::var(name)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let (Some(account_holder), Some(iban), Some(bic)) = (
+        var("INVOICE_ACCOUNT_HOLDER"),
+        var("INVOICE_IBAN"),
+        var("INVOICE_BIC"),
+    ) else {
+        tracing::warn!("Invoice bank details not set, business customers can only pay by card");
+        return None;
+    };
+
+    Some(InvoiceSettings {
+        bank: BankDetails {
+            account_holder,
+            iban: iban.replace(' ', ""),
+            bic,
+        },
+        payment_terms_days: var("INVOICE_PAYMENT_TERMS_DAYS").map_or(14, |days| {
+            days.parse()
+                .expect("INVOICE_PAYMENT_TERMS_DAYS must be a number of days")
+        }),
+    })
+}
+
+/// Create the first owner from `ADMIN_EMAIL` and `ADMIN_PASSWORD` while the
+/// shop has none; later staff are added through the admin API
+async fn bootstrap_owner(state: &AppState) {
+    let owners = db::staff::count_owners(&state.db)
+        .await
+        .expect("Failed to count staff owners");
+    if owners > 0 {
+        return;
+    }
+
+    let (Ok(email), Ok(password)) = (
+        std::env::var("ADMIN_EMAIL"),
+        std::env::var("ADMIN_PASSWORD"),
+    ) else {
+        tracing::warn!("No owner account; set ADMIN_EMAIL and ADMIN_PASSWORD to create one");
+        return;
+    };
+
+    let password_hash = auth::hash_password(&password).expect("Failed to hash ADMIN_PASSWORD");
+    let owner = db::staff::insert_staff(
+        &state.db,
+        &email.trim().to_lowercase(),
+        &password_hash,
+        None,
+        StaffRole::Owner,
+    )
+    .await
+    .expect("Failed to create owner account");
+    tracing::info!("Created owner account {} for {}", owner.id, owner.email);
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A customer's account, as shown to the customer
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Account {
+    pub id: Uuid,
+    /// Sign-in email, lowercased
+    pub email: String,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
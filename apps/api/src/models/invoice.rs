@@ -0,0 +1,108 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The shop's bank account customers transfer invoiced amounts to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct BankDetails {
+    pub account_holder: String,
+    pub iban: String,
+    pub bic: String,
+}
+
+/// How invoices are issued; orders cannot be paid by invoice without them
+#[derive(Debug, Clone)]
+pub struct InvoiceSettings {
+    pub bank: BankDetails,
+    /// Days from issue until an invoice is due
+    pub payment_terms_days: u32,
+}
+
+impl InvoiceSettings {
+    /// Date an invoice issued on `issued_on` is due
+    pub fn due_on(&self, issued_on: NaiveDate) -> NaiveDate {
+        issued_on + Duration::days(self.payment_terms_days.into())
+    }
+}
+
+/// An invoice for an order paid by bank transfer
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    /// The order's payment, settled when the transfer is recorded
+    pub payment_id: Uuid,
+    /// e.g. `INV-2026-000042`; customers quote it as the transfer reference
+    pub number: String,
+    /// Gross amount due, in minor units (cents)
+    pub amount: i64,
+    pub currency: String,
+    /// Where the invoice was sent
+    pub customer_email: String,
+    /// Bank details as printed on the invoice
+    #[sqlx(flatten)]
+    pub bank: BankDetails,
+    pub issued_on: NaiveDate,
+    pub due_on: NaiveDate,
+    /// When the transfer arrived; absent while unpaid
+    pub paid_on: Option<NaiveDate>,
+    /// Staff member who recorded the transfer
+    pub received_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Invoice {
+    /// Days past its due date an unpaid invoice is on `today`, or 0
+    pub fn days_overdue(&self, today: NaiveDate) -> i64 {
+        match self.paid_on {
+            Some(_) => 0,
+            None => (today - self.due_on).num_days().max(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_overdue() {
+        let settings = InvoiceSettings {
+            bank: BankDetails {
+                account_holder: "Powder Coaters SIA".to_string(),
+                iban: "LV80BANK0000435195001".to_string(),
+                bic: "HABALV22".to_string(),
+            },
+            payment_terms_days: 14,
+        };
+        let issued_on = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+        let mut invoice = Invoice {
+            id: Uuid::new_v4(),
+            order_id: Uuid::new_v4(),
+            payment_id: Uuid::new_v4(),
+            number: "INV-2026-000001".to_string(),
+            amount: 120_000,
+            currency: "EUR".to_string(),
+            customer_email: "ap@example.com".to_string(),
+            bank: settings.bank.clone(),
+            issued_on,
+            due_on: settings.due_on(issued_on),
+            paid_on: None,
+            received_by: None,
+            created_at: Utc::now(),
+        };
+        assert_eq!(
+            invoice.due_on,
+            NaiveDate::from_ymd_opt(2026, 11, 3).unwrap()
+        );
+
+        let day = |d| NaiveDate::from_ymd_opt(2026, 11, d).unwrap();
+        assert_eq!(invoice.days_overdue(day(3)), 0);
+        assert_eq!(invoice.days_overdue(day(1)), 0);
+        assert_eq!(invoice.days_overdue(day(10)), 7);
+
+        invoice.paid_on = Some(day(9));
+        assert_eq!(invoice.days_overdue(day(10)), 0);
+    }
+}
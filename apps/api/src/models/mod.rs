@@ -1 +1,15 @@
-// Models module - to be implemented
+mod account;
+mod invoice;
+mod order;
+mod payment;
+mod quote;
+mod staff;
+mod webhook_event;
+
+pub use account::Account;
+pub use invoice::{BankDetails, Invoice, InvoiceSettings};
+pub use order::{Order, OrderItem, OrderStatus, OrderStatusChange};
+pub use payment::{Payment, PaymentKind, Refund, RefundStatus};
+pub use quote::{PriceOverride, StoredQuote};
+pub use staff::{Permission, Staff, StaffRole};
+pub use webhook_event::WebhookEvent;
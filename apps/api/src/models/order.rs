@@ -0,0 +1,240 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Lifecycle of an order from checkout to hand-over
+///
+/// The happy path is `Quoted → AwaitingPayment → Paid → InPrep → Coating →
+/// Curing → Qc → ReadyForPickup → Shipped`. `Cancelled`, `Expired` and
+/// `PaymentFailed` branch off before production starts. `Refunded` is where
+/// a paid order ends up once its whole payment has been returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum OrderStatus {
+    Quoted,
+    AwaitingPayment,
+    Paid,
+    InPrep,
+    Coating,
+    Curing,
+    Qc,
+    ReadyForPickup,
+    Shipped,
+    Cancelled,
+    Expired,
+    PaymentFailed,
+    Refunded,
+}
+
+impl OrderStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderStatus::Quoted => "quoted",
+            OrderStatus::AwaitingPayment => "awaiting_payment",
+            OrderStatus::Paid => "paid",
+            OrderStatus::InPrep => "in_prep",
+            OrderStatus::Coating => "coating",
+            OrderStatus::Curing => "curing",
+            OrderStatus::Qc => "qc",
+            OrderStatus::ReadyForPickup => "ready_for_pickup",
+            OrderStatus::Shipped => "shipped",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Expired => "expired",
+            OrderStatus::PaymentFailed => "payment_failed",
+            OrderStatus::Refunded => "refunded",
+        }
+    }
+
+    /// Whether the order may move directly from `self` to `next`
+    pub fn can_transition_to(self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+
+        matches!(
+            (self, next),
+            (Quoted, AwaitingPayment | Cancelled | Expired)
+                | (AwaitingPayment, Paid | PaymentFailed | Cancelled | Expired)
+                // Checkout lets the customer retry a declined payment in the same session
+                | (PaymentFailed, AwaitingPayment | Paid | Cancelled | Expired)
+                | (Paid, InPrep | Cancelled)
+                | (InPrep, Coating | Cancelled)
+                | (Coating, Curing)
+                | (Curing, Qc)
+                // Parts failing QC go back for another coat
+                | (Qc, ReadyForPickup | Coating)
+                | (ReadyForPickup, Shipped)
+                // Money can be returned at any point after payment
+                | (
+                    Paid | InPrep | Coating | Curing | Qc | ReadyForPickup | Shipped | Cancelled,
+                    Refunded
+                )
+        )
+    }
+
+    /// Validate a transition, returning the new status
+    pub fn transition_to(self, next: OrderStatus) -> Result<OrderStatus, InvalidTransition> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(InvalidTransition {
+                from: self,
+                to: next,
+            })
+        }
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use OrderStatus::*;
+
+        [
+            Quoted,
+            AwaitingPayment,
+            Paid,
+            InPrep,
+            Coating,
+            Curing,
+            Qc,
+            ReadyForPickup,
+            Shipped,
+            Cancelled,
+            Expired,
+            PaymentFailed,
+            Refunded,
+        ]
+        .into_iter()
+        .find(|status| status.as_str() == s)
+        .ok_or_else(|| format!("unknown order status: {}", s))
+    }
+}
+
+/// Rejected order status change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "illegal order transition {} -> {}", self.from, self.to)
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
+/// An order as stored in the database
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Order {
+    pub id: Uuid,
+    pub quote_id: Uuid,
+    pub status: OrderStatus,
+    pub customer_email: Option<String>,
+    /// Account the order belongs to; absent for guest checkouts
+    pub customer_id: Option<Uuid>,
+    /// Total in minor units (cents)
+    pub total_amount: i64,
+    /// Part of the total paid at checkout, the rest being due at pickup;
+    /// absent when the order is paid in full at checkout
+    pub deposit_amount: Option<i64>,
+    pub currency: String,
+    pub stripe_checkout_session_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A priced line of an order, as sent to the payment provider
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct OrderItem {
+    pub name: String,
+    pub description: Option<String>,
+    pub quantity: i32,
+    /// Minor units (cents)
+    pub unit_amount: i64,
+}
+
+/// One step in an order's status history
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct OrderStatusChange {
+    pub from_status: OrderStatus,
+    pub to_status: OrderStatus,
+    /// Staff member who made the change; absent for payment webhooks
+    pub staff_id: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_happy_path_transitions() {
+        use OrderStatus::*;
+
+        let path = [
+            Quoted,
+            AwaitingPayment,
+            Paid,
+            InPrep,
+            Coating,
+            Curing,
+            Qc,
+            ReadyForPickup,
+            Shipped,
+        ];
+
+        for pair in path.windows(2) {
+            assert_eq!(pair[0].transition_to(pair[1]), Ok(pair[1]));
+        }
+    }
+
+    #[test]
+    fn test_illegal_transitions_rejected() {
+        use OrderStatus::*;
+
+        assert!(AwaitingPayment.transition_to(Shipped).is_err());
+        assert!(Paid.transition_to(Expired).is_err());
+        assert!(Coating.transition_to(Cancelled).is_err());
+        assert!(Paid.transition_to(Paid).is_err());
+
+        for next in [Quoted, AwaitingPayment, Paid, Cancelled] {
+            assert!(Shipped.transition_to(next).is_err());
+            assert!(Expired.transition_to(next).is_err());
+            assert!(Refunded.transition_to(next).is_err());
+        }
+
+        // Only paid orders can be refunded
+        assert!(Shipped.transition_to(Refunded).is_ok());
+        assert!(Cancelled.transition_to(Refunded).is_ok());
+        for unpaid in [Quoted, AwaitingPayment, PaymentFailed, Expired] {
+            assert!(unpaid.transition_to(Refunded).is_err());
+        }
+    }
+
+    #[test]
+    fn test_status_round_trips_through_str() {
+        for status in [
+            OrderStatus::AwaitingPayment,
+            OrderStatus::Qc,
+            OrderStatus::ReadyForPickup,
+            OrderStatus::PaymentFailed,
+            OrderStatus::Refunded,
+        ] {
+            assert_eq!(status.as_str().parse::<OrderStatus>(), Ok(status));
+        }
+        assert!("on_hold".parse::<OrderStatus>().is_err());
+    }
+}
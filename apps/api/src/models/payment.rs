@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Which part of an order's total a payment covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum PaymentKind {
+    /// The whole total, at checkout
+    Full,
+    /// The upfront share of a large order, at checkout
+    Deposit,
+    /// The rest of a large order, once it is ready for pickup
+    Balance,
+}
+
+impl PaymentKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaymentKind::Full => "full",
+            PaymentKind::Deposit => "deposit",
+            PaymentKind::Balance => "balance",
+        }
+    }
+}
+
+/// A payment attempt against an order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Payment {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub kind: PaymentKind,
+    /// e.g. `"stripe"`
+    pub provider: String,
+    /// Provider's checkout session ID
+    pub provider_reference: String,
+    /// Where the customer pays; balance links are sent on to the customer
+    pub checkout_url: Option<String>,
+    /// Provider's payment ID refunds are issued against, once paid
+    pub payment_intent_id: Option<String>,
+    /// Amount charged at checkout, in minor units (cents)
+    pub amount: i64,
+    /// Amount actually captured; refunds never exceed it
+    pub captured_amount: i64,
+    /// Sum of succeeded refunds
+    pub refunded_amount: i64,
+    pub currency: String,
+    /// `pending`, `succeeded`, `partially_refunded`, `refunded`, `failed` or `expired`
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Payment {
+    /// Payment status after `refunded` of the captured amount has been refunded
+    pub fn status_after_refunds(&self, refunded: i64) -> &'static str {
+        if refunded <= 0 {
+            "succeeded"
+        } else if refunded >= self.captured_amount {
+            "refunded"
+        } else {
+            "partially_refunded"
+        }
+    }
+}
+
+/// Where a refund is with the payment provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum RefundStatus {
+    /// Requested, money not returned yet
+    Pending,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl RefundStatus {
+    /// Map a provider's refund status; anything unfinished counts as pending
+    pub fn from_provider(status: &str) -> RefundStatus {
+        match status {
+            "succeeded" => RefundStatus::Succeeded,
+            "failed" => RefundStatus::Failed,
+            "canceled" => RefundStatus::Canceled,
+            _ => RefundStatus::Pending,
+        }
+    }
+
+    /// Whether the refund's amount is spoken for: succeeded, or may still succeed
+    pub fn holds_amount(self) -> bool {
+        matches!(self, RefundStatus::Pending | RefundStatus::Succeeded)
+    }
+}
+
+/// Money returned to the customer from a payment
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Refund {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub payment_id: Uuid,
+    /// Provider's refund ID, once the provider accepted the refund
+    pub provider_refund_id: Option<String>,
+    /// Minor units (cents)
+    pub amount: i64,
+    pub currency: String,
+    pub reason: String,
+    pub status: RefundStatus,
+    /// Staff member who issued it; absent for refunds made in the provider's dashboard
+    pub staff_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_refund_statuses() {
+        assert_eq!(
+            RefundStatus::from_provider("succeeded"),
+            RefundStatus::Succeeded
+        );
+        assert_eq!(
+            RefundStatus::from_provider("requires_action"),
+            RefundStatus::Pending
+        );
+        assert!(RefundStatus::from_provider("pending").holds_amount());
+        assert!(!RefundStatus::from_provider("canceled").holds_amount());
+        assert!(!RefundStatus::from_provider("failed").holds_amount());
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use quote_core::{Money, PricedQuote, Quote};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A quote priced by the server and kept for checkout
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StoredQuote {
+    /// Quote ID to pass to checkout
+    pub id: Uuid,
+    /// Parts and order details the price was calculated from
+    pub input: Quote,
+    /// Server-computed price
+    pub output: PricedQuote,
+    /// Account the quote was made from; absent for anonymous quotes
+    pub customer_id: Option<Uuid>,
+    /// Price agreed with staff, charged at checkout instead of the quoted price
+    pub price_override: Option<PriceOverride>,
+    pub created_at: DateTime<Utc>,
+    /// Checkout refuses the quote after this time
+    pub expires_at: DateTime<Utc>,
+}
+
+impl StoredQuote {
+    /// Quoted amount in minor units (cents), VAT included
+    pub fn total_amount(&self) -> i64 {
+        self.output.amount_due().amount
+    }
+
+    /// What checkout charges: the agreed price if staff set one, else the quoted amount
+    pub fn amount_due(&self) -> Money {
+        self.price_override
+            .as_ref()
+            .map_or_else(|| self.output.amount_due(), |o| o.amount)
+    }
+}
+
+/// A price agreed by staff that checkout charges instead of the quoted price
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PriceOverride {
+    /// Gross amount, VAT included
+    pub amount: Money,
+    pub reason: String,
+    pub staff_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// What a staff member does in the shop, and so what they may change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum StaffRole {
+    /// Runs the shop: every permission, including managing staff
+    Owner,
+    /// Deals with customers: prices, payments, cancellations and refunds
+    Sales,
+    /// Runs production: moves orders through the line
+    Operator,
+}
+
+/// Something a staff member may be allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ViewOrders,
+    UpdateOrderStatus,
+    CancelOrders,
+    OverridePrices,
+    RequestPayments,
+    RecordPayments,
+    RefundOrders,
+    ReplayWebhooks,
+    ManageStaff,
+}
+
+impl StaffRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StaffRole::Owner => "owner",
+            StaffRole::Sales => "sales",
+            StaffRole::Operator => "operator",
+        }
+    }
+
+    pub fn allows(self, permission: Permission) -> bool {
+        use Permission::*;
+
+        match self {
+            StaffRole::Owner => true,
+            StaffRole::Sales => matches!(
+                permission,
+                ViewOrders
+                    | CancelOrders
+                    | OverridePrices
+                    | RequestPayments
+                    | RecordPayments
+                    | RefundOrders
+            ),
+            StaffRole::Operator => matches!(permission, ViewOrders | UpdateOrderStatus),
+        }
+    }
+}
+
+impl fmt::Display for StaffRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A staff account, without its password hash
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Staff {
+    pub id: Uuid,
+    /// Sign-in email, lowercased
+    pub email: String,
+    pub name: Option<String>,
+    pub role: StaffRole,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_permissions() {
+        use Permission::*;
+
+        let all = [
+            ViewOrders,
+            UpdateOrderStatus,
+            CancelOrders,
+            OverridePrices,
+            RequestPayments,
+            RecordPayments,
+            RefundOrders,
+            ReplayWebhooks,
+            ManageStaff,
+        ];
+        let allowed = |role: StaffRole| {
+            all.into_iter()
+                .filter(|&p| role.allows(p))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(allowed(StaffRole::Owner), all);
+        assert_eq!(
+            allowed(StaffRole::Sales),
+            [
+                ViewOrders,
+                CancelOrders,
+                OverridePrices,
+                RequestPayments,
+                RecordPayments,
+                RefundOrders
+            ]
+        );
+        assert_eq!(
+            allowed(StaffRole::Operator),
+            [ViewOrders, UpdateOrderStatus]
+        );
+    }
+}
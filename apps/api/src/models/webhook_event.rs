@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A verified webhook delivery recorded in the event ledger
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct WebhookEvent {
+    /// Provider event ID (e.g. `evt_...`)
+    pub id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub received_at: DateTime<Utc>,
+    /// Set once the event has been applied; redeliveries are then ignored
+    pub processed_at: Option<DateTime<Utc>>,
+    /// How many times the provider delivered this event
+    pub delivery_count: i32,
+    pub replayed_at: Option<DateTime<Utc>>,
+}
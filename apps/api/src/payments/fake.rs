@@ -0,0 +1,115 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{
+    CheckoutRequest, CheckoutSession, PaymentError, PaymentProvider, ProviderRefund, RefundRequest,
+};
+use crate::models::RefundStatus;
+
+/// A session the fake provider was asked to create
+#[derive(Debug, Clone)]
+pub struct RecordedSession {
+    pub id: String,
+    pub request: CheckoutRequest,
+}
+
+/// A refund the fake provider was asked to make
+#[derive(Debug, Clone)]
+pub struct RecordedRefund {
+    pub id: String,
+    pub request: RefundRequest,
+}
+
+/// In-memory provider for tests: records sessions instead of calling out
+///
+/// It stands in for Stripe: session IDs look like Stripe's (`cs_test_…`) and
+/// payments are recorded as Stripe's, so webhook events built for them go
+/// through the same handler as real ones.
+#[derive(Debug, Default)]
+pub struct FakePaymentProvider {
+    sessions: Mutex<Vec<RecordedSession>>,
+    refunds: Mutex<Vec<RecordedRefund>>,
+    failure: Option<String>,
+    /// Refunds are reported pending, to be settled by webhook, instead of succeeded
+    pending_refunds: bool,
+}
+
+impl FakePaymentProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A provider that refuses every session with `message`
+    pub fn failing(message: &str) -> Self {
+        FakePaymentProvider {
+            failure: Some(message.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// A provider whose refunds stay pending until a webhook settles them
+    pub fn with_pending_refunds() -> Self {
+        FakePaymentProvider {
+            pending_refunds: true,
+            ..Self::default()
+        }
+    }
+
+    /// Sessions created so far, oldest first
+    pub fn sessions(&self) -> Vec<RecordedSession> {
+        self.sessions.lock().unwrap().clone()
+    }
+
+    /// Refunds made so far, oldest first
+    pub fn refunds(&self) -> Vec<RecordedRefund> {
+        self.refunds.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for FakePaymentProvider {
+    fn name(&self) -> &'static str {
+        "stripe"
+    }
+
+    async fn create_checkout_session(
+        &self,
+        request: CheckoutRequest,
+    ) -> Result<CheckoutSession, PaymentError> {
+        if let Some(message) = &self.failure {
+            return Err(PaymentError(message.clone()));
+        }
+
+        let id = format!("cs_test_{}", Uuid::new_v4().simple());
+        let url = format!("https://checkout.example.test/{}", id);
+        self.sessions.lock().unwrap().push(RecordedSession {
+            id: id.clone(),
+            request,
+        });
+
+        Ok(CheckoutSession { id, url })
+    }
+
+    async fn refund(&self, request: RefundRequest) -> Result<ProviderRefund, PaymentError> {
+        if let Some(message) = &self.failure {
+            return Err(PaymentError(message.clone()));
+        }
+
+        let id = format!("re_test_{}", Uuid::new_v4().simple());
+        self.refunds.lock().unwrap().push(RecordedRefund {
+            id: id.clone(),
+            request,
+        });
+
+        Ok(ProviderRefund {
+            id,
+            status: if self.pending_refunds {
+                RefundStatus::Pending
+            } else {
+                RefundStatus::Succeeded
+            },
+        })
+    }
+}
@@ -0,0 +1,93 @@
+//! Payment providers checkout hands orders to
+//!
+//! Handlers only see [`PaymentProvider`]; production uses Stripe, tests use
+//! the in-memory [`FakePaymentProvider`].
+
+use std::collections::HashMap;
+use std::fmt;
+
+use async_trait::async_trait;
+use quote_core::Currency;
+
+use crate::models::RefundStatus;
+
+mod fake;
+mod stripe;
+
+pub use fake::{FakePaymentProvider, RecordedRefund, RecordedSession};
+pub use stripe::StripeProvider;
+
+/// A priced line charged at checkout
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckoutItem {
+    pub name: String,
+    pub description: Option<String>,
+    /// Amount in minor units, charged once
+    pub amount: i64,
+}
+
+/// Everything a provider needs to open a hosted checkout page
+#[derive(Debug, Clone)]
+pub struct CheckoutRequest {
+    pub currency: Currency,
+    pub items: Vec<CheckoutItem>,
+    pub customer_email: Option<String>,
+    pub success_url: String,
+    pub cancel_url: String,
+    /// Copied onto the session and its payment, so events can be matched to the order
+    pub metadata: HashMap<String, String>,
+}
+
+/// A checkout session the customer is redirected to
+#[derive(Debug, Clone)]
+pub struct CheckoutSession {
+    /// Provider's session ID; webhook events refer to it
+    pub id: String,
+    pub url: String,
+}
+
+/// Money to return from a captured payment
+#[derive(Debug, Clone)]
+pub struct RefundRequest {
+    /// Provider's payment ID, as recorded when the payment was captured
+    pub payment_intent_id: String,
+    /// Amount in minor units, at most what is left of the capture
+    pub amount: i64,
+    pub currency: Currency,
+    /// Copied onto the refund, so its events can be matched to our record
+    pub metadata: HashMap<String, String>,
+}
+
+/// A refund the provider accepted
+#[derive(Debug, Clone)]
+pub struct ProviderRefund {
+    /// Provider's refund ID; refund webhook events refer to it
+    pub id: String,
+    /// Card refunds usually succeed at once, others finish later by webhook
+    pub status: RefundStatus,
+}
+
+/// A provider call that failed
+#[derive(Debug)]
+pub struct PaymentError(pub String);
+
+impl fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PaymentError {}
+
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    /// Name payments are recorded under, e.g. `"stripe"`
+    fn name(&self) -> &'static str;
+
+    async fn create_checkout_session(
+        &self,
+        request: CheckoutRequest,
+    ) -> Result<CheckoutSession, PaymentError>;
+
+    async fn refund(&self, request: RefundRequest) -> Result<ProviderRefund, PaymentError>;
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use stripe::{
+    CheckoutSessionMode, Client, CreateCheckoutSession, CreateCheckoutSessionLineItems,
+    CreateCheckoutSessionLineItemsPriceData, CreateCheckoutSessionLineItemsPriceDataProductData,
+    CreateCheckoutSessionPaymentIntentData, CreateRefund, Currency, PaymentIntentId,
+    RefundReasonFilter,
+};
+
+use super::{
+    CheckoutRequest, CheckoutSession, PaymentError, PaymentProvider, ProviderRefund, RefundRequest,
+};
+use crate::models::RefundStatus;
+
+/// Stripe Checkout, via the Stripe API
+pub struct StripeProvider {
+    client: Client,
+}
+
+impl StripeProvider {
+    pub fn new(secret_key: &str) -> Self {
+        StripeProvider {
+            client: Client::new(secret_key),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for StripeProvider {
+    fn name(&self) -> &'static str {
+        "stripe"
+    }
+
+    async fn create_checkout_session(
+        &self,
+        request: CheckoutRequest,
+    ) -> Result<CheckoutSession, PaymentError> {
+        let line_items = request
+            .items
+            .iter()
+            .map(|item| CreateCheckoutSessionLineItems {
+                price_data: Some(CreateCheckoutSessionLineItemsPriceData {
+                    currency: stripe_currency(request.currency),
+                    unit_amount: Some(item.amount),
+                    product_data: Some(CreateCheckoutSessionLineItemsPriceDataProductData {
+                        name: item.name.clone(),
+                        description: item.description.clone(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                quantity: Some(1),
+                ..Default::default()
+            })
+            .collect();
+
+        let mut params = CreateCheckoutSession::new();
+        params.line_items = Some(line_items);
+        params.mode = Some(CheckoutSessionMode::Payment);
+        params.success_url = Some(&request.success_url);
+        params.cancel_url = Some(&request.cancel_url);
+        params.customer_email = request.customer_email.as_deref();
+
+        // Copy the metadata onto the payment intent so its events can be matched to the order
+        params.payment_intent_data = Some(CreateCheckoutSessionPaymentIntentData {
+            metadata: Some(request.metadata.clone()),
+            ..Default::default()
+        });
+        params.metadata = Some(request.metadata.clone());
+
+        let session = stripe::CheckoutSession::create(&self.client, params)
+            .await
+            .map_err(|e| PaymentError(e.to_string()))?;
+
+        Ok(CheckoutSession {
+            id: session.id.to_string(),
+            url: session.url.unwrap_or_default(),
+        })
+    }
+
+    async fn refund(&self, request: RefundRequest) -> Result<ProviderRefund, PaymentError> {
+        let payment_intent = request
+            .payment_intent_id
+            .parse::<PaymentIntentId>()
+            .map_err(|e| PaymentError(e.to_string()))?;
+
+        let mut params = CreateRefund::new();
+        params.payment_intent = Some(payment_intent);
+        params.amount = Some(request.amount);
+        params.reason = Some(RefundReasonFilter::RequestedByCustomer);
+        params.metadata = Some(request.metadata);
+
+        let refund = stripe::Refund::create(&self.client, params)
+            .await
+            .map_err(|e| PaymentError(e.to_string()))?;
+
+        Ok(ProviderRefund {
+            id: refund.id.to_string(),
+            status: RefundStatus::from_provider(refund.status.as_deref().unwrap_or("pending")),
+        })
+    }
+}
+
+fn stripe_currency(currency: quote_core::Currency) -> Currency {
+    match currency {
+        quote_core::Currency::Eur => Currency::EUR,
+        quote_core::Currency::Usd => Currency::USD,
+        quote_core::Currency::Gbp => Currency::GBP,
+        quote_core::Currency::Chf => Currency::CHF,
+        quote_core::Currency::Sek => Currency::SEK,
+        quote_core::Currency::Nok => Currency::NOK,
+        quote_core::Currency::Dkk => Currency::DKK,
+        quote_core::Currency::Pln => Currency::PLN,
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::Path;
+
+use quote_core::{PriceBook, PriceBookError, PriceBookSet};
+
+/// Load every `*.toml` / `*.json` price book from `dir`
+///
+/// Without a directory the standard books compiled into `quote_core` are used.
+pub fn load_price_books(dir: Option<&Path>) -> Result<PriceBookSet, PriceBookError> {
+    let Some(dir) = dir else {
+        return Ok(PriceBookSet::default());
+    };
+
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| PriceBookError::Parse(format!("cannot read {}: {}", dir.display(), e)))?;
+
+    let mut books = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| PriceBookError::Parse(e.to_string()))?
+            .path();
+
+        let parse = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => PriceBook::from_toml,
+            Some("json") => PriceBook::from_json,
+            _ => continue,
+        };
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| PriceBookError::Parse(format!("cannot read {}: {}", path.display(), e)))?;
+        let book = parse(&text)
+            .map_err(|e| PriceBookError::Invalid(format!("{}: {}", path.display(), e)))?;
+
+        tracing::info!(
+            "Loaded price book {} (effective {}) from {}",
+            book.version,
+            book.effective_from,
+            path.display()
+        );
+        books.push(book);
+    }
+
+    PriceBookSet::new(books)
+}
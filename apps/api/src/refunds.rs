@@ -0,0 +1,284 @@
+//! Refunds of captured payments, whether issued by staff through the API or
+//! reported by the payment provider's webhooks
+//!
+//! A refund is recorded as pending before the provider is called, so the
+//! amount is held against the capture while the call is in flight and two
+//! refunds can never together return more than was captured.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::db::{self, refunds::NewRefund};
+use crate::models::{Order, OrderStatus, Payment, Refund, RefundStatus};
+use crate::payments::{PaymentError, RefundRequest};
+use crate::AppState;
+
+/// Reason recorded for refunds made outside the API, e.g. in the provider's dashboard
+const EXTERNAL_REFUND_REASON: &str = "Refunded with the payment provider";
+
+#[derive(Debug)]
+pub enum RefundError {
+    /// The order has no payment captured through the provider; bank
+    /// transfers are paid back by hand
+    NotPaid,
+    /// Amount not positive
+    InvalidAmount,
+    /// More than is left of the payment after earlier refunds
+    ExceedsRefundable {
+        refundable: i64,
+    },
+    Provider(PaymentError),
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for RefundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefundError::NotPaid => f.write_str("order has no captured payment to refund"),
+            RefundError::InvalidAmount => f.write_str("refund amount must be positive"),
+            RefundError::ExceedsRefundable { refundable } => {
+                write!(f, "at most {} is left to refund", refundable)
+            }
+            RefundError::Provider(e) => write!(f, "payment provider refused the refund: {}", e),
+            RefundError::Database(e) => write!(f, "database error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for RefundError {
+    fn from(e: sqlx::Error) -> Self {
+        RefundError::Database(e)
+    }
+}
+
+/// A refund as the provider reports it in a webhook event
+#[derive(Debug)]
+pub struct ProviderRefundEvent<'a> {
+    pub id: &'a str,
+    pub payment_intent_id: &'a str,
+    pub amount: i64,
+    pub currency: &'a str,
+    pub status: RefundStatus,
+    /// Our refund ID, from the metadata of refunds issued through the API
+    pub refund_id: Option<Uuid>,
+    pub reason: Option<&'a str>,
+}
+
+/// Refund `amount` of an order's latest captured payment, or all that is
+/// left of it
+///
+/// Orders paid as a deposit and a balance are refunded one payment at a
+/// time, the balance first. The order moves to `refunded` once everything
+/// captured has been returned.
+pub async fn issue_refund(
+    state: &AppState,
+    order: &Order,
+    amount: Option<i64>,
+    reason: &str,
+    staff_id: Uuid,
+) -> Result<Refund, RefundError> {
+    let mut tx = state.db.begin().await?;
+
+    let Some(payment) =
+        db::payments::lock_refundable_payment(&mut tx, order.id, state.payments.name()).await?
+    else {
+        return Err(
+            match db::payments::captured_total(&mut tx, order.id, state.payments.name()).await? {
+                0 => RefundError::NotPaid,
+                _ => RefundError::ExceedsRefundable { refundable: 0 },
+            },
+        );
+    };
+    let payment_intent_id = payment
+        .payment_intent_id
+        .clone()
+        .ok_or(RefundError::NotPaid)?;
+
+    let refundable =
+        payment.captured_amount - db::refunds::held_amount(&mut tx, payment.id).await?;
+    let amount = match amount {
+        Some(amount) if amount <= 0 => return Err(RefundError::InvalidAmount),
+        Some(amount) => amount,
+        None => refundable,
+    };
+    if refundable <= 0 || amount > refundable {
+        return Err(RefundError::ExceedsRefundable {
+            refundable: refundable.max(0),
+        });
+    }
+    let currency = payment
+        .currency
+        .parse()
+        .map_err(|e: String| RefundError::Provider(PaymentError(e)))?;
+
+    let refund = db::refunds::insert_refund(
+        &mut tx,
+        &NewRefund {
+            payment_id: payment.id,
+            order_id: order.id,
+            provider_refund_id: None,
+            amount,
+            currency: &payment.currency,
+            reason,
+            status: RefundStatus::Pending,
+            staff_id: Some(staff_id),
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    let request = RefundRequest {
+        payment_intent_id,
+        amount,
+        currency,
+        metadata: HashMap::from([
+            ("order_id".to_string(), order.id.to_string()),
+            ("refund_id".to_string(), refund.id.to_string()),
+            ("reason".to_string(), reason.to_string()),
+        ]),
+    };
+
+    let provider_refund = match state.payments.refund(request).await {
+        Ok(provider_refund) => provider_refund,
+        Err(e) => {
+            tracing::error!("Refund {} of order {} failed: {}", refund.id, order.id, e);
+            let mut tx = state.db.begin().await?;
+            db::refunds::update_refund(&mut tx, refund.id, None, RefundStatus::Failed).await?;
+            tx.commit().await?;
+            return Err(RefundError::Provider(e));
+        }
+    };
+
+    let mut tx = state.db.begin().await?;
+    let payment = db::payments::lock_payment(&mut tx, payment.id).await?;
+    let refund = db::refunds::update_refund(
+        &mut tx,
+        refund.id,
+        Some(&provider_refund.id),
+        provider_refund.status,
+    )
+    .await?;
+    settle(&mut tx, &payment, Some(staff_id), reason).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "Staff {} refunded {} {} of order {} ({:?})",
+        staff_id,
+        amount,
+        payment.currency,
+        order.id,
+        refund.status
+    );
+
+    Ok(refund)
+}
+
+/// Apply a refund the provider reported: settle one issued here, or record
+/// one made elsewhere
+pub async fn record_provider_refund(
+    state: &AppState,
+    event: &ProviderRefundEvent<'_>,
+) -> Result<(), sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+
+    let Some(payment) = db::payments::lock_payment_by_intent(
+        &mut tx,
+        state.payments.name(),
+        event.payment_intent_id,
+    )
+    .await?
+    else {
+        tracing::warn!(
+            "No payment found for refund {} of {}",
+            event.id,
+            event.payment_intent_id
+        );
+        return Ok(());
+    };
+
+    let existing = db::refunds::find_refund(&mut tx, payment.id, event.id, event.refund_id).await?;
+    let (reason, staff_id) = match existing {
+        Some(refund) => {
+            db::refunds::update_refund(&mut tx, refund.id, Some(event.id), event.status).await?;
+            (refund.reason, refund.staff_id)
+        }
+        None if event.amount > 0 => {
+            let reason = event.reason.unwrap_or(EXTERNAL_REFUND_REASON);
+            db::refunds::insert_refund(
+                &mut tx,
+                &NewRefund {
+                    payment_id: payment.id,
+                    order_id: payment.order_id,
+                    provider_refund_id: Some(event.id),
+                    amount: event.amount,
+                    currency: event.currency,
+                    reason,
+                    status: event.status,
+                    staff_id: None,
+                },
+            )
+            .await?;
+            tracing::info!(
+                "Recorded refund {} of order {} made with the provider",
+                event.id,
+                payment.order_id
+            );
+            (reason.to_string(), None)
+        }
+        None => {
+            tracing::warn!("Ignoring refund {} without an amount", event.id);
+            return Ok(());
+        }
+    };
+
+    settle(&mut tx, &payment, staff_id, &reason).await?;
+    tx.commit().await
+}
+
+/// Bring a locked payment's refunded amount up to date with its refunds,
+/// moving the order to `refunded` once nothing it paid is left
+async fn settle(
+    tx: &mut Transaction<'_, Postgres>,
+    payment: &Payment,
+    staff_id: Option<Uuid>,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    let refunded = db::refunds::succeeded_amount(tx, payment.id)
+        .await?
+        .min(payment.captured_amount);
+    let status = payment.status_after_refunds(refunded);
+    db::payments::set_refunded(tx, payment.id, refunded, status).await?;
+
+    if !db::payments::fully_refunded(tx, payment.order_id).await? {
+        return Ok(());
+    }
+
+    let Some(order) = db::orders::lock_order(tx, payment.order_id).await? else {
+        return Ok(());
+    };
+    if order.status.transition_to(OrderStatus::Refunded).is_err() {
+        if order.status != OrderStatus::Refunded {
+            tracing::warn!("Order {} refunded in full while {}", order.id, order.status);
+        }
+        return Ok(());
+    }
+
+    db::orders::update_order_status(tx, order.id, order.status, OrderStatus::Refunded).await?;
+    db::orders::insert_status_change(
+        tx,
+        order.id,
+        order.status,
+        OrderStatus::Refunded,
+        staff_id,
+        Some(reason),
+    )
+    .await?;
+    db::oven_bookings::release_bookings(tx, order.id).await?;
+
+    tracing::info!("Order {} moved {} -> refunded", order.id, order.status);
+
+    Ok(())
+}
@@ -0,0 +1,314 @@
+//! Stripe webhook signature verification
+//!
+//! Stripe signs `"{timestamp}.{payload}"` with HMAC-SHA256 and sends
+//! `Stripe-Signature: t=<timestamp>,v1=<hex signature>[,v1=...]`. Several
+//! secrets can be active at once so a secret can be rolled without dropping
+//! deliveries, and the clock is injectable so tolerance checks can be tested.
+
+use std::fmt;
+use std::sync::Arc;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How far a signature's timestamp may be from now, as Stripe's own libraries allow
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+/// Source of the current time, in Unix seconds
+pub trait Clock: Send + Sync {
+    fn now(&self) -> i64;
+}
+
+/// The system clock
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+}
+
+/// A clock stopped at a given Unix time, for tests
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+/// Why a signature was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// No webhook secret is configured, so nothing can be verified
+    NoSecrets,
+    /// The header has no usable `t=` timestamp
+    MissingTimestamp,
+    /// The header has no `v1=` signature
+    NoSignatures,
+    /// The timestamp is further from now than the tolerance
+    OutsideTolerance { age_secs: i64 },
+    /// No signature matches the payload under any active secret
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::NoSecrets => write!(f, "no webhook secret configured"),
+            SignatureError::MissingTimestamp => write!(f, "signature header has no timestamp"),
+            SignatureError::NoSignatures => write!(f, "signature header has no v1 signatures"),
+            SignatureError::OutsideTolerance { age_secs } => {
+                write!(f, "signature timestamp is {} seconds off", age_secs)
+            }
+            SignatureError::Mismatch => write!(f, "no signature matches the payload"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Verifies `Stripe-Signature` headers against the active webhook secrets
+pub struct SignatureVerifier {
+    secrets: Vec<String>,
+    tolerance_secs: i64,
+    clock: Arc<dyn Clock>,
+}
+
+impl SignatureVerifier {
+    /// A verifier accepting any of `secrets`, with the default tolerance and
+    /// the system clock
+    pub fn new(secrets: Vec<String>) -> Self {
+        SignatureVerifier {
+            secrets: secrets.into_iter().filter(|s| !s.is_empty()).collect(),
+            tolerance_secs: DEFAULT_TOLERANCE_SECS,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Secrets from a comma-separated list, newest first, e.g.
+    /// `"whsec_new,whsec_old"` while rolling a secret
+    pub fn from_list(secrets: &str) -> Self {
+        Self::new(secrets.split(',').map(|s| s.trim().to_string()).collect())
+    }
+
+    pub fn with_tolerance(mut self, tolerance_secs: i64) -> Self {
+        self.tolerance_secs = tolerance_secs;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Check `header` signs `payload` with one of the active secrets
+    pub fn verify(&self, payload: &[u8], header: &str) -> Result<(), SignatureError> {
+        if self.secrets.is_empty() {
+            return Err(SignatureError::NoSecrets);
+        }
+
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            // Split at the first `=` only; other schemes may use `=` in values
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("v1", value)) => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let timestamp = timestamp.ok_or(SignatureError::MissingTimestamp)?;
+        if signatures.is_empty() {
+            return Err(SignatureError::NoSignatures);
+        }
+
+        let age_secs = self.clock.now() - timestamp;
+        if age_secs.abs() > self.tolerance_secs {
+            return Err(SignatureError::OutsideTolerance { age_secs });
+        }
+
+        for secret in &self.secrets {
+            let mac = signed_payload_mac(secret, timestamp, payload);
+            for signature in &signatures {
+                let Ok(bytes) = hex::decode(signature) else {
+                    continue;
+                };
+                // verify_slice compares in constant time
+                if mac.clone().verify_slice(&bytes).is_ok() {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(SignatureError::Mismatch)
+    }
+
+    /// The `Stripe-Signature` header Stripe would send for `payload`
+    pub fn sign(secret: &str, timestamp: i64, payload: &[u8]) -> String {
+        let signature = signed_payload_mac(secret, timestamp, payload)
+            .finalize()
+            .into_bytes();
+        format!("t={},v1={}", timestamp, hex::encode(signature))
+    }
+}
+
+fn signed_payload_mac(secret: &str, timestamp: i64, payload: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_test_secret";
+    const OLD_SECRET: &str = "whsec_old_secret";
+    const TIMESTAMP: i64 = 1_700_000_000;
+    const PAYLOAD: &[u8] = br#"{"id":"evt_1","type":"checkout.session.completed"}"#;
+
+    // HMAC-SHA256 of "1700000000.<PAYLOAD>", computed independently with openssl
+    const SIGNATURE: &str = "a13d4b1f9003bc2fb6c06224e878b6056185eaa50d40e1e7dc05517f8af41d7b";
+    const OLD_SIGNATURE: &str = "6d77b47d6c990842c24c3b1b7c9d0044cb127910ca0e049a63e0cbfdfa8962fc";
+
+    fn verifier(secrets: &[&str], now: i64) -> SignatureVerifier {
+        SignatureVerifier::new(secrets.iter().map(|s| s.to_string()).collect())
+            .with_clock(Arc::new(FixedClock(now)))
+    }
+
+    fn verify(verifier: &SignatureVerifier, header: &str) -> Result<(), SignatureError> {
+        verifier.verify(PAYLOAD, header)
+    }
+
+    #[test]
+    fn test_known_good_signatures() {
+        let v = verifier(&[SECRET], TIMESTAMP);
+        let header = format!("t={},v1={}", TIMESTAMP, SIGNATURE);
+
+        assert_eq!(verify(&v, &header), Ok(()));
+        assert_eq!(SignatureVerifier::sign(SECRET, TIMESTAMP, PAYLOAD), header);
+
+        // Stripe sends one v1 per active secret, plus legacy v0 signatures
+        let several = format!(
+            "t={},v0=ignored,v1={},v1={}",
+            TIMESTAMP, OLD_SIGNATURE, SIGNATURE
+        );
+        assert_eq!(verify(&v, &several), Ok(()));
+
+        let spaced = format!("t={}, v1={}", TIMESTAMP, SIGNATURE);
+        assert_eq!(verify(&v, &spaced), Ok(()));
+    }
+
+    #[test]
+    fn test_rotated_secrets_are_all_accepted() {
+        let v = verifier(&[SECRET, OLD_SECRET], TIMESTAMP);
+
+        assert_eq!(
+            verify(&v, &format!("t={},v1={}", TIMESTAMP, OLD_SIGNATURE)),
+            Ok(())
+        );
+        assert_eq!(
+            verify(&v, &format!("t={},v1={}", TIMESTAMP, SIGNATURE)),
+            Ok(())
+        );
+
+        let from_list = SignatureVerifier::from_list(" whsec_test_secret , whsec_old_secret,")
+            .with_clock(Arc::new(FixedClock(TIMESTAMP)));
+        assert_eq!(
+            verify(&from_list, &format!("t={},v1={}", TIMESTAMP, OLD_SIGNATURE)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_known_bad_signatures() {
+        let v = verifier(&[SECRET], TIMESTAMP);
+        let good = format!("t={},v1={}", TIMESTAMP, SIGNATURE);
+
+        // Signed with a retired secret
+        assert_eq!(
+            verify(&v, &format!("t={},v1={}", TIMESTAMP, OLD_SIGNATURE)),
+            Err(SignatureError::Mismatch)
+        );
+        // Tampered payload
+        assert_eq!(
+            v.verify(
+                br#"{"id":"evt_2","type":"checkout.session.completed"}"#,
+                &good
+            ),
+            Err(SignatureError::Mismatch)
+        );
+        // Timestamp changed after signing
+        let shifted = verifier(&[SECRET], TIMESTAMP + 1);
+        assert_eq!(
+            verify(&shifted, &format!("t={},v1={}", TIMESTAMP + 1, SIGNATURE)),
+            Err(SignatureError::Mismatch)
+        );
+        // Truncated, non-hex and padded signatures
+        for signature in [&SIGNATURE[..62], "not-hex", &format!("{}==", SIGNATURE)] {
+            assert_eq!(
+                verify(&v, &format!("t={},v1={}", TIMESTAMP, signature)),
+                Err(SignatureError::Mismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_headers() {
+        let v = verifier(&[SECRET], TIMESTAMP);
+
+        assert_eq!(verify(&v, ""), Err(SignatureError::MissingTimestamp));
+        assert_eq!(
+            verify(&v, &format!("v1={}", SIGNATURE)),
+            Err(SignatureError::MissingTimestamp)
+        );
+        assert_eq!(
+            verify(&v, &format!("t=soon,v1={}", SIGNATURE)),
+            Err(SignatureError::MissingTimestamp)
+        );
+        assert_eq!(
+            verify(&v, &format!("t={}", TIMESTAMP)),
+            Err(SignatureError::NoSignatures)
+        );
+        assert_eq!(
+            verify(&v, &format!("t={},v0={}", TIMESTAMP, SIGNATURE)),
+            Err(SignatureError::NoSignatures)
+        );
+    }
+
+    #[test]
+    fn test_tolerance_window() {
+        let header = format!("t={},v1={}", TIMESTAMP, SIGNATURE);
+
+        assert_eq!(
+            verify(&verifier(&[SECRET], TIMESTAMP + 300), &header),
+            Ok(())
+        );
+        assert_eq!(
+            verify(&verifier(&[SECRET], TIMESTAMP + 301), &header),
+            Err(SignatureError::OutsideTolerance { age_secs: 301 })
+        );
+        assert_eq!(
+            verify(&verifier(&[SECRET], TIMESTAMP - 301), &header),
+            Err(SignatureError::OutsideTolerance { age_secs: -301 })
+        );
+
+        let relaxed = verifier(&[SECRET], TIMESTAMP + 3600).with_tolerance(3600);
+        assert_eq!(verify(&relaxed, &header), Ok(()));
+    }
+
+    #[test]
+    fn test_no_secrets_verifies_nothing() {
+        let v = verifier(&[""], TIMESTAMP);
+        let header = format!("t={},v1={}", TIMESTAMP, SIGNATURE);
+
+        assert_eq!(verify(&v, &header), Err(SignatureError::NoSecrets));
+    }
+}
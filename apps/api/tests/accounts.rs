@@ -0,0 +1,243 @@
+//! Registration, tokens and account history against a real database
+//!
+//! Skipped unless `DATABASE_URL` points at a PostgreSQL database the tests
+//! may write to.
+
+mod common;
+
+use serde_json::{json, Value};
+
+fn token<'a>(tokens: &'a Value, kind: &str) -> &'a str {
+    tokens[kind].as_str().unwrap()
+}
+
+#[tokio::test]
+async fn test_quotes_and_orders_show_in_account_history() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let tokens = app.register().await;
+    let access = token(&tokens, "access_token");
+    let email = tokens["account"]["email"].as_str().unwrap();
+
+    let response = app.post_as("/api/quotes", access, &common::part()).await;
+    assert_eq!(response.status(), 201);
+    let quote: Value = response.json().await.unwrap();
+    assert_eq!(quote["customer_id"], tokens["account"]["id"]);
+
+    // Anonymous quotes stay out of the history
+    let anonymous = app.create_quote(&common::part()).await;
+    assert!(anonymous["customer_id"].is_null());
+
+    let response = app.get_as("/api/account/quotes", access).await;
+    assert_eq!(response.status(), 200);
+    let quotes: Vec<Value> = response.json().await.unwrap();
+    assert_eq!(quotes.len(), 1);
+    assert_eq!(quotes[0]["id"], quote["id"]);
+
+    // The order inherits the account, and receipts default to its email
+    let response = app
+        .post_as(
+            "/api/checkout/create-session",
+            access,
+            &json!({ "quote_id": quote["id"], "currency": "EUR" }),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    let session: Value = response.json().await.unwrap();
+    let recorded = app.payments.sessions();
+    let recorded = recorded
+        .iter()
+        .find(|s| s.id == session["session_id"].as_str().unwrap())
+        .unwrap();
+    assert_eq!(recorded.request.customer_email.as_deref(), Some(email));
+
+    let orders: Vec<Value> = app
+        .get_as("/api/account/orders", access)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0]["quote_id"], quote["id"]);
+    assert_eq!(orders[0]["customer_email"], email);
+}
+
+#[tokio::test]
+async fn test_account_quotes_cannot_be_checked_out_by_others() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let owner = app.register().await;
+    let other = app.register().await;
+    let quote: Value = app
+        .post_as(
+            "/api/quotes",
+            token(&owner, "access_token"),
+            &common::part(),
+        )
+        .await
+        .json()
+        .await
+        .unwrap();
+    let body = json!({ "quote_id": quote["id"], "currency": "EUR" });
+
+    let response = app.post("/api/checkout/create-session", &body).await;
+    assert_eq!(response.status(), 401);
+
+    let response = app
+        .post_as(
+            "/api/checkout/create-session",
+            token(&other, "access_token"),
+            &body,
+        )
+        .await;
+    assert_eq!(response.status(), 403);
+    assert!(app.payments.sessions().is_empty());
+}
+
+#[tokio::test]
+async fn test_account_quotes_are_hidden_from_others() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let owner = app.register().await;
+    let other = app.register().await;
+    let mut part = common::part();
+    part["customer"] =
+        json!({ "country": "DE", "customer_type": "business", "vat_id": "DE123456789" });
+    let quote: Value = app
+        .post_as("/api/quotes", token(&owner, "access_token"), &part)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    for path in [
+        format!("/api/quotes/{}", quote["id"].as_str().unwrap()),
+        format!("/api/quotes/{}/availability", quote["id"].as_str().unwrap()),
+    ] {
+        let response = app.get(&path).await;
+        assert_eq!(response.status(), 404, "{} without a token", path);
+        let response = app.get_as(&path, token(&other, "access_token")).await;
+        assert_eq!(response.status(), 404, "{} for another account", path);
+        let response = app.get_as(&path, token(&owner, "access_token")).await;
+        assert_eq!(response.status(), 200, "{} for the owner", path);
+    }
+
+    let response = app
+        .get_as(
+            &format!("/api/quotes/{}", quote["id"].as_str().unwrap()),
+            token(&owner, "access_token"),
+        )
+        .await;
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["input"]["customer"]["vat_id"], "DE123456789");
+
+    // Anonymous quotes stay readable by ID
+    let anonymous = app.create_quote(&common::part()).await;
+    let response = app
+        .get(&format!(
+            "/api/quotes/{}",
+            anonymous["id"].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn test_login_and_refresh_rotation() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let registered = app.register().await;
+    let email = registered["account"]["email"].as_str().unwrap();
+
+    // Emails are case-insensitive; the password is not
+    let response = app
+        .post(
+            "/api/auth/login",
+            &json!({ "email": email.to_uppercase(), "password": common::PASSWORD }),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    let tokens: Value = response.json().await.unwrap();
+    assert_eq!(tokens["token_type"], "Bearer");
+    assert_eq!(tokens["account"]["id"], registered["account"]["id"]);
+
+    let response = app
+        .post(
+            "/api/auth/login",
+            &json!({ "email": email, "password": "Correct horse battery" }),
+        )
+        .await;
+    assert_eq!(response.status(), 401);
+
+    // A refresh token works exactly once
+    let refresh = json!({ "refresh_token": token(&tokens, "refresh_token") });
+    let response = app.post("/api/auth/refresh", &refresh).await;
+    assert_eq!(response.status(), 200);
+    let rotated: Value = response.json().await.unwrap();
+    let response = app.post("/api/auth/refresh", &refresh).await;
+    assert_eq!(response.status(), 401);
+
+    let response = app
+        .get_as("/api/account", token(&rotated, "access_token"))
+        .await;
+    assert_eq!(response.status(), 200);
+    let account: Value = response.json().await.unwrap();
+    assert_eq!(account["email"], email);
+
+    // Logging out revokes the rotated refresh token
+    let refresh = json!({ "refresh_token": token(&rotated, "refresh_token") });
+    assert_eq!(app.post("/api/auth/logout", &refresh).await.status(), 204);
+    assert_eq!(app.post("/api/auth/refresh", &refresh).await.status(), 401);
+}
+
+#[tokio::test]
+async fn test_registration_and_token_errors() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let registered = app.register().await;
+    let email = registered["account"]["email"].as_str().unwrap();
+
+    let response = app
+        .post(
+            "/api/auth/register",
+            &json!({ "email": email, "password": "another long password" }),
+        )
+        .await;
+    assert_eq!(response.status(), 409);
+
+    let response = app
+        .post(
+            "/api/auth/register",
+            &json!({ "email": "not-an-email", "password": "short" }),
+        )
+        .await;
+    assert_eq!(response.status(), 422);
+    let body: Value = response.json().await.unwrap();
+    let fields: Vec<&str> = body["field_errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["email", "password"]);
+
+    // No token, a refresh token in place of an access token, and garbage
+    assert_eq!(app.get("/api/account/quotes").await.status(), 401);
+    let refresh_token = token(&registered, "refresh_token");
+    for bad in [refresh_token, "not-a-token"] {
+        assert_eq!(app.get_as("/api/account", bad).await.status(), 401);
+        // An invalid token is refused, not treated as anonymous
+        let response = app.post_as("/api/quotes", bad, &common::part()).await;
+        assert_eq!(response.status(), 401);
+    }
+}
@@ -0,0 +1,238 @@
+//! Staff sign-in, roles and order management against a real database
+//!
+//! Skipped unless `DATABASE_URL` points at a PostgreSQL database the tests
+//! may write to.
+
+mod common;
+
+use api::models::StaffRole;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn test_roles_gate_admin_endpoints() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let owner = app.staff_token(StaffRole::Owner).await;
+    let sales = app.staff_token(StaffRole::Sales).await;
+    let operator = app.staff_token(StaffRole::Operator).await;
+    let customer = app.register().await;
+    let customer = customer["access_token"].as_str().unwrap();
+
+    // Customer tokens and missing tokens never reach admin endpoints
+    assert_eq!(app.get("/api/admin/orders").await.status(), 401);
+    assert_eq!(
+        app.get_as("/api/admin/orders", customer).await.status(),
+        401
+    );
+    for token in [&owner, &sales, &operator] {
+        assert_eq!(app.get_as("/api/admin/orders", token).await.status(), 200);
+    }
+
+    // Only owners manage staff
+    let new_staff = json!({
+        "email": format!("op-{}@example.com", uuid::Uuid::new_v4().simple()),
+        "password": common::PASSWORD,
+        "role": "operator"
+    });
+    for token in [&sales, &operator] {
+        assert_eq!(app.get_as("/api/admin/staff", token).await.status(), 403);
+        let response = app.post_as("/api/admin/staff", token, &new_staff).await;
+        assert_eq!(response.status(), 403);
+    }
+    let response = app.post_as("/api/admin/staff", &owner, &new_staff).await;
+    assert_eq!(response.status(), 201);
+    let created: Value = response.json().await.unwrap();
+    assert_eq!(created["role"], "operator");
+    assert_eq!(
+        app.post_as("/api/admin/staff", &owner, &new_staff)
+            .await
+            .status(),
+        409
+    );
+
+    // Operators run production, sales cancel, neither does the other's job
+    let (_, order_id) = app.paid_order("gate@example.com").await;
+    let cancel = json!({ "reason": "customer changed their mind" });
+    let response = app
+        .post_as(
+            &format!("/api/admin/orders/{}/cancel", order_id),
+            &operator,
+            &cancel,
+        )
+        .await;
+    assert_eq!(response.status(), 403);
+    let response = app
+        .post_as(
+            &format!("/api/admin/orders/{}/status", order_id),
+            &sales,
+            &json!({ "status": "in_prep" }),
+        )
+        .await;
+    assert_eq!(response.status(), 403);
+    let response = app
+        .post_as("/api/admin/webhooks/evt_missing/replay", &sales, &json!({}))
+        .await;
+    assert_eq!(response.status(), 403);
+}
+
+#[tokio::test]
+async fn test_staff_move_orders_through_production() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let operator = app.staff_token(StaffRole::Operator).await;
+    let email = format!("line-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, order_id) = app.paid_order(&email).await;
+    let status_url = format!("/api/admin/orders/{}/status", order_id);
+
+    for status in ["in_prep", "coating"] {
+        let response = app
+            .post_as(&status_url, &operator, &json!({ "status": status }))
+            .await;
+        assert_eq!(response.status(), 200);
+        let order: Value = response.json().await.unwrap();
+        assert_eq!(order["status"], status);
+    }
+
+    // Illegal jumps and payment statuses are refused
+    let response = app
+        .post_as(&status_url, &operator, &json!({ "status": "shipped" }))
+        .await;
+    assert_eq!(response.status(), 409);
+    let response = app
+        .post_as(&status_url, &operator, &json!({ "status": "paid" }))
+        .await;
+    assert_eq!(response.status(), 400);
+
+    // Search by part of the email, then the full history
+    let response = app
+        .get_as(
+            &format!("/api/admin/orders?status=coating&q={}", &email[..12]),
+            &operator,
+        )
+        .await;
+    let orders: Vec<Value> = response.json().await.unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0]["id"], order_id);
+
+    let detail: Value = app
+        .get_as(&format!("/api/admin/orders/{}", order_id), &operator)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let history: Vec<(&str, bool)> = detail["status_history"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| (c["to_status"].as_str().unwrap(), c["staff_id"].is_null()))
+        .collect();
+    assert_eq!(
+        history,
+        [("paid", true), ("in_prep", false), ("coating", false)]
+    );
+    assert!(!detail["items"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cancelling_a_paid_order_releases_oven_time() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let sales = app.staff_token(StaffRole::Sales).await;
+    let (_, order_id) = app.paid_order("cancel@example.com").await;
+    let cancel_url = format!("/api/admin/orders/{}/cancel", order_id);
+
+    let response = app
+        .post_as(&cancel_url, &sales, &json!({ "reason": " " }))
+        .await;
+    assert_eq!(response.status(), 400);
+
+    let response = app
+        .post_as(&cancel_url, &sales, &json!({ "reason": "duplicate order" }))
+        .await;
+    assert_eq!(response.status(), 200);
+    let order: Value = response.json().await.unwrap();
+    assert_eq!(order["status"], "cancelled");
+
+    let booked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM oven_bookings WHERE order_id = $1")
+        .bind(order_id.parse::<uuid::Uuid>().unwrap())
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(booked, 0);
+
+    let response = app
+        .post_as(&cancel_url, &sales, &json!({ "reason": "again" }))
+        .await;
+    assert_eq!(response.status(), 409);
+}
+
+#[tokio::test]
+async fn test_price_override_is_charged_at_checkout() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let sales = app.staff_token(StaffRole::Sales).await;
+    let quote = app.create_quote(&common::part()).await;
+    let override_url = format!(
+        "/api/admin/quotes/{}/price-override",
+        quote["id"].as_str().unwrap()
+    );
+
+    let response = app
+        .post_as(
+            &override_url,
+            &sales,
+            &json!({ "amount": 12000, "reason": "" }),
+        )
+        .await;
+    assert_eq!(response.status(), 400);
+
+    let response = app
+        .post_as(
+            &override_url,
+            &sales,
+            &json!({ "amount": 12000, "reason": "repeat customer" }),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    let overridden: Value = response.json().await.unwrap();
+    assert_eq!(overridden["price_override"]["amount"]["amount"], 12000);
+    assert_eq!(overridden["price_override"]["reason"], "repeat customer");
+
+    let response = app
+        .post(
+            "/api/checkout/create-session",
+            &json!({ "quote_id": quote["id"], "currency": "EUR" }),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    let session: Value = response.json().await.unwrap();
+    let session_id = session["session_id"].as_str().unwrap();
+    let recorded = app.payments.sessions();
+    let recorded = recorded.iter().find(|s| s.id == session_id).unwrap();
+    let charged: Vec<i64> = recorded.request.items.iter().map(|i| i.amount).collect();
+    assert_eq!(charged, [12000]);
+    assert_eq!(
+        recorded.request.metadata["quoted_amount"],
+        quote["output"]["vat"]["gross"]["amount"].to_string()
+    );
+
+    // Once paid, the price is settled
+    app.send_webhook(&common::session_completed(session_id))
+        .await;
+    let response = app
+        .post_as(
+            &override_url,
+            &sales,
+            &json!({ "amount": 10000, "reason": "too late" }),
+        )
+        .await;
+    assert_eq!(response.status(), 409);
+}
@@ -0,0 +1,160 @@
+//! Quote → checkout → webhook against a real database, with payments faked
+//!
+//! Skipped unless `DATABASE_URL` points at a PostgreSQL database the tests
+//! may write to.
+
+mod common;
+
+use api::payments::FakePaymentProvider;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+async fn order_status(app: &common::TestApp, quote_id: Uuid) -> Option<String> {
+    sqlx::query_scalar("SELECT status FROM orders WHERE quote_id = $1")
+        .bind(quote_id)
+        .fetch_optional(&app.db)
+        .await
+        .unwrap()
+}
+
+fn quote_id(quote: &Value) -> Uuid {
+    quote["id"].as_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_checkout_and_webhook_mark_order_paid() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let quote = app.create_quote(&common::part()).await;
+    let quote_id = quote_id(&quote);
+    let amount_due = quote["output"]["vat"]["gross"]["amount"].as_i64().unwrap();
+
+    let response = app
+        .post(
+            "/api/checkout/create-session",
+            &json!({ "quote_id": quote_id, "currency": "EUR" }),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    let session: Value = response.json().await.unwrap();
+    let session_id = session["session_id"].as_str().unwrap();
+
+    // The provider was asked to charge exactly the quoted gross amount
+    let recorded = app.payments.sessions();
+    let recorded = recorded.iter().find(|s| s.id == session_id).unwrap();
+    let charged: i64 = recorded.request.items.iter().map(|item| item.amount).sum();
+    assert_eq!(charged, amount_due);
+    assert_eq!(recorded.request.metadata["quote_id"], quote_id.to_string());
+    assert_eq!(
+        order_status(&app, quote_id).await.as_deref(),
+        Some("awaiting_payment")
+    );
+
+    let event = common::session_completed(session_id);
+    let response = app.send_webhook(&event).await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(order_status(&app, quote_id).await.as_deref(), Some("paid"));
+
+    let payment_status: String = sqlx::query_scalar(
+        "SELECT p.status FROM payments p JOIN orders o ON o.id = p.order_id WHERE o.quote_id = $1",
+    )
+    .bind(quote_id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(payment_status, "succeeded");
+
+    // Stripe redelivers events; the second delivery is acknowledged only
+    let response = app.send_webhook(&event).await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(order_status(&app, quote_id).await.as_deref(), Some("paid"));
+}
+
+#[tokio::test]
+async fn test_provider_failure_creates_no_order() {
+    let Some(app) = common::spawn_with(FakePaymentProvider::failing("card network down")).await
+    else {
+        return;
+    };
+
+    let quote = app.create_quote(&common::part()).await;
+    let quote_id = quote_id(&quote);
+
+    let response = app
+        .post(
+            "/api/checkout/create-session",
+            &json!({ "quote_id": quote_id, "currency": "EUR" }),
+        )
+        .await;
+    assert_eq!(response.status(), 500);
+    let error: Value = response.json().await.unwrap();
+    assert_eq!(error["error"], "stripe_error");
+    assert_eq!(order_status(&app, quote_id).await, None);
+}
+
+#[tokio::test]
+async fn test_checkout_refuses_other_currency() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let quote = app.create_quote(&common::part()).await;
+    let response = app
+        .post(
+            "/api/checkout/create-session",
+            &json!({ "quote_id": quote_id(&quote), "currency": "USD" }),
+        )
+        .await;
+    assert_eq!(response.status(), 400);
+    assert!(app.payments.sessions().is_empty());
+}
+
+#[tokio::test]
+async fn test_invalid_quote_reports_field_errors() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let mut part = common::part();
+    part["quantity"] = json!(0);
+    let response = app.post("/api/quotes", &part).await;
+    assert_eq!(response.status(), 422);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["field_errors"][0]["field"], "quantity");
+
+    // Multi-line quotes are checked before their lead time is worked out
+    let mut line = part;
+    line.as_object_mut().unwrap().remove("turnaround_days");
+    line.as_object_mut().unwrap().remove("is_rush");
+    let response = app
+        .post(
+            "/api/quotes",
+            &json!({ "lines": [line], "turnaround_days": 10, "is_rush": false }),
+        )
+        .await;
+    assert_eq!(response.status(), 422);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["field_errors"][0]["field"], "lines[0].quantity");
+}
+
+#[tokio::test]
+async fn test_webhook_with_bad_signature_is_rejected() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let response = app
+        .client
+        .post(format!("{}/api/webhooks/stripe", app.url))
+        .header("stripe-signature", "t=1,v1=deadbeef")
+        .body(common::session_completed("cs_test_unknown").to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = app.get("/health").await;
+    assert_eq!(response.status(), 200);
+}
@@ -0,0 +1,255 @@
+//! Test server around the real router, backed by the database in
+//! `DATABASE_URL` and the in-memory payment provider
+
+// Each test binary compiles this module and uses only some of its helpers
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use api::{
+    auth::TokenKeys,
+    db,
+    models::{BankDetails, InvoiceSettings, StaffRole},
+    payments::FakePaymentProvider,
+    signature::SignatureVerifier,
+    AppState,
+};
+use quote_core::{PriceBook, PriceBookSet};
+use serde_json::Value;
+use sqlx::PgPool;
+
+pub const WEBHOOK_SECRET: &str = "whsec_integration_test";
+pub const JWT_SECRET: &str = "integration test secret, 32 bytes";
+/// Password of accounts made with [`TestApp::register`]
+pub const PASSWORD: &str = "correct horse battery";
+
+pub struct TestApp {
+    pub url: String,
+    pub db: PgPool,
+    pub payments: Arc<FakePaymentProvider>,
+    pub client: reqwest::Client,
+}
+
+/// Start the API on a free port, or `None` (and a note) without `DATABASE_URL`
+pub async fn spawn() -> Option<TestApp> {
+    spawn_with(FakePaymentProvider::new()).await
+}
+
+pub async fn spawn_with(payments: FakePaymentProvider) -> Option<TestApp> {
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL not set, skipping API integration test");
+        return None;
+    };
+
+    let pool = db::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    let payments = Arc::new(payments);
+    let state = AppState {
+        payments: payments.clone(),
+        webhook_signatures: Arc::new(SignatureVerifier::new(vec![WEBHOOK_SECRET.to_string()])),
+        db: pool.clone(),
+        tokens: Arc::new(TokenKeys::new(JWT_SECRET.as_bytes())),
+        price_books: Arc::new(price_books()),
+        invoicing: Some(Arc::new(InvoiceSettings {
+            bank: BankDetails {
+                account_holder: "Test Powder Coating".to_string(),
+                iban: "LV80BANK0000435195001".to_string(),
+                bic: "HABALV22".to_string(),
+            },
+            payment_terms_days: 14,
+        })),
+    };
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, api::app(state)).await.unwrap();
+    });
+
+    Some(TestApp {
+        url,
+        db: pool,
+        payments,
+        client: reqwest::Client::new(),
+    })
+}
+
+/// The shipped price books, plus the newest rates in force from today so
+/// tests see oven capacity and volume tiers before those books take effect
+fn price_books() -> PriceBookSet {
+    let standard = PriceBookSet::default();
+    let mut books = standard.books().to_vec();
+    books.push(PriceBook {
+        version: "test-current".to_string(),
+        effective_from: chrono::Utc::now().date_naive().to_string(),
+        ..standard.latest().clone()
+    });
+    PriceBookSet::new(books).unwrap()
+}
+
+impl TestApp {
+    pub async fn get(&self, path: &str) -> reqwest::Response {
+        self.client
+            .get(format!("{}{}", self.url, path))
+            .send()
+            .await
+            .unwrap()
+    }
+
+    pub async fn post(&self, path: &str, body: &Value) -> reqwest::Response {
+        self.client
+            .post(format!("{}{}", self.url, path))
+            .json(body)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_as(&self, path: &str, access_token: &str) -> reqwest::Response {
+        self.client
+            .get(format!("{}{}", self.url, path))
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    pub async fn post_as(&self, path: &str, access_token: &str, body: &Value) -> reqwest::Response {
+        self.client
+            .post(format!("{}{}", self.url, path))
+            .bearer_auth(access_token)
+            .json(body)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    /// Register an account with a fresh email and return the token response
+    pub async fn register(&self) -> Value {
+        let email = format!("customer-{}@example.com", uuid::Uuid::new_v4().simple());
+        let response = self
+            .post(
+                "/api/auth/register",
+                &serde_json::json!({ "email": email, "password": PASSWORD }),
+            )
+            .await;
+        assert_eq!(response.status(), 201);
+        response.json().await.unwrap()
+    }
+
+    /// Add a staff member with `role` and return their admin API token
+    pub async fn staff_token(&self, role: StaffRole) -> String {
+        let email = format!("staff-{}@example.com", uuid::Uuid::new_v4().simple());
+        let hash = api::auth::hash_password(PASSWORD).unwrap();
+        db::staff::insert_staff(&self.db, &email, &hash, None, role)
+            .await
+            .unwrap();
+
+        let response = self
+            .post(
+                "/api/admin/auth/login",
+                &serde_json::json!({ "email": email, "password": PASSWORD }),
+            )
+            .await;
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        body["access_token"].as_str().unwrap().to_string()
+    }
+
+    /// Deliver `event` to the Stripe webhook, signed like Stripe signs it
+    pub async fn send_webhook(&self, event: &Value) -> reqwest::Response {
+        let payload = event.to_string();
+        let signature = SignatureVerifier::sign(
+            WEBHOOK_SECRET,
+            chrono::Utc::now().timestamp(),
+            payload.as_bytes(),
+        );
+
+        self.client
+            .post(format!("{}/api/webhooks/stripe", self.url))
+            .header("stripe-signature", signature)
+            .body(payload)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    /// Create a quote and return its stored JSON
+    pub async fn create_quote(&self, body: &Value) -> Value {
+        let response = self.post("/api/quotes", body).await;
+        assert_eq!(response.status(), 201);
+        response.json().await.unwrap()
+    }
+
+    /// Create a quote, check it out and mark it paid; returns (quote, order ID)
+    pub async fn paid_order(&self, email: &str) -> (Value, String) {
+        let quote = self.create_quote(&part()).await;
+        let session: Value = self
+            .post(
+                "/api/checkout/create-session",
+                &serde_json::json!({
+                    "quote_id": quote["id"],
+                    "currency": "EUR",
+                    "customer_email": email
+                }),
+            )
+            .await
+            .json()
+            .await
+            .unwrap();
+        let session_id = session["session_id"].as_str().unwrap();
+        let response = self.send_webhook(&session_completed(session_id)).await;
+        assert_eq!(response.status(), 200);
+
+        let order_id: uuid::Uuid =
+            sqlx::query_scalar("SELECT id FROM orders WHERE stripe_checkout_session_id = $1")
+                .bind(session_id)
+                .fetch_one(&self.db)
+                .await
+                .unwrap();
+        (quote, order_id.to_string())
+    }
+}
+
+/// A single steel box, as the quote form sends it
+pub fn part() -> Value {
+    serde_json::json!({
+        "length_mm": 1000.0,
+        "width_mm": 500.0,
+        "height_mm": 300.0,
+        "shape": "Box",
+        "material": "Steel",
+        "prep_level": "Clean",
+        "color": "9005",
+        "turnaround_days": 10,
+        "quantity": 4,
+        "is_rush": false
+    })
+}
+
+/// A `checkout.session.completed` event for a paid session, whose payment
+/// intent is `pi_` followed by the session ID
+pub fn session_completed(session_id: &str) -> Value {
+    serde_json::json!({
+        "id": format!("evt_{}", uuid::Uuid::new_v4().simple()),
+        "type": "checkout.session.completed",
+        "data": {
+            "object": {
+                "id": session_id,
+                "payment_status": "paid",
+                "payment_intent": format!("pi_{}", session_id),
+                "customer_details": { "email": "buyer@example.com" }
+            }
+        }
+    })
+}
+
+/// A Stripe event of `event_type` about a refund
+pub fn refund_event(event_type: &str, refund: Value) -> Value {
+    serde_json::json!({
+        "id": format!("evt_{}", uuid::Uuid::new_v4().simple()),
+        "type": event_type,
+        "data": { "object": refund }
+    })
+}
@@ -0,0 +1,179 @@
+//! Deposit checkout and balance payments of large orders against a real
+//! database
+//!
+//! Skipped unless `DATABASE_URL` points at a PostgreSQL database the tests
+//! may write to.
+
+mod common;
+
+use api::models::StaffRole;
+use serde_json::{json, Value};
+
+/// A quote well above the standard book's 5000 EUR deposit threshold
+fn large_job() -> Value {
+    let mut part = common::part();
+    part["quantity"] = json!(400);
+    part
+}
+
+async fn checkout(app: &common::TestApp, quote: &Value) -> Value {
+    let response = app
+        .post(
+            "/api/checkout/create-session",
+            &json!({ "quote_id": quote["id"], "currency": "EUR", "customer_email": "fab@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    response.json().await.unwrap()
+}
+
+async fn order_detail(app: &common::TestApp, token: &str, order_id: &str) -> Value {
+    let response = app
+        .get_as(&format!("/api/admin/orders/{}", order_id), token)
+        .await;
+    assert_eq!(response.status(), 200);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_large_orders_pay_deposit_then_balance() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let operator = app.staff_token(StaffRole::Operator).await;
+    let sales = app.staff_token(StaffRole::Sales).await;
+
+    let quote = app.create_quote(&large_job()).await;
+
+    // Only the deposit is charged at checkout
+    let session = checkout(&app, &quote).await;
+    let charged = &app.payments.sessions()[0].request;
+    let total: i64 = charged.metadata["total_amount"].parse().unwrap();
+    assert!(total >= 500_000, "quote total {} below threshold", total);
+    let deposit = session["deposit_amount"].as_i64().unwrap();
+    assert_eq!(deposit, (total as f64 * 0.3).round() as i64);
+    assert_eq!(charged.items.len(), 1);
+    assert_eq!(charged.items[0].amount, deposit);
+    assert_eq!(charged.metadata["payment_kind"], "deposit");
+
+    let session_id = session["session_id"].as_str().unwrap();
+    let response = app
+        .send_webhook(&common::session_completed(session_id))
+        .await;
+    assert_eq!(response.status(), 200);
+    let order_id: uuid::Uuid =
+        sqlx::query_scalar("SELECT id FROM orders WHERE stripe_checkout_session_id = $1")
+            .bind(session_id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    let order_id = order_id.to_string();
+
+    let detail = order_detail(&app, &operator, &order_id).await;
+    assert_eq!(detail["order"]["status"], "paid");
+    assert_eq!(detail["order"]["total_amount"], total);
+    assert_eq!(detail["order"]["deposit_amount"], deposit);
+    assert!(detail["items"].as_array().unwrap().len() > 1);
+    assert_eq!(detail["payments"][0]["kind"], "deposit");
+    assert_eq!(detail["payments"][0]["status"], "succeeded");
+    assert_eq!(detail["payments"][0]["captured_amount"], deposit);
+
+    // No balance is due before the parts are ready
+    let balance_url = format!("/api/admin/orders/{}/balance-payment", order_id);
+    let response = app.post_as(&balance_url, &sales, &json!({})).await;
+    assert_eq!(response.status(), 409);
+
+    let status_url = format!("/api/admin/orders/{}/status", order_id);
+    for status in ["in_prep", "coating", "curing", "qc", "ready_for_pickup"] {
+        let response = app
+            .post_as(&status_url, &operator, &json!({ "status": status }))
+            .await;
+        assert_eq!(response.status(), 200);
+    }
+
+    // Reaching pickup asks for the balance
+    let detail = order_detail(&app, &operator, &order_id).await;
+    let payments = detail["payments"].as_array().unwrap();
+    assert_eq!(payments.len(), 2);
+    assert_eq!(payments[1]["kind"], "balance");
+    assert_eq!(payments[1]["status"], "pending");
+    assert_eq!(payments[1]["amount"], total - deposit);
+    assert!(payments[1]["checkout_url"].is_string());
+    let sessions = app.payments.sessions();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[1].request.items[0].amount, total - deposit);
+
+    // The parts stay in the shop until the balance is paid
+    let response = app
+        .post_as(&status_url, &operator, &json!({ "status": "shipped" }))
+        .await;
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "balance_unpaid");
+
+    // A new link replaces the unpaid one; operators cannot ask for money
+    let response = app.post_as(&balance_url, &operator, &json!({})).await;
+    assert_eq!(response.status(), 403);
+    let response = app.post_as(&balance_url, &sales, &json!({})).await;
+    assert_eq!(response.status(), 201);
+    let balance: Value = response.json().await.unwrap();
+    assert_eq!(balance["amount"], total - deposit);
+
+    let response = app
+        .send_webhook(&common::session_completed(
+            balance["provider_reference"].as_str().unwrap(),
+        ))
+        .await;
+    assert_eq!(response.status(), 200);
+
+    // The balance is tracked against the order without moving it
+    let detail = order_detail(&app, &operator, &order_id).await;
+    assert_eq!(detail["order"]["status"], "ready_for_pickup");
+    let payments = detail["payments"].as_array().unwrap();
+    assert_eq!(payments.len(), 3);
+    assert_eq!(payments[1]["status"], "expired");
+    assert_eq!(payments[2]["status"], "succeeded");
+    assert_eq!(payments[2]["captured_amount"], total - deposit);
+
+    let response = app.post_as(&balance_url, &sales, &json!({})).await;
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "balance_paid");
+    let response = app
+        .post_as(&status_url, &operator, &json!({ "status": "shipped" }))
+        .await;
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn test_small_orders_are_paid_in_full() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let sales = app.staff_token(StaffRole::Sales).await;
+    let quote = app.create_quote(&common::part()).await;
+    let session = checkout(&app, &quote).await;
+    assert!(session["deposit_amount"].is_null());
+    assert_eq!(
+        app.payments.sessions()[0].request.metadata["payment_kind"],
+        "full"
+    );
+
+    let (_, order_id) = app.paid_order("small@example.com").await;
+    let detail = order_detail(&app, &sales, &order_id).await;
+    assert!(detail["order"]["deposit_amount"].is_null());
+    assert_eq!(detail["payments"][0]["kind"], "full");
+
+    let response = app
+        .post_as(
+            &format!("/api/admin/orders/{}/balance-payment", order_id),
+            &sales,
+            &json!({}),
+        )
+        .await;
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "no_deposit");
+}
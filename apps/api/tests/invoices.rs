@@ -0,0 +1,223 @@
+//! Business orders paid by bank transfer against an invoice, against a real
+//! database
+//!
+//! Skipped unless `DATABASE_URL` points at a PostgreSQL database the tests
+//! may write to.
+
+mod common;
+
+use api::models::StaffRole;
+use chrono::{Duration, NaiveDate, Utc};
+use serde_json::{json, Value};
+
+/// The standard part, quoted for a Latvian company
+fn business_job() -> Value {
+    let mut part = common::part();
+    part["customer"] = json!({ "country": "LV", "customer_type": "business" });
+    part
+}
+
+async fn invoice_checkout(
+    app: &common::TestApp,
+    quote: &Value,
+    email: Option<&str>,
+) -> reqwest::Response {
+    app.post(
+        "/api/checkout/invoice",
+        &json!({ "quote_id": quote["id"], "currency": "EUR", "customer_email": email }),
+    )
+    .await
+}
+
+/// Check out a business quote by invoice; returns the response body
+async fn invoiced_order(app: &common::TestApp) -> Value {
+    let quote = app.create_quote(&business_job()).await;
+    let response = invoice_checkout(app, &quote, Some("ap@example.com")).await;
+    assert_eq!(response.status(), 201);
+    response.json().await.unwrap()
+}
+
+async fn order_detail(app: &common::TestApp, token: &str, order_id: &Value) -> Value {
+    let response = app
+        .get_as(
+            &format!("/api/admin/orders/{}", order_id.as_str().unwrap()),
+            token,
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    response.json().await.unwrap()
+}
+
+fn transfer_url(invoice: &Value) -> String {
+    format!(
+        "/api/admin/invoices/{}/transfer",
+        invoice["id"].as_str().unwrap()
+    )
+}
+
+#[tokio::test]
+async fn test_business_orders_pay_by_bank_transfer() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let sales = app.staff_token(StaffRole::Sales).await;
+    let operator = app.staff_token(StaffRole::Operator).await;
+
+    // Consumers pay by card
+    let quote = app.create_quote(&common::part()).await;
+    let response = invoice_checkout(&app, &quote, Some("home@example.com")).await;
+    assert_eq!(response.status(), 400);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "invoice_not_available");
+
+    let quote = app.create_quote(&business_job()).await;
+    let response = invoice_checkout(&app, &quote, None).await;
+    assert_eq!(response.status(), 400);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "email_required");
+
+    let response = invoice_checkout(&app, &quote, Some("ap@example.com")).await;
+    assert_eq!(response.status(), 201);
+    let placed: Value = response.json().await.unwrap();
+    let invoice = &placed["invoice"];
+    assert!(invoice["number"].as_str().unwrap().starts_with("INV-"));
+    assert_eq!(invoice["bank"]["iban"], "LV80BANK0000435195001");
+    assert_eq!(invoice["customer_email"], "ap@example.com");
+    assert!(invoice["paid_on"].is_null());
+    let issued_on: NaiveDate = serde_json::from_value(invoice["issued_on"].clone()).unwrap();
+    let due_on: NaiveDate = serde_json::from_value(invoice["due_on"].clone()).unwrap();
+    assert_eq!(due_on - issued_on, Duration::days(14));
+    assert!(app.payments.sessions().is_empty());
+
+    // The order waits for the transfer, itemized like a card order
+    let detail = order_detail(&app, &sales, &placed["order_id"]).await;
+    assert_eq!(detail["order"]["status"], "awaiting_payment");
+    assert_eq!(detail["order"]["total_amount"], invoice["amount"]);
+    assert!(detail["order"]["stripe_checkout_session_id"].is_null());
+    assert!(!detail["items"].as_array().unwrap().is_empty());
+    assert_eq!(detail["invoice"]["id"], invoice["id"]);
+    assert_eq!(detail["payments"][0]["provider"], "bank_transfer");
+    assert_eq!(
+        detail["payments"][0]["provider_reference"],
+        invoice["number"]
+    );
+    assert_eq!(detail["payments"][0]["status"], "pending");
+
+    // Operators never touch money; transfers cannot arrive tomorrow
+    let url = transfer_url(invoice);
+    let response = app.post_as(&url, &operator, &json!({})).await;
+    assert_eq!(response.status(), 403);
+    let tomorrow = Utc::now().date_naive() + Duration::days(1);
+    let response = app
+        .post_as(&url, &sales, &json!({ "received_on": tomorrow }))
+        .await;
+    assert_eq!(response.status(), 400);
+
+    let response = app
+        .post_as(&url, &sales, &json!({ "note": "Bank ref 20261017-553" }))
+        .await;
+    assert_eq!(response.status(), 200);
+    let paid: Value = response.json().await.unwrap();
+    assert_eq!(paid["paid_on"], json!(Utc::now().date_naive().to_string()));
+    assert!(paid["received_by"].is_string());
+
+    let detail = order_detail(&app, &sales, &placed["order_id"]).await;
+    assert_eq!(detail["order"]["status"], "paid");
+    assert_eq!(detail["payments"][0]["status"], "succeeded");
+    assert_eq!(detail["payments"][0]["captured_amount"], invoice["amount"]);
+    let last_change = detail["status_history"].as_array().unwrap().last().unwrap();
+    assert_eq!(last_change["to_status"], "paid");
+    assert_eq!(last_change["note"], "Bank ref 20261017-553");
+
+    let order_id: uuid::Uuid = placed["order_id"].as_str().unwrap().parse().unwrap();
+    let booked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM oven_bookings WHERE order_id = $1")
+        .bind(order_id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert!(booked > 0);
+
+    let response = app.post_as(&url, &sales, &json!({})).await;
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "invoice_paid");
+
+    // Transfers are paid back by hand, not through the card provider
+    let response = app
+        .post_as(
+            &format!("/api/admin/orders/{}/refunds", order_id),
+            &sales,
+            &json!({ "reason": "job cancelled" }),
+        )
+        .await;
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "not_paid");
+}
+
+#[tokio::test]
+async fn test_overdue_invoices_are_reported() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let sales = app.staff_token(StaffRole::Sales).await;
+    let overdue = invoiced_order(&app).await;
+    let cancelled = invoiced_order(&app).await;
+    let current = invoiced_order(&app).await;
+
+    // Issued a month ago, due 16 days ago
+    let today = Utc::now().date_naive();
+    for placed in [&overdue, &cancelled] {
+        let invoice_id: uuid::Uuid = placed["invoice"]["id"].as_str().unwrap().parse().unwrap();
+        sqlx::query("UPDATE invoices SET issued_on = $2, due_on = $3 WHERE id = $1")
+            .bind(invoice_id)
+            .bind(today - Duration::days(30))
+            .bind(today - Duration::days(16))
+            .execute(&app.db)
+            .await
+            .unwrap();
+    }
+    let response = app
+        .post_as(
+            &format!(
+                "/api/admin/orders/{}/cancel",
+                cancelled["order_id"].as_str().unwrap()
+            ),
+            &sales,
+            &json!({ "reason": "customer went elsewhere" }),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+
+    let listed = |report: &Value, placed: &Value| {
+        report
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["invoice"]["id"] == placed["invoice"]["id"])
+            .cloned()
+    };
+
+    let response = app.get_as("/api/admin/invoices/overdue", &sales).await;
+    assert_eq!(response.status(), 200);
+    let report: Value = response.json().await.unwrap();
+    let entry = listed(&report, &overdue).expect("overdue invoice not reported");
+    assert_eq!(entry["days_overdue"], 16);
+    assert!(listed(&report, &cancelled).is_none());
+    assert!(listed(&report, &current).is_none());
+
+    // Paid late, it drops off the report
+    let response = app
+        .post_as(&transfer_url(&overdue["invoice"]), &sales, &json!({}))
+        .await;
+    assert_eq!(response.status(), 200);
+    let report: Value = app
+        .get_as("/api/admin/invoices/overdue", &sales)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(listed(&report, &overdue).is_none());
+}
@@ -0,0 +1,226 @@
+//! Staff refunds and provider-reported refunds against a real database
+//!
+//! Skipped unless `DATABASE_URL` points at a PostgreSQL database the tests
+//! may write to.
+
+mod common;
+
+use api::{models::StaffRole, payments::FakePaymentProvider};
+use serde_json::{json, Value};
+
+async fn order_detail(app: &common::TestApp, token: &str, order_id: &str) -> Value {
+    let response = app
+        .get_as(&format!("/api/admin/orders/{}", order_id), token)
+        .await;
+    assert_eq!(response.status(), 200);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_partial_refunds_never_exceed_the_capture() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let sales = app.staff_token(StaffRole::Sales).await;
+    let operator = app.staff_token(StaffRole::Operator).await;
+    let (_, order_id) = app.paid_order("refund@example.com").await;
+    let refunds_url = format!("/api/admin/orders/{}/refunds", order_id);
+
+    let detail = order_detail(&app, &sales, &order_id).await;
+    let captured = detail["payments"][0]["captured_amount"].as_i64().unwrap();
+    assert!(captured > 1000);
+
+    // Operators run production and never touch money
+    let response = app
+        .post_as(
+            &refunds_url,
+            &operator,
+            &json!({ "amount": 1000, "reason": "scratched" }),
+        )
+        .await;
+    assert_eq!(response.status(), 403);
+
+    let response = app
+        .post_as(
+            &refunds_url,
+            &sales,
+            &json!({ "amount": 1000, "reason": " " }),
+        )
+        .await;
+    assert_eq!(response.status(), 400);
+    let response = app
+        .post_as(
+            &refunds_url,
+            &sales,
+            &json!({ "amount": 0, "reason": "scratched" }),
+        )
+        .await;
+    assert_eq!(response.status(), 400);
+
+    // Partial refund
+    let response = app
+        .post_as(
+            &refunds_url,
+            &sales,
+            &json!({ "amount": 1000, "reason": "one panel scratched" }),
+        )
+        .await;
+    assert_eq!(response.status(), 201);
+    let refund: Value = response.json().await.unwrap();
+    assert_eq!(refund["amount"], 1000);
+    assert_eq!(refund["status"], "succeeded");
+    assert!(refund["provider_refund_id"]
+        .as_str()
+        .unwrap()
+        .starts_with("re_test_"));
+
+    let detail = order_detail(&app, &sales, &order_id).await;
+    assert_eq!(detail["order"]["status"], "paid");
+    assert_eq!(detail["payments"][0]["status"], "partially_refunded");
+    assert_eq!(detail["payments"][0]["refunded_amount"], 1000);
+
+    // More than is left is refused before the provider is asked
+    let response = app
+        .post_as(
+            &refunds_url,
+            &sales,
+            &json!({ "amount": captured, "reason": "everything" }),
+        )
+        .await;
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "exceeds_refundable");
+    assert_eq!(app.payments.refunds().len(), 1);
+
+    // Without an amount, the rest is refunded and the order closes
+    let response = app
+        .post_as(&refunds_url, &sales, &json!({ "reason": "job cancelled" }))
+        .await;
+    assert_eq!(response.status(), 201);
+    let refund: Value = response.json().await.unwrap();
+    assert_eq!(refund["amount"], captured - 1000);
+
+    let recorded = app.payments.refunds();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(
+        recorded[1].request.metadata.get("refund_id").unwrap(),
+        refund["id"].as_str().unwrap()
+    );
+
+    let detail = order_detail(&app, &sales, &order_id).await;
+    assert_eq!(detail["order"]["status"], "refunded");
+    assert_eq!(detail["payments"][0]["status"], "refunded");
+    assert_eq!(detail["payments"][0]["refunded_amount"], captured);
+    assert_eq!(detail["refunds"].as_array().unwrap().len(), 2);
+    let last_change = detail["status_history"].as_array().unwrap().last().unwrap();
+    assert_eq!(last_change["to_status"], "refunded");
+    assert_eq!(last_change["note"], "job cancelled");
+
+    let response = app
+        .post_as(&refunds_url, &sales, &json!({ "reason": "again" }))
+        .await;
+    assert_eq!(response.status(), 409);
+}
+
+#[tokio::test]
+async fn test_pending_refunds_settle_by_webhook() {
+    let Some(app) = common::spawn_with(FakePaymentProvider::with_pending_refunds()).await else {
+        return;
+    };
+
+    let owner = app.staff_token(StaffRole::Owner).await;
+    let (_, order_id) = app.paid_order("pending-refund@example.com").await;
+    let refunds_url = format!("/api/admin/orders/{}/refunds", order_id);
+
+    let response = app
+        .post_as(
+            &refunds_url,
+            &owner,
+            &json!({ "reason": "bank transfer refund" }),
+        )
+        .await;
+    assert_eq!(response.status(), 201);
+    let refund: Value = response.json().await.unwrap();
+    assert_eq!(refund["status"], "pending");
+
+    // The pending refund holds the whole capture
+    let response = app
+        .post_as(
+            &refunds_url,
+            &owner,
+            &json!({ "amount": 1, "reason": "extra" }),
+        )
+        .await;
+    assert_eq!(response.status(), 409);
+
+    let detail = order_detail(&app, &owner, &order_id).await;
+    assert_eq!(detail["order"]["status"], "paid");
+    assert_eq!(detail["payments"][0]["refunded_amount"], 0);
+
+    let event = common::refund_event(
+        "refund.updated",
+        json!({
+            "id": refund["provider_refund_id"],
+            "amount": refund["amount"],
+            "currency": "eur",
+            "status": "succeeded",
+            "payment_intent": detail["payments"][0]["payment_intent_id"],
+            "metadata": { "refund_id": refund["id"], "order_id": order_id }
+        }),
+    );
+    assert_eq!(app.send_webhook(&event).await.status(), 200);
+
+    let detail = order_detail(&app, &owner, &order_id).await;
+    assert_eq!(detail["order"]["status"], "refunded");
+    assert_eq!(detail["refunds"][0]["status"], "succeeded");
+    assert_eq!(
+        detail["payments"][0]["refunded_amount"],
+        detail["payments"][0]["captured_amount"]
+    );
+}
+
+#[tokio::test]
+async fn test_refunds_made_with_the_provider_are_recorded() {
+    let Some(app) = common::spawn().await else {
+        return;
+    };
+
+    let owner = app.staff_token(StaffRole::Owner).await;
+    let (_, order_id) = app.paid_order("dashboard-refund@example.com").await;
+    let detail = order_detail(&app, &owner, &order_id).await;
+    let payment_intent = detail["payments"][0]["payment_intent_id"].clone();
+
+    let refund_id = format!("re_{}", uuid::Uuid::new_v4().simple());
+    let refund = json!({
+        "id": refund_id,
+        "amount": 2500,
+        "currency": "eur",
+        "status": "succeeded",
+        "metadata": {}
+    });
+    let event = common::refund_event(
+        "charge.refunded",
+        json!({
+            "id": "ch_dashboard",
+            "payment_intent": payment_intent,
+            "refunds": { "data": [refund] }
+        }),
+    );
+    assert_eq!(app.send_webhook(&event).await.status(), 200);
+
+    // The same refund reported again is not counted twice
+    let mut refund = refund;
+    refund["payment_intent"] = payment_intent;
+    let event = common::refund_event("refund.updated", refund);
+    assert_eq!(app.send_webhook(&event).await.status(), 200);
+
+    let detail = order_detail(&app, &owner, &order_id).await;
+    assert_eq!(detail["order"]["status"], "paid");
+    assert_eq!(detail["payments"][0]["status"], "partially_refunded");
+    assert_eq!(detail["payments"][0]["refunded_amount"], 2500);
+    let refunds = detail["refunds"].as_array().unwrap();
+    assert_eq!(refunds.len(), 1);
+    assert_eq!(refunds[0]["provider_refund_id"], refund_id.as_str());
+    assert!(refunds[0]["staff_id"].is_null());
+}
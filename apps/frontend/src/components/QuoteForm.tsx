@@ -470,7 +470,7 @@ export default function QuoteForm() {
                   />
                   <span className="text-sm font-medium">Rush Order</span>
                   <Badge variant="warning" className="ml-1">
-                    +50%
+                    up to +100%
                   </Badge>
                 </label>
               </div>
@@ -71,6 +71,8 @@ export function formatPrice(amount: number, currency: string = 'EUR'): string {
 //
 // export function calculateQuote(input: QuoteInput): QuoteOutput {
 //   const inputJson = JSON.stringify(input)
-//   const outputJson = calculate_quote_wasm(inputJson)
+//   // Price with the book the API uses today (UTC), not the newest one
+//   const today = new Date().toISOString().slice(0, 10)
+//   const outputJson = calculate_quote_wasm(inputJson, today)
 //   return JSON.parse(outputJson)
 // }
@@ -48,7 +48,7 @@ export const quoteSchema = z.object({
     .number()
     .int()
     .min(1, 'Quantity must be at least 1')
-    .max(1000, 'Please contact us for quantities over 1000'),
+    .max(10000, 'Please contact us for quantities over 10000'),
 
   // Rush order flag
   is_rush: z.boolean(),
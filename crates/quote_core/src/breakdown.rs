@@ -0,0 +1,440 @@
+//! Step-by-step explanation of a priced quote
+//!
+//! Every amount on a quote becomes a step with a stable reason code and the
+//! values it was worked out from, so the API, printed quotes and the frontend
+//! can show (and translate) the same explanation.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ral_color, BatchFeeKind, Money, PriceBook, PricedQuote, Quote, QuoteLine, QuoteLineOutput,
+};
+
+/// Why an amount is on the quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ReasonCode {
+    /// Coated area at the base rate, with the material multiplier
+    BaseRate,
+    /// Share of the base price taken off for the quantity tier
+    VolumeDiscount,
+    /// Surface preparation per m²
+    PrepSurcharge,
+    /// Special finishes, special powders and colors not in stock, per m²
+    ColorSurcharge,
+    /// An additional coat: material, labour, masking and its oven cycle
+    Coat,
+    /// Share of the discounted base price for a turnaround below the lead time
+    RushSurcharge,
+    SetupFee,
+    ColorChangeFee,
+    /// Top-up to the minimum order charge
+    MinimumOrder,
+    Vat,
+}
+
+/// What a step's `basis` counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum BasisUnit {
+    /// Coated area in m²; `rate` is per m²
+    SquareMeters,
+    /// Number of times a fee applies; `rate` is per time
+    Count,
+    /// An amount in the quote's currency; `rate` is a fraction of it
+    Amount,
+}
+
+/// One amount on the quote and how it was worked out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BreakdownStep {
+    pub code: ReasonCode,
+    /// What `rate` applies to, in `unit`s
+    pub basis: f64,
+    pub unit: BasisUnit,
+    /// Rate per unit of `basis`, in the quote's currency or as a fraction for amounts
+    pub rate: Option<f64>,
+    /// Factor on top of `basis` × `rate`, e.g. the material multiplier
+    pub multiplier: Option<f64>,
+    /// Rounded amount; discounts are negative so the steps add up to the total
+    pub amount: Money,
+    /// Values the description is built from (material, RAL code, tier, ...),
+    /// for translating it
+    pub params: BTreeMap<String, String>,
+    /// The step in English, e.g. "Base rate for steel: 1.90 m² × 25.00 EUR/m² × 1.2"
+    pub description: String,
+}
+
+/// Steps of one quote line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LineBreakdown {
+    pub reference: Option<String>,
+    pub quantity: u32,
+    /// Coated area of a single part in m²
+    pub area_per_part_m2: f64,
+    /// Coated area across all parts of the line in m²
+    pub coated_area_m2: f64,
+    pub steps: Vec<BreakdownStep>,
+    /// Sum of `steps`, the line total
+    pub total: Money,
+}
+
+/// Explanation of a whole quote
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PriceBreakdown {
+    pub lines: Vec<LineBreakdown>,
+    /// Batch fees and VAT, charged once for the order
+    pub order: Vec<BreakdownStep>,
+}
+
+impl PriceBreakdown {
+    /// Sum of every step: what the customer pays, VAT included
+    pub fn total(&self) -> i64 {
+        self.lines
+            .iter()
+            .flat_map(|line| &line.steps)
+            .chain(&self.order)
+            .fold(0i64, |sum, step| sum.saturating_add(step.amount.amount))
+    }
+}
+
+impl BreakdownStep {
+    fn new(code: ReasonCode, basis: f64, unit: BasisUnit, amount: Money) -> Self {
+        BreakdownStep {
+            code,
+            basis,
+            unit,
+            rate: None,
+            multiplier: None,
+            amount,
+            params: BTreeMap::new(),
+            description: String::new(),
+        }
+    }
+
+    fn rate(mut self, rate: f64) -> Self {
+        self.rate = Some(rate);
+        self
+    }
+
+    fn param(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Fill in the English description from the other fields
+    fn describe(mut self, what: &str) -> Self {
+        let currency = self.amount.currency;
+        let basis = match self.unit {
+            BasisUnit::SquareMeters => format!("{:.2} m²", self.basis),
+            BasisUnit::Count => format!("{}", self.basis),
+            BasisUnit::Amount => format!("{:.2} {}", self.basis, currency),
+        };
+        let mut description = match (self.rate, self.unit) {
+            (Some(rate), BasisUnit::Amount) => format!("{} of {}", percent(rate), basis),
+            (Some(rate), BasisUnit::SquareMeters) => {
+                format!("{} × {:.2} {}/m²", basis, rate, currency)
+            }
+            (Some(rate), BasisUnit::Count) => format!("{} × {:.2} {}", basis, rate, currency),
+            (None, _) => basis,
+        };
+        if let Some(multiplier) = self.multiplier {
+            description.push_str(&format!(" × {}", multiplier));
+        }
+        self.description = format!("{}: {}", what, description);
+        self
+    }
+}
+
+/// Explain how `priced` was worked out from `quote` and `book`
+pub(crate) fn explain(quote: &Quote, priced: &PricedQuote, book: &PriceBook) -> PriceBreakdown {
+    let fx = priced.exchange_rate;
+
+    let lines = quote
+        .lines
+        .iter()
+        .zip(&priced.lines)
+        .map(|(line, output)| explain_line(line, output, priced, book))
+        .collect();
+
+    let mut order: Vec<BreakdownStep> = priced
+        .batch_fees
+        .iter()
+        .map(|fee| {
+            let count = fee.count as f64;
+            match fee.kind {
+                BatchFeeKind::Setup => {
+                    BreakdownStep::new(ReasonCode::SetupFee, count, BasisUnit::Count, fee.amount)
+                        .rate(book.batch_fees.setup * fx)
+                        .describe("Setup")
+                }
+                BatchFeeKind::ColorChange => BreakdownStep::new(
+                    ReasonCode::ColorChangeFee,
+                    count,
+                    BasisUnit::Count,
+                    fee.amount,
+                )
+                .rate(book.batch_fees.color_change * fx)
+                .describe("Color changes between powders"),
+                BatchFeeKind::MinimumOrder => BreakdownStep::new(
+                    ReasonCode::MinimumOrder,
+                    count,
+                    BasisUnit::Count,
+                    fee.amount,
+                )
+                .param(
+                    "minimum_order",
+                    format!("{:.2}", book.batch_fees.minimum_order * fx),
+                )
+                .describe(&format!(
+                    "Top-up to the {:.2} {} minimum order",
+                    book.batch_fees.minimum_order * fx,
+                    priced.currency
+                )),
+            }
+        })
+        .collect();
+
+    if let Some(vat) = &priced.vat {
+        order.push(
+            BreakdownStep::new(
+                ReasonCode::Vat,
+                vat.net.to_major(),
+                BasisUnit::Amount,
+                vat.vat,
+            )
+            .rate(vat.rate)
+            .param("treatment", vat.treatment.as_str())
+            .param("country", &vat.country)
+            .describe(&format!(
+                "VAT ({}, {})",
+                vat.treatment.as_str(),
+                vat.country
+            )),
+        );
+    }
+
+    PriceBreakdown { lines, order }
+}
+
+fn explain_line(
+    line: &QuoteLine,
+    output: &QuoteLineOutput,
+    priced: &PricedQuote,
+    book: &PriceBook,
+) -> LineBreakdown {
+    let fx = priced.exchange_rate;
+    let area = output.coated_area_m2;
+    let material = serde_name(&line.material);
+    let mut steps = Vec::new();
+
+    let mut base = BreakdownStep::new(
+        ReasonCode::BaseRate,
+        area,
+        BasisUnit::SquareMeters,
+        output.base_price,
+    )
+    .rate(book.base_rate_per_m2 * fx)
+    .param("material", &material);
+    base.multiplier = Some(book.material_multipliers.for_material(&line.material));
+    steps.push(base.describe(&format!("Base rate for {}", material.to_lowercase())));
+
+    if let Some(tier) = output
+        .quantity_tier
+        .as_ref()
+        .filter(|_| output.volume_discount.is_positive())
+    {
+        steps.push(
+            BreakdownStep::new(
+                ReasonCode::VolumeDiscount,
+                output.base_price.to_major(),
+                BasisUnit::Amount,
+                Money::new(-output.volume_discount.amount, priced.currency),
+            )
+            .rate(tier.discount)
+            .param("min_quantity", tier.min_quantity)
+            .describe(&format!("Volume discount for {}+ parts", tier.min_quantity)),
+        );
+    }
+
+    if output.prep_surcharge.is_positive() {
+        let prep_level = serde_name(&line.prep_level);
+        steps.push(
+            BreakdownStep::new(
+                ReasonCode::PrepSurcharge,
+                area,
+                BasisUnit::SquareMeters,
+                output.prep_surcharge,
+            )
+            .rate(book.prep_rates_per_m2.for_level(&line.prep_level) * fx)
+            .param("prep_level", &prep_level)
+            .describe(&format!("{} preparation", prep_level)),
+        );
+    }
+
+    if output.color_surcharge.is_positive() {
+        let finish = serde_name(&line.finish);
+        let rate = ral_color(&line.color)
+            .map_or(0.0, |color| book.colors.rate_per_m2(&color, line.finish));
+        steps.push(
+            BreakdownStep::new(
+                ReasonCode::ColorSurcharge,
+                area,
+                BasisUnit::SquareMeters,
+                output.color_surcharge,
+            )
+            .rate(rate * fx)
+            .param("color", &line.color)
+            .param("finish", &finish)
+            .describe(&format!("RAL {} {}", line.color, finish)),
+        );
+    }
+
+    for coat in &output.coats {
+        let mut step = BreakdownStep::new(
+            ReasonCode::Coat,
+            coat.area_m2,
+            BasisUnit::SquareMeters,
+            coat.total,
+        )
+        .param("kind", serde_name(&coat.kind))
+        .param("finish", serde_name(&coat.finish));
+        if let Some(color) = &coat.color {
+            step = step.param("color", color);
+        }
+        steps.push(step.describe(&format!("{} coat", coat.kind.label())));
+    }
+
+    if output.rush_surcharge.is_positive() {
+        let discounted = output.base_price.amount - output.volume_discount.amount;
+        steps.push(
+            BreakdownStep::new(
+                ReasonCode::RushSurcharge,
+                Money::new(discounted, priced.currency).to_major(),
+                BasisUnit::Amount,
+                output.rush_surcharge,
+            )
+            .rate(priced.rush_rate)
+            .param(
+                "lead_days",
+                priced.lead_days.unwrap_or(book.rush.below_days),
+            )
+            .describe("Rush surcharge"),
+        );
+    }
+
+    LineBreakdown {
+        reference: output.reference.clone(),
+        quantity: line.quantity,
+        area_per_part_m2: output.area_per_part_m2,
+        coated_area_m2: area,
+        steps,
+        total: output.total_price,
+    }
+}
+
+/// The name a value serializes to, e.g. `"blast_clean"` or `"BlastClean"`
+fn serde_name(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// A fraction as a percentage for display, e.g. "12.5%"
+fn percent(rate: f64) -> String {
+    format!("{}%", (rate * 1000.0).round() / 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_book::newest_standard_book;
+    use crate::{price_quote, Coat, CoatKind, Finish, Material, PartShape, PrepLevel, QuoteLine};
+
+    fn line(color: &str, quantity: u32) -> QuoteLine {
+        QuoteLine {
+            reference: None,
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::BlastClean,
+            color: color.to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_steps_add_up_to_amount_due() {
+        let mut frame = line("9005", 30);
+        frame.coats.push(Coat {
+            kind: CoatKind::Clear,
+            color: None,
+            finish: Finish::Gloss,
+            coverage_percent: 100.0,
+        });
+        let quote = Quote {
+            lines: vec![frame, line("1026", 2)],
+            turnaround_days: 3,
+            is_rush: true,
+            currency: None,
+            customer: None,
+        };
+
+        let priced = price_quote(&quote, &newest_standard_book()).unwrap();
+        let breakdown = &priced.breakdown;
+
+        assert_eq!(breakdown.total(), priced.amount_due().amount);
+        for (line, output) in breakdown.lines.iter().zip(&priced.lines) {
+            let sum: i64 = line.steps.iter().map(|step| step.amount.amount).sum();
+            assert_eq!(sum, output.total_price.amount);
+        }
+
+        let codes = |steps: &[BreakdownStep]| steps.iter().map(|s| s.code).collect::<Vec<_>>();
+        assert_eq!(
+            codes(&breakdown.lines[0].steps),
+            [
+                ReasonCode::BaseRate,
+                ReasonCode::VolumeDiscount,
+                ReasonCode::PrepSurcharge,
+                ReasonCode::Coat,
+                ReasonCode::RushSurcharge
+            ]
+        );
+        assert_eq!(
+            codes(&breakdown.order),
+            [ReasonCode::ColorChangeFee, ReasonCode::Vat]
+        );
+
+        let base = &breakdown.lines[0].steps[0];
+        assert_eq!(base.basis, 1.9 * 30.0);
+        assert_eq!(base.rate, Some(25.0));
+        assert_eq!(base.multiplier, Some(0.9));
+        assert_eq!(base.params["material"], "Steel");
+        assert_eq!(
+            base.description,
+            "Base rate for steel: 57.00 m² × 25.00 EUR/m² × 0.9"
+        );
+
+        let discount = &breakdown.lines[0].steps[1];
+        assert_eq!(
+            discount.amount.amount,
+            -priced.lines[0].volume_discount.amount
+        );
+        assert_eq!(discount.params["min_quantity"], "25");
+
+        // The fluorescent second part is the one with a color surcharge
+        assert!(codes(&breakdown.lines[1].steps).contains(&ReasonCode::ColorSurcharge));
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Quote;
+
+/// Longest lead time the scheduler looks ahead, in days
+pub const MAX_LEAD_DAYS: u32 = 365;
+
+/// Oven capacity of the shop, for load-based lead times
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Capacity {
+    /// Oven hours available each day
+    pub oven_hours_per_day: f64,
+    /// Coated area one oven hour cures, in m²
+    pub m2_per_oven_hour: f64,
+    /// Prep, curing and packing take at least this many days even when the
+    /// oven is free
+    pub min_lead_days: u32,
+}
+
+/// Oven hours already booked, day by day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ShopLoad {
+    /// Booked hours per day, starting today; days past the end are free
+    pub booked_hours: Vec<f64>,
+}
+
+impl ShopLoad {
+    fn booked(&self, day: usize) -> f64 {
+        self.booked_hours.get(day).copied().unwrap_or(0.0)
+    }
+}
+
+impl Capacity {
+    /// Oven time a quote needs: every part once, plus one pass per additional coat
+    pub fn oven_hours(&self, quote: &Quote) -> f64 {
+        let m2: f64 = quote
+            .lines
+            .iter()
+            .map(|line| {
+                let area =
+                    line.shape
+                        .surface_area_m2(line.length_mm, line.width_mm, line.height_mm)
+                        * line.quantity as f64;
+                let coats: f64 = line
+                    .coats
+                    .iter()
+                    .map(|coat| area * coat.coverage_percent / 100.0)
+                    .sum();
+                area + coats
+            })
+            .sum();
+
+        m2 / self.m2_per_oven_hour
+    }
+
+    /// Spread `hours` over the free oven time from today on
+    ///
+    /// Returns the hours used on each day, by day offset from today. The job
+    /// is cut off at [`MAX_LEAD_DAYS`] if the oven is booked solid until then.
+    pub fn schedule(&self, load: &ShopLoad, hours: f64) -> Vec<(u32, f64)> {
+        let mut remaining = hours;
+        let mut slots = Vec::new();
+
+        for day in 0..MAX_LEAD_DAYS {
+            if remaining <= 0.0 {
+                break;
+            }
+            let free = (self.oven_hours_per_day - load.booked(day as usize)).max(0.0);
+            let used = free.min(remaining);
+            if used > 0.0 {
+                slots.push((day, used));
+                remaining -= used;
+            }
+        }
+
+        slots
+    }
+
+    /// Days until a job of `hours` is ready, counting from today
+    ///
+    /// A job that fits in today's free oven time is ready tomorrow, i.e. in 1
+    /// day, unless `min_lead_days` is longer.
+    pub fn lead_days(&self, load: &ShopLoad, hours: f64) -> u32 {
+        self.schedule(load, hours)
+            .last()
+            .map_or(1, |(day, _)| day + 1)
+            .max(self.min_lead_days)
+            .min(MAX_LEAD_DAYS)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.oven_hours_per_day.is_finite()
+            || self.oven_hours_per_day <= 0.0
+            || self.oven_hours_per_day > 24.0
+        {
+            return Err("capacity.oven_hours_per_day must be above 0 and at most 24".into());
+        }
+        if !self.m2_per_oven_hour.is_finite() || self.m2_per_oven_hour <= 0.0 {
+            return Err("capacity.m2_per_oven_hour must be a positive number".into());
+        }
+        if self.min_lead_days > MAX_LEAD_DAYS {
+            return Err(format!(
+                "capacity.min_lead_days must be at most {}",
+                MAX_LEAD_DAYS
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capacity() -> Capacity {
+        Capacity {
+            oven_hours_per_day: 8.0,
+            m2_per_oven_hour: 10.0,
+            min_lead_days: 2,
+        }
+    }
+
+    #[test]
+    fn test_schedule_fills_free_oven_time() {
+        let load = ShopLoad {
+            booked_hours: vec![8.0, 5.0, 0.0],
+        };
+
+        // Today is full, tomorrow has 3 hours left, the day after is free
+        assert_eq!(capacity().schedule(&load, 6.0), [(1, 3.0), (2, 3.0)]);
+        assert_eq!(capacity().lead_days(&load, 6.0), 3);
+        assert_eq!(capacity().lead_days(&load, 20.0), 5);
+    }
+
+    #[test]
+    fn test_quiet_shop_keeps_minimum_lead_time() {
+        let load = ShopLoad::default();
+
+        assert_eq!(capacity().lead_days(&load, 1.0), 2);
+        assert_eq!(capacity().lead_days(&load, 0.0), 2);
+    }
+}
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ral_color, FieldError, Finish, Money, PrepLevel, PriceBook, QuoteError, QuoteLine};
+
+/// Most coats a part can get on top of its main color coat
+pub const MAX_ADDITIONAL_COATS: usize = 3;
+
+/// Kind of coat in a coat stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CoatKind {
+    /// Zinc-rich primer for corrosion protection on steel
+    ZincPrimer,
+    /// Epoxy primer for adhesion and edge cover
+    EpoxyPrimer,
+    /// A further color; with partial coverage this is a masked two-tone job
+    Color,
+    /// Clear top coat, e.g. over metallics
+    Clear,
+}
+
+impl CoatKind {
+    pub fn is_primer(self) -> bool {
+        matches!(self, CoatKind::ZincPrimer | CoatKind::EpoxyPrimer)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CoatKind::ZincPrimer => "Zinc-Rich Primer",
+            CoatKind::EpoxyPrimer => "Epoxy Primer",
+            CoatKind::Color => "Second Color",
+            CoatKind::Clear => "Clear",
+        }
+    }
+}
+
+/// A coat applied in addition to the main color coat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Coat {
+    pub kind: CoatKind,
+    /// RAL code, required for `color` coats
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub finish: Finish,
+    /// Share of the part's coated area this coat covers (0-100)
+    #[serde(default = "full_coverage")]
+    pub coverage_percent: f64,
+}
+
+fn full_coverage() -> f64 {
+    100.0
+}
+
+/// Cost of one additional coat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CoatLine {
+    pub kind: CoatKind,
+    pub color: Option<String>,
+    pub finish: Finish,
+    /// Area covered across all parts in m²
+    pub area_m2: f64,
+    pub material: Money,
+    pub labour: Money,
+    /// Color and finish surcharge for the coat's powder
+    pub color_surcharge: Money,
+    /// Masking the rest of the part, for partial coverage
+    pub masking: Money,
+    /// The extra cure cycle the coat needs
+    pub oven_cycle: Money,
+    /// Sum of the amounts above
+    pub total: Money,
+}
+
+/// Material and labour rates of one coat kind, per m²
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CoatRates {
+    pub material_per_m2: f64,
+    pub labour_per_m2: f64,
+}
+
+/// Price book rates for coats beyond the main color coat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CoatPricing {
+    pub zinc_primer: CoatRates,
+    pub epoxy_primer: CoatRates,
+    pub color: CoatRates,
+    pub clear: CoatRates,
+    /// Masking labour per m² of a partially covering coat's area
+    pub masking_per_m2: f64,
+    /// Flat charge for each extra oven cycle
+    pub oven_cycle: f64,
+}
+
+impl CoatPricing {
+    pub fn rates(&self, kind: CoatKind) -> &CoatRates {
+        match kind {
+            CoatKind::ZincPrimer => &self.zinc_primer,
+            CoatKind::EpoxyPrimer => &self.epoxy_primer,
+            CoatKind::Color => &self.color,
+            CoatKind::Clear => &self.clear,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let kinds = [
+            ("zinc_primer", &self.zinc_primer),
+            ("epoxy_primer", &self.epoxy_primer),
+            ("color", &self.color),
+            ("clear", &self.clear),
+        ];
+        let mut rates: Vec<(String, f64)> = kinds
+            .iter()
+            .flat_map(|(name, rates)| {
+                [
+                    (
+                        format!("coats.{}.material_per_m2", name),
+                        rates.material_per_m2,
+                    ),
+                    (format!("coats.{}.labour_per_m2", name), rates.labour_per_m2),
+                ]
+            })
+            .collect();
+        rates.push(("coats.masking_per_m2".to_string(), self.masking_per_m2));
+        rates.push(("coats.oven_cycle".to_string(), self.oven_cycle));
+
+        match rates
+            .into_iter()
+            .find(|(_, value)| !value.is_finite() || *value < 0.0)
+        {
+            Some((name, _)) => Err(format!("{} must be a non-negative number", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Check the coat stack is one the shop can apply
+pub(crate) fn validate_coats(line: &QuoteLine, prefix: &str, errors: &mut Vec<FieldError>) {
+    let coats_field = format!("{}coats", prefix);
+
+    if line.coats.len() > MAX_ADDITIONAL_COATS {
+        errors.push(FieldError::new(
+            &coats_field,
+            format!("at most {} additional coats", MAX_ADDITIONAL_COATS),
+        ));
+    }
+
+    if line.coats.iter().filter(|c| c.kind.is_primer()).count() > 1 {
+        errors.push(FieldError::new(
+            &coats_field,
+            "only one primer coat is possible",
+        ));
+    }
+
+    if line
+        .coats
+        .iter()
+        .filter(|c| c.kind == CoatKind::Clear)
+        .count()
+        > 1
+    {
+        errors.push(FieldError::new(
+            &coats_field,
+            "only one clear coat is possible",
+        ));
+    }
+
+    if matches!(line.prep_level, PrepLevel::BlastPrime)
+        && line.coats.iter().any(|c| c.kind.is_primer())
+    {
+        errors.push(FieldError::new(
+            &coats_field,
+            "BlastPrime already includes a primer; use BlastClean with a primer coat",
+        ));
+    }
+
+    for (i, coat) in line.coats.iter().enumerate() {
+        let field = |name: &str| format!("{}[{}].{}", coats_field, i, name);
+
+        if !coat.coverage_percent.is_finite()
+            || coat.coverage_percent <= 0.0
+            || coat.coverage_percent > 100.0
+        {
+            errors.push(FieldError::new(
+                &field("coverage_percent"),
+                "must be above 0 and at most 100",
+            ));
+        }
+
+        match (coat.kind, coat.color.as_deref()) {
+            (CoatKind::Color, None) => {
+                errors.push(FieldError::new(
+                    &field("color"),
+                    "a color coat needs a RAL code",
+                ));
+            }
+            (CoatKind::Color, Some(code)) if ral_color(code).is_none() => {
+                errors.push(FieldError::new(
+                    &field("color"),
+                    format!("RAL {} is not in the RAL Classic catalog", code),
+                ));
+            }
+            (kind, Some(_)) if kind != CoatKind::Color => {
+                errors.push(FieldError::new(
+                    &field("color"),
+                    format!("{} coats have no color", kind.label().to_lowercase()),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Price each additional coat, in application order
+pub(crate) fn price_coats(
+    line: &QuoteLine,
+    area_per_part_m2: f64,
+    book: &PriceBook,
+    money: impl Fn(f64) -> Money,
+) -> Result<Vec<CoatLine>, QuoteError> {
+    if line.coats.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let pricing = book.coats.as_ref().ok_or_else(|| {
+        QuoteError::Invalid(vec![FieldError::new(
+            "coats",
+            format!(
+                "price book {} does not price additional coats",
+                book.version
+            ),
+        )])
+    })?;
+
+    let mut coats: Vec<&Coat> = line.coats.iter().collect();
+    coats.sort_by_key(|coat| coat.kind);
+
+    let lines = coats
+        .into_iter()
+        .map(|coat| {
+            let area = area_per_part_m2 * line.quantity as f64 * coat.coverage_percent / 100.0;
+            let rates = pricing.rates(coat.kind);
+
+            let color_rate = coat
+                .color
+                .as_deref()
+                .and_then(ral_color)
+                .map_or(0.0, |color| book.colors.rate_per_m2(&color, coat.finish));
+            let masking_rate = if coat.coverage_percent < 100.0 {
+                pricing.masking_per_m2
+            } else {
+                0.0
+            };
+
+            let material = money(area * rates.material_per_m2);
+            let labour = money(area * rates.labour_per_m2);
+            let color_surcharge = money(area * color_rate);
+            let masking = money(area * masking_rate);
+            let oven_cycle = money(pricing.oven_cycle);
+            let total = Money::new(
+                [material, labour, color_surcharge, masking, oven_cycle]
+                    .iter()
+                    .fold(0i64, |sum, m| sum.saturating_add(m.amount)),
+                material.currency,
+            );
+
+            CoatLine {
+                kind: coat.kind,
+                color: coat.color.clone(),
+                finish: coat.finish,
+                area_m2: area,
+                material,
+                labour,
+                color_surcharge,
+                masking,
+                oven_cycle,
+                total,
+            }
+        })
+        .collect();
+
+    Ok(lines)
+}
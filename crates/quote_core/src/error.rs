@@ -0,0 +1,122 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Currency, MeshError, PriceBookError};
+
+/// A problem with one field of the quote input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FieldError {
+    /// Field path, e.g. `"length_mm"` or `"customer.country"`
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> FieldError {
+        FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Why a quote could not be priced
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuoteError {
+    /// The input is not valid JSON for a quote
+    Malformed(String),
+    /// One or more fields are out of range
+    Invalid(Vec<FieldError>),
+    /// The price book has no exchange rate for the requested currency
+    UnsupportedCurrency {
+        currency: Currency,
+        price_book_version: String,
+    },
+    /// The customer is in an EU country the price book has no VAT rate for
+    UnsupportedCountry { country: String },
+    /// The VAT number does not belong to the customer's country
+    InvalidVatId { vat_id: String, country: String },
+    /// The uploaded mesh could not be read
+    Mesh(MeshError),
+    /// The price book supplied with the quote is unusable
+    PriceBook(PriceBookError),
+}
+
+impl QuoteError {
+    /// Stable machine-readable code, used as `error` in API and WASM responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            QuoteError::Malformed(_) => "malformed_input",
+            QuoteError::Invalid(_) => "invalid_input",
+            QuoteError::UnsupportedCurrency { .. } => "unsupported_currency",
+            QuoteError::UnsupportedCountry { .. } => "unsupported_country",
+            QuoteError::InvalidVatId { .. } => "invalid_vat_id",
+            QuoteError::Mesh(_) => "invalid_mesh",
+            QuoteError::PriceBook(_) => "invalid_price_book",
+        }
+    }
+
+    /// Per-field details; empty unless the input failed validation
+    pub fn field_errors(&self) -> &[FieldError] {
+        match self {
+            QuoteError::Invalid(errors) => errors,
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteError::Malformed(message) => write!(f, "malformed quote input: {}", message),
+            QuoteError::Invalid(errors) => {
+                write!(f, "invalid quote input: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{} {}", error.field, error.message)?;
+                }
+                Ok(())
+            }
+            QuoteError::UnsupportedCurrency {
+                currency,
+                price_book_version,
+            } => write!(
+                f,
+                "price book {} has no exchange rate for {}",
+                price_book_version, currency
+            ),
+            QuoteError::UnsupportedCountry { country } => {
+                write!(f, "no VAT rate configured for {}", country)
+            }
+            QuoteError::InvalidVatId { vat_id, country } => {
+                write!(f, "{} is not a valid VAT number for {}", vat_id, country)
+            }
+            QuoteError::Mesh(e) => write!(f, "{}", e),
+            QuoteError::PriceBook(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {}
+
+impl From<MeshError> for QuoteError {
+    fn from(e: MeshError) -> Self {
+        QuoteError::Mesh(e)
+    }
+}
+
+impl From<PriceBookError> for QuoteError {
+    fn from(e: PriceBookError) -> Self {
+        QuoteError::PriceBook(e)
+    }
+}
+
+impl From<serde_json::Error> for QuoteError {
+    fn from(e: serde_json::Error) -> Self {
+        QuoteError::Malformed(e.to_string())
+    }
+}
@@ -0,0 +1,150 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+/// Share of a wheel disc covered by spokes, per face
+const WHEEL_SPOKE_FACE_FRACTION: f64 = 0.4;
+
+/// Part shape used to turn the bounding dimensions into a coated area
+///
+/// `length_mm`, `width_mm` and `height_mm` on the quote always describe the
+/// part's bounding box; each shape documents how it reads them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum PartShape {
+    /// Closed box, all six faces coated
+    #[default]
+    Box,
+    /// Sheet of `length × width` with `height` as the thickness; both faces and edges
+    FlatPanel,
+    /// Round tube of `length` with outer diameter `width`; open ends
+    RoundTube {
+        /// Also coat the bore (thin-wall approximation)
+        coat_inside: bool,
+    },
+    /// Rectangular hollow section of `length` with a `width × height` profile; open ends
+    RectangularTube {
+        /// Also coat the inside (thin-wall approximation)
+        coat_inside: bool,
+    },
+    /// Solid cylinder (shaft, roller) of `length` with diameter `width`, end faces included
+    Cylinder,
+    /// Wheel rim of diameter `length` with barrel width `height`
+    WheelRim,
+    /// Wire mesh, grating or railing infill panel of `length × width`
+    WireMesh {
+        /// Percentage of the panel that is open space (0-100)
+        open_area_percent: f64,
+    },
+    /// Box with one `length × width` face open (tray, enclosure)
+    OpenBox {
+        /// Also coat the inside faces
+        coat_inside: bool,
+    },
+    /// Coated area measured by the customer, per part
+    ExplicitArea { area_m2: f64 },
+}
+
+impl PartShape {
+    /// Coated area of one part in m²
+    pub fn surface_area_m2(&self, length_mm: f64, width_mm: f64, height_mm: f64) -> f64 {
+        let (l, w, h) = (length_mm, width_mm, height_mm);
+
+        let area_mm2 = match self {
+            PartShape::Box => 2.0 * (l * w + l * h + w * h),
+            PartShape::FlatPanel => 2.0 * l * w + 2.0 * (l + w) * h,
+            PartShape::RoundTube { coat_inside } => sides(PI * w * l, *coat_inside),
+            PartShape::RectangularTube { coat_inside } => sides(2.0 * (w + h) * l, *coat_inside),
+            PartShape::Cylinder => PI * w * l + 2.0 * disc(w),
+            PartShape::WheelRim => {
+                // Barrel inside and outside plus the spokes on both faces
+                2.0 * PI * l * h + 2.0 * WHEEL_SPOKE_FACE_FRACTION * disc(l)
+            }
+            PartShape::WireMesh { open_area_percent } => {
+                // Round wire exposes π times its projected width, front and back combined
+                let solid_fraction = 1.0 - open_area_percent.clamp(0.0, 100.0) / 100.0;
+                PI * l * w * solid_fraction
+            }
+            PartShape::OpenBox { coat_inside } => {
+                sides(l * w + 2.0 * l * h + 2.0 * w * h, *coat_inside)
+            }
+            PartShape::ExplicitArea { area_m2 } => return *area_m2,
+        };
+
+        area_mm2 / 1_000_000.0 // Convert mm² to m²
+    }
+}
+
+/// Outside area, doubled when the inside is coated too
+fn sides(outside: f64, coat_inside: bool) -> f64 {
+    if coat_inside {
+        2.0 * outside
+    } else {
+        outside
+    }
+}
+
+/// Area of a disc of the given diameter
+fn disc(diameter: f64) -> f64 {
+    PI * diameter * diameter / 4.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_box_matches_bounding_box_area() {
+        let area = PartShape::Box.surface_area_m2(1000.0, 500.0, 300.0);
+        assert!(approx(area, 1.9));
+    }
+
+    #[test]
+    fn test_flat_panel_counts_faces_and_edges() {
+        let area = PartShape::FlatPanel.surface_area_m2(1000.0, 500.0, 2.0);
+        assert!(approx(area, 1.0 + 0.006));
+    }
+
+    #[test]
+    fn test_round_tube_is_far_below_box_estimate() {
+        let tube = PartShape::RoundTube { coat_inside: false };
+        let area = tube.surface_area_m2(2000.0, 50.0, 50.0);
+
+        assert!(approx(area, PI * 0.05 * 2.0));
+        assert!(area < PartShape::Box.surface_area_m2(2000.0, 50.0, 50.0));
+
+        let both = PartShape::RoundTube { coat_inside: true }.surface_area_m2(2000.0, 50.0, 50.0);
+        assert!(approx(both, 2.0 * area));
+    }
+
+    #[test]
+    fn test_wire_mesh_scales_with_solid_fraction() {
+        let open = PartShape::WireMesh {
+            open_area_percent: 80.0,
+        };
+        let area = open.surface_area_m2(1000.0, 1000.0, 5.0);
+        assert!(approx(area, PI * 0.2));
+
+        let clamped = PartShape::WireMesh {
+            open_area_percent: 150.0,
+        };
+        assert_eq!(clamped.surface_area_m2(1000.0, 1000.0, 5.0), 0.0);
+    }
+
+    #[test]
+    fn test_open_box_drops_one_face() {
+        let tray = PartShape::OpenBox { coat_inside: false };
+        let area = tray.surface_area_m2(1000.0, 500.0, 300.0);
+        assert!(approx(area, 1.9 - 0.5));
+    }
+
+    #[test]
+    fn test_explicit_area_ignores_dimensions() {
+        let shape = PartShape::ExplicitArea { area_m2: 0.75 };
+        assert_eq!(shape.surface_area_m2(5000.0, 5000.0, 5000.0), 0.75);
+    }
+}
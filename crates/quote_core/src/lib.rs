@@ -1,22 +1,70 @@
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+mod breakdown;
+mod capacity;
+mod coats;
+mod error;
+mod geometry;
+mod mesh;
+mod money;
+mod price_book;
+mod quote;
+mod ral;
+mod tax;
+mod validation;
+
+pub use breakdown::{BasisUnit, BreakdownStep, LineBreakdown, PriceBreakdown, ReasonCode};
+pub use capacity::{Capacity, ShopLoad, MAX_LEAD_DAYS};
+pub use coats::{Coat, CoatKind, CoatLine, CoatPricing, CoatRates, MAX_ADDITIONAL_COATS};
+pub use error::{FieldError, QuoteError};
+pub use geometry::PartShape;
+pub use mesh::{analyze_mesh, MeshError, MeshFormat, MeshSummary, MeshUnits};
+pub use money::{Currency, Money, Rounding, RoundingMode, RoundingScope};
+pub use price_book::{
+    BatchFees, ColorPricing, Deposit, FinishRates, MaterialMultipliers, PrepRates, PriceBook,
+    PriceBookError, PriceBookSet, QuantityTier, RushRule, RushStep,
+};
+pub use quote::{
+    price_quote, price_quote_with_lead_time, BatchFee, BatchFeeKind, PricedQuote, Quote, QuoteLine,
+    QuoteLineOutput, MAX_QUOTE_LINES,
+};
+pub use ral::{ral_catalog, ral_color, Finish, RalColor};
+pub use tax::{Customer, CustomerType, VatBreakdown, VatRules, VatTreatment};
+pub use validation::{
+    MAX_AREA_M2, MAX_DIMENSION_MM, MAX_QUANTITY, MAX_TURNAROUND_DAYS, MIN_DIMENSION_MM,
+    MIN_QUANTITY, MIN_TURNAROUND_DAYS,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct QuoteInput {
     /// Dimensions in millimeters: length x width x height
     pub length_mm: f64,
     pub width_mm: f64,
     pub height_mm: f64,
 
+    /// Part shape used to derive the coated area from the dimensions
+    #[serde(default)]
+    pub shape: PartShape,
+
     /// Material type
     pub material: Material,
 
     /// Surface preparation level
     pub prep_level: PrepLevel,
 
-    /// RAL color code (e.g., "9005", "9016")
+    /// RAL Classic color code (e.g., "9005", "9016")
     pub color: String,
 
+    /// Powder finish
+    #[serde(default)]
+    pub finish: Finish,
+
+    /// Coats on top of the main color coat: primer, second color or clear
+    #[serde(default)]
+    pub coats: Vec<Coat>,
+
     /// Turnaround time in days
     pub turnaround_days: u32,
 
@@ -25,9 +73,18 @@ pub struct QuoteInput {
 
     /// Rush order flag
     pub is_rush: bool,
+
+    /// Currency to price in; the price book's own currency when omitted
+    #[serde(default)]
+    pub currency: Option<Currency>,
+
+    /// Who is invoiced, for VAT; a domestic consumer when omitted
+    #[serde(default)]
+    pub customer: Option<Customer>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum Material {
     Aluminium,
     Steel,
@@ -35,6 +92,7 @@ pub enum Material {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum PrepLevel {
     Clean,      // Basic cleaning
     BlastClean, // Blast + clean
@@ -42,71 +100,257 @@ pub enum PrepLevel {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct QuoteOutput {
-    pub base_price: f64,
-    pub prep_surcharge: f64,
-    pub rush_surcharge: f64,
-    pub total_price: f64,
-    pub currency: String,
-}
-
-/// Calculate quote price (native Rust function)
-pub fn calculate_quote(input: &QuoteInput) -> QuoteOutput {
-    // Calculate surface area (simplified - treating as a box)
-    let surface_area = 2.0
-        * (input.length_mm * input.width_mm
-            + input.length_mm * input.height_mm
-            + input.width_mm * input.height_mm)
-        / 1_000_000.0; // Convert mm² to m²
-
-    // Base price per m² (EUR)
-    let base_rate = 25.0;
-    let mut base_price = surface_area * base_rate * input.quantity as f64;
-
-    // Material multiplier
-    let material_multiplier = match input.material {
-        Material::Aluminium => 1.0,
-        Material::Steel => 0.9,
-        Material::Stainless => 1.2,
-    };
-    base_price *= material_multiplier;
-
-    // Prep surcharge
-    let prep_surcharge = match input.prep_level {
-        PrepLevel::Clean => 0.0,
-        PrepLevel::BlastClean => surface_area * 15.0 * input.quantity as f64,
-        PrepLevel::BlastPrime => surface_area * 25.0 * input.quantity as f64,
-    };
-
-    // Rush surcharge (50% if rush and < 5 days)
-    let rush_surcharge = if input.is_rush && input.turnaround_days < 5 {
-        base_price * 0.5
-    } else {
-        0.0
-    };
-
-    let total_price = base_price + prep_surcharge + rush_surcharge;
-
-    QuoteOutput {
-        base_price,
-        prep_surcharge,
-        rush_surcharge,
-        total_price,
-        currency: "EUR".to_string(),
-    }
-}
-
-/// WASM-exposed function for frontend use
+    /// Coated area of a single part in m²
+    #[serde(default)]
+    pub area_per_part_m2: f64,
+    /// Coated area across all parts in m²
+    #[serde(default)]
+    pub coated_area_m2: f64,
+    pub base_price: Money,
+    pub prep_surcharge: Money,
+    /// Special finishes, special powders and colors not in stock
+    pub color_surcharge: Money,
+    /// Additional coats, the sum of the `coats` totals
+    pub coats_surcharge: Money,
+    pub rush_surcharge: Money,
+    /// Quantity break the part reached, if any
+    pub quantity_tier: Option<QuantityTier>,
+    /// Taken off the base price for the quantity tier
+    pub volume_discount: Money,
+    /// Setup, color change and minimum order charges for the job
+    pub batch_fees: Vec<BatchFee>,
+    /// Net total: the five lines above less `volume_discount`, plus `batch_fees`
+    pub total_price: Money,
+    /// Net price per part, with the batch fees spread across the quantity
+    pub unit_price: Money,
+    /// Each additional coat in application order
+    #[serde(default)]
+    pub coats: Vec<CoatLine>,
+    /// VAT on `total_price`; absent when the price book has no VAT rules
+    #[serde(default)]
+    pub vat: Option<VatBreakdown>,
+    /// Days the shop needs for the part, which rush pricing is measured against
+    pub lead_days: u32,
+    /// Share of the base price added as the rush surcharge
+    pub rush_rate: f64,
+    pub currency: Currency,
+    /// Units of `currency` per unit of the price book's currency
+    #[serde(default = "unit_exchange_rate")]
+    pub exchange_rate: f64,
+    /// Version of the price book the quote was priced with
+    #[serde(default)]
+    pub price_book_version: String,
+    /// How every amount above was worked out
+    #[serde(default)]
+    pub breakdown: PriceBreakdown,
+}
+
+fn unit_exchange_rate() -> f64 {
+    1.0
+}
+
+impl QuoteOutput {
+    /// What the customer pays: the gross amount including VAT
+    pub fn amount_due(&self) -> Money {
+        self.vat.as_ref().map_or(self.total_price, |vat| vat.gross)
+    }
+}
+
+/// Calculate quote price with the given price book (native Rust function)
+///
+/// The input is validated first. Amounts are converted to `input.currency`
+/// with the book's exchange rate before rounding. The part is priced as an
+/// order of one line, so it pays setup and minimum order charges too.
+pub fn calculate_quote(input: &QuoteInput, book: &PriceBook) -> Result<QuoteOutput, QuoteError> {
+    input.validate()?;
+
+    let quote = quote::price(&Quote::from(input.clone()), book, None)?;
+    let line = quote.lines.into_iter().next().expect("one line per input");
+    let unit_price = Money::new(
+        book.rounding
+            .mode
+            .round(quote.total_price.amount as f64 / input.quantity as f64),
+        quote.currency,
+    );
+
+    Ok(QuoteOutput {
+        area_per_part_m2: line.area_per_part_m2,
+        coated_area_m2: line.coated_area_m2,
+        base_price: line.base_price,
+        prep_surcharge: line.prep_surcharge,
+        color_surcharge: line.color_surcharge,
+        coats_surcharge: line.coats_surcharge,
+        rush_surcharge: line.rush_surcharge,
+        quantity_tier: line.quantity_tier,
+        volume_discount: line.volume_discount,
+        batch_fees: quote.batch_fees,
+        total_price: quote.total_price,
+        unit_price,
+        coats: line.coats,
+        vat: quote.vat,
+        lead_days: quote.lead_days.unwrap_or(book.rush.below_days),
+        rush_rate: quote.rush_rate,
+        currency: quote.currency,
+        exchange_rate: quote.exchange_rate,
+        price_book_version: quote.price_book_version,
+        breakdown: quote.breakdown,
+    })
+}
+
+/// Quote priced from an uploaded CAD mesh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MeshQuote {
+    pub mesh: MeshSummary,
+    /// The template input with dimensions and area taken from the mesh
+    pub input: QuoteInput,
+    pub output: QuoteOutput,
+}
+
+/// Price a part from its mesh file (native Rust function)
+///
+/// Material, prep, color and quantity come from `template`; the coated area
+/// and bounding box come from the mesh.
+pub fn calculate_mesh_quote(
+    mesh_bytes: &[u8],
+    format: MeshFormat,
+    units: MeshUnits,
+    template: &QuoteInput,
+    book: &PriceBook,
+) -> Result<MeshQuote, QuoteError> {
+    let mesh = analyze_mesh(mesh_bytes, format, units)?;
+
+    let mut input = template.clone();
+    input.apply_mesh(&mesh);
+    let output = calculate_quote(&input, book)?;
+
+    Ok(MeshQuote {
+        mesh,
+        input,
+        output,
+    })
+}
+
+/// Error object returned by the WASM functions in place of a result
+#[derive(Serialize)]
+struct WasmError<'a> {
+    error: &'static str,
+    message: String,
+    field_errors: &'a [FieldError],
+}
+
+/// Serialize a result for JS: the value itself, or an object with an `error` code
+fn to_wasm_json<T: Serialize>(result: Result<T, QuoteError>) -> String {
+    let result = result.and_then(|value| Ok(serde_json::to_string(&value)?));
+
+    result.unwrap_or_else(|e| {
+        serde_json::to_string(&WasmError {
+            error: e.code(),
+            message: e.to_string(),
+            field_errors: e.field_errors(),
+        })
+        .expect("error object serializes")
+    })
+}
+
+/// The standard price book in force on `date` (YYYY-MM-DD), so the browser
+/// prices with the same rates as the API does on that day
+fn standard_book_at(date: &str) -> Result<PriceBook, QuoteError> {
+    if !price_book::is_iso_date(date) {
+        return Err(QuoteError::Invalid(vec![FieldError::new(
+            "date",
+            "must be a date in YYYY-MM-DD format",
+        )]));
+    }
+
+    PriceBookSet::default()
+        .effective_at(date)
+        .cloned()
+        .ok_or_else(|| {
+            PriceBookError::Invalid(format!("no standard price book is in force on {}", date))
+                .into()
+        })
+}
+
+/// WASM-exposed function for frontend use, priced with the standard price
+/// book in force on `date` (YYYY-MM-DD), normally today
+///
+/// Returns the `QuoteOutput` JSON, or `{"error", "message", "field_errors"}`
+/// when the input is malformed or invalid.
+#[wasm_bindgen]
+pub fn calculate_quote_wasm(input_json: &str, date: &str) -> String {
+    to_wasm_json((|| {
+        let input: QuoteInput = serde_json::from_str(input_json)?;
+        calculate_quote(&input, &standard_book_at(date)?)
+    })())
+}
+
+/// WASM-exposed pricing against a price book fetched from the API
+#[wasm_bindgen]
+pub fn calculate_quote_with_price_book_wasm(input_json: &str, price_book_json: &str) -> String {
+    to_wasm_json((|| {
+        let input: QuoteInput = serde_json::from_str(input_json)?;
+        let book = PriceBook::from_json(price_book_json)?;
+        calculate_quote(&input, &book)
+    })())
+}
+
+/// WASM-exposed pricing of a multi-line `Quote` with the standard price book
+/// in force on `date` (YYYY-MM-DD)
+#[wasm_bindgen]
+pub fn price_quote_wasm(quote_json: &str, date: &str) -> String {
+    to_wasm_json((|| {
+        let quote: Quote = serde_json::from_str(quote_json)?;
+        price_quote(&quote, &standard_book_at(date)?)
+    })())
+}
+
+/// WASM-exposed RAL Classic catalog with stock finishes, as JSON
+#[wasm_bindgen]
+pub fn ral_catalog_wasm() -> String {
+    serde_json::to_string(&ral_catalog()).expect("catalog serializes")
+}
+
+/// WASM-exposed mesh pricing so the browser can quote an uploaded STL/OBJ offline
+///
+/// `units` is one of `"mm"`, `"cm"`, `"m"` or `"in"`; the format is taken from
+/// the file name's extension. Priced with the standard price book in force on
+/// `date` (YYYY-MM-DD).
 #[wasm_bindgen]
-pub fn calculate_quote_wasm(input_json: &str) -> String {
-    let input: QuoteInput = serde_json::from_str(input_json).unwrap();
-    let output = calculate_quote(&input);
-    serde_json::to_string(&output).unwrap()
+pub fn calculate_mesh_quote_wasm(
+    mesh_bytes: &[u8],
+    file_name: &str,
+    units: &str,
+    input_json: &str,
+    date: &str,
+) -> String {
+    to_wasm_json((|| {
+        let format = MeshFormat::from_file_name(file_name).ok_or_else(|| {
+            QuoteError::Invalid(vec![FieldError::new(
+                "file_name",
+                "unsupported mesh file, expected .stl or .obj",
+            )])
+        })?;
+        let units = MeshUnits::from_abbreviation(units).ok_or_else(|| {
+            QuoteError::Invalid(vec![FieldError::new(
+                "units",
+                "unsupported units, expected mm, cm, m or in",
+            )])
+        })?;
+        let template: QuoteInput = serde_json::from_str(input_json)?;
+
+        let book = standard_book_at(date)?;
+
+        calculate_mesh_quote(mesh_bytes, format, units, &template, &book)
+    })())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::price_book::newest_standard_book;
 
     #[test]
     fn test_basic_quote() {
@@ -114,18 +358,23 @@ mod tests {
             length_mm: 1000.0,
             width_mm: 500.0,
             height_mm: 300.0,
+            shape: PartShape::Box,
             material: Material::Steel,
             prep_level: PrepLevel::Clean,
             color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
             turnaround_days: 7,
             quantity: 1,
             is_rush: false,
+            currency: None,
+            customer: None,
         };
 
-        let output = calculate_quote(&input);
-        assert!(output.total_price > 0.0);
-        assert_eq!(output.prep_surcharge, 0.0);
-        assert_eq!(output.rush_surcharge, 0.0);
+        let output = calculate_quote(&input, &newest_standard_book()).unwrap();
+        assert!(output.total_price.is_positive());
+        assert_eq!(output.prep_surcharge.amount, 0);
+        assert_eq!(output.rush_surcharge.amount, 0);
     }
 
     #[test]
@@ -134,15 +383,488 @@ mod tests {
             length_mm: 1000.0,
             width_mm: 500.0,
             height_mm: 300.0,
+            shape: PartShape::Box,
             material: Material::Steel,
             prep_level: PrepLevel::Clean,
             color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
             turnaround_days: 3,
             quantity: 1,
             is_rush: true,
+            currency: None,
+            customer: None,
+        };
+
+        let output = calculate_quote(&input, &newest_standard_book()).unwrap();
+        assert!(output.rush_surcharge.is_positive());
+    }
+
+    #[test]
+    fn test_price_book_drives_rates() {
+        let input = QuoteInput {
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Aluminium,
+            prep_level: PrepLevel::BlastClean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 3,
+            quantity: 1,
+            is_rush: true,
+            currency: None,
+            customer: None,
+        };
+
+        let standard = newest_standard_book();
+        let book = PriceBook {
+            version: "2026-test".to_string(),
+            base_rate_per_m2: 30.0,
+            prep_rates_per_m2: PrepRates {
+                blast_clean: 10.0,
+                ..standard.prep_rates_per_m2.clone()
+            },
+            rush: RushRule {
+                below_days: 3,
+                ..standard.rush.clone()
+            },
+            ..standard
+        };
+
+        let output = calculate_quote(&input, &book).unwrap();
+
+        assert_eq!(output.base_price, Money::new(5700, Currency::Eur));
+        assert_eq!(output.prep_surcharge, Money::new(1900, Currency::Eur));
+        assert_eq!(output.rush_surcharge.amount, 0);
+        assert_eq!(output.price_book_version, "2026-test");
+    }
+
+    #[test]
+    fn test_quote_in_other_currency() {
+        let mut input = QuoteInput {
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::Clean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 1,
+            is_rush: false,
+            currency: Some(Currency::Usd),
+            customer: None,
+        };
+
+        let output = calculate_quote(&input, &newest_standard_book()).unwrap();
+        assert_eq!(output.currency, Currency::Usd);
+        assert_eq!(output.exchange_rate, 1.08);
+        // 42.75 EUR × 1.08
+        assert_eq!(output.total_price, Money::new(4617, Currency::Usd));
+
+        input.currency = Some(Currency::Pln);
+        assert!(matches!(
+            calculate_quote(&input, &newest_standard_book()),
+            Err(QuoteError::UnsupportedCurrency { .. })
+        ));
+    }
+
+    #[test]
+    fn test_vat_added_to_amount_due() {
+        let mut input = QuoteInput {
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::Clean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 1,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        };
+
+        // Domestic consumer: 42.75 EUR + 21% Latvian VAT
+        let output = calculate_quote(&input, &newest_standard_book()).unwrap();
+        let vat = output.vat.as_ref().unwrap();
+        assert_eq!(vat.net, output.total_price);
+        assert_eq!(vat.vat.amount, 898);
+        assert_eq!(output.amount_due().amount, 5173);
+
+        input.customer = Some(Customer {
+            country: "LT".to_string(),
+            customer_type: CustomerType::Business,
+            vat_id: Some("LT100001234567".to_string()),
+        });
+        let output = calculate_quote(&input, &newest_standard_book()).unwrap();
+        assert_eq!(
+            output.vat.as_ref().unwrap().treatment,
+            VatTreatment::ReverseCharge
+        );
+        assert_eq!(output.amount_due(), output.total_price);
+    }
+
+    #[test]
+    fn test_color_surcharges() {
+        let mut input = QuoteInput {
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::Clean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 1,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        };
+        let book = newest_standard_book();
+
+        let stock = calculate_quote(&input, &book).unwrap();
+        assert_eq!(stock.color_surcharge.amount, 0);
+
+        // Stocked in textured, so only the finish rate of 2.00/m² applies
+        input.finish = Finish::Textured;
+        let textured = calculate_quote(&input, &book).unwrap();
+        assert_eq!(textured.color_surcharge.amount, 380);
+
+        // Luminous yellow: ordered in (6.00/m²) plus its own 8.00/m²
+        input.color = "1026".to_string();
+        input.finish = Finish::Gloss;
+        let luminous = calculate_quote(&input, &book).unwrap();
+        assert_eq!(luminous.color_surcharge.amount, 2660);
+        assert_eq!(
+            luminous.total_price.amount,
+            luminous.base_price.amount + luminous.color_surcharge.amount
+        );
+    }
+
+    #[test]
+    fn test_coat_stack_itemized() {
+        let mut input = QuoteInput {
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::BlastClean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: vec![
+                Coat {
+                    kind: CoatKind::Clear,
+                    color: None,
+                    finish: Finish::Gloss,
+                    coverage_percent: 100.0,
+                },
+                Coat {
+                    kind: CoatKind::Color,
+                    color: Some("3020".to_string()),
+                    finish: Finish::Gloss,
+                    coverage_percent: 25.0,
+                },
+                Coat {
+                    kind: CoatKind::ZincPrimer,
+                    color: None,
+                    finish: Finish::Gloss,
+                    coverage_percent: 100.0,
+                },
+            ],
+            turnaround_days: 7,
+            quantity: 1,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        };
+
+        let output = calculate_quote(&input, &newest_standard_book()).unwrap();
+        let kinds: Vec<CoatKind> = output.coats.iter().map(|coat| coat.kind).collect();
+        assert_eq!(
+            kinds,
+            [CoatKind::ZincPrimer, CoatKind::Color, CoatKind::Clear]
+        );
+
+        // 1.9 m² of primer: 13.30 material, 11.40 labour and a 20.00 oven cycle
+        let primer = &output.coats[0];
+        assert_eq!(primer.material.amount, 1330);
+        assert_eq!(primer.labour.amount, 1140);
+        assert_eq!(primer.masking.amount, 0);
+        assert_eq!(primer.total.amount, 4470);
+
+        // A quarter of the part in red, masked
+        let second = &output.coats[1];
+        assert_eq!(second.area_m2, 0.475);
+        assert_eq!(second.masking.amount, 380);
+        assert_eq!(second.total.amount, 3045);
+
+        assert_eq!(output.coats_surcharge.amount, 4470 + 3045 + 4090);
+
+        // The second color also means one color change
+        assert_eq!(output.batch_fees[0].kind, BatchFeeKind::ColorChange);
+        assert_eq!(
+            output.total_price.amount,
+            output.base_price.amount
+                + output.prep_surcharge.amount
+                + output.coats_surcharge.amount
+                + output.batch_fees[0].amount.amount
+        );
+
+        input.prep_level = PrepLevel::BlastPrime;
+        assert!(matches!(
+            calculate_quote(&input, &newest_standard_book()),
+            Err(QuoteError::Invalid(_))
+        ));
+
+        input.prep_level = PrepLevel::BlastClean;
+        let no_coat_rates = PriceBook {
+            coats: None,
+            ..newest_standard_book()
+        };
+        assert_eq!(
+            calculate_quote(&input, &no_coat_rates).unwrap_err().code(),
+            "invalid_input"
+        );
+    }
+
+    #[test]
+    fn test_quantity_tiers_and_minimum_order() {
+        let mut input = QuoteInput {
+            length_mm: 100.0,
+            width_mm: 50.0,
+            height_mm: 30.0,
+            shape: PartShape::Box,
+            material: Material::Aluminium,
+            prep_level: PrepLevel::Clean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 100,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        };
+        let standard = newest_standard_book();
+        let book = PriceBook {
+            batch_fees: BatchFees {
+                setup: 40.0,
+                minimum_order: 50.0,
+                ..standard.batch_fees.clone()
+            },
+            ..standard
+        };
+
+        // 100 brackets of 0.019 m²: 47.50 base, 10% off, plus the 40.00 setup
+        let bulk = calculate_quote(&input, &book).unwrap();
+        assert_eq!(bulk.quantity_tier.as_ref().unwrap().min_quantity, 100);
+        assert_eq!(bulk.base_price.amount, 4750);
+        assert_eq!(bulk.volume_discount.amount, 475);
+        assert_eq!(bulk.total_price.amount, 4275 + 4000);
+        assert_eq!(bulk.unit_price.amount, 83);
+
+        // The setup spreads over fewer parts, and the order is topped up to 50.00
+        input.quantity = 10;
+        let small = calculate_quote(&input, &book).unwrap();
+        assert_eq!(small.quantity_tier, None);
+        assert_eq!(small.total_price.amount, 5000);
+        assert_eq!(small.unit_price.amount, 500);
+        assert_eq!(
+            small.batch_fees.last().unwrap().kind,
+            BatchFeeKind::MinimumOrder
+        );
+        assert_eq!(
+            small.batch_fees.last().unwrap().amount.amount,
+            5000 - 475 - 4000
+        );
+    }
+
+    #[test]
+    fn test_shape_drives_coated_area() {
+        let mut input = QuoteInput {
+            length_mm: 2000.0,
+            width_mm: 60.0,
+            height_mm: 60.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::BlastClean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 4,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        };
+        let boxed = calculate_quote(&input, &newest_standard_book()).unwrap();
+
+        input.shape = PartShape::RoundTube { coat_inside: false };
+        let tube = calculate_quote(&input, &newest_standard_book()).unwrap();
+
+        assert!(tube.total_price.amount < boxed.total_price.amount);
+        assert_eq!(tube.coated_area_m2, tube.area_per_part_m2 * 4.0);
+    }
+
+    #[test]
+    fn test_mesh_quote_uses_measured_area() {
+        let obj = "v 0 0 0\nv 1000 0 0\nv 1000 500 0\nv 0 500 0\nf 1 2 3 4\n";
+        let template = QuoteInput {
+            length_mm: 10.0,
+            width_mm: 10.0,
+            height_mm: 10.0,
+            shape: PartShape::Box,
+            material: Material::Aluminium,
+            prep_level: PrepLevel::Clean,
+            color: "9016".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 2,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        };
+
+        let quote = calculate_mesh_quote(
+            obj.as_bytes(),
+            MeshFormat::Obj,
+            MeshUnits::Millimeters,
+            &template,
+            &newest_standard_book(),
+        )
+        .unwrap();
+
+        assert_eq!(quote.input.shape, PartShape::ExplicitArea { area_m2: 0.5 });
+        assert_eq!(quote.input.length_mm, 1000.0);
+        assert_eq!(quote.output.coated_area_m2, 1.0);
+        assert_eq!(quote.output.base_price.amount, 2500);
+    }
+
+    #[test]
+    fn test_rounding_scope_keeps_lines_summing_to_total() {
+        let input = QuoteInput {
+            length_mm: 10.0,
+            width_mm: 10.0,
+            height_mm: 10.0,
+            shape: PartShape::ExplicitArea { area_m2: 0.006 },
+            material: Material::Aluminium,
+            prep_level: PrepLevel::BlastClean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 1,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        };
+
+        // Base and prep are 0.6 cents each
+        let standard = newest_standard_book();
+        let mut book = PriceBook {
+            base_rate_per_m2: 1.0,
+            prep_rates_per_m2: PrepRates {
+                blast_clean: 1.0,
+                ..standard.prep_rates_per_m2.clone()
+            },
+            ..standard
+        };
+
+        book.rounding.scope = RoundingScope::PerLine;
+        let per_line = calculate_quote(&input, &book).unwrap();
+        assert_eq!(per_line.base_price.amount, 1);
+        assert_eq!(per_line.prep_surcharge.amount, 1);
+        assert_eq!(per_line.total_price.amount, 2);
+
+        book.rounding.scope = RoundingScope::PerTotal;
+        let per_total = calculate_quote(&input, &book).unwrap();
+        assert_eq!(per_total.base_price.amount, 0);
+        assert_eq!(per_total.prep_surcharge.amount, 1);
+        assert_eq!(per_total.total_price.amount, 1);
+    }
+
+    #[test]
+    fn test_wasm_returns_error_objects() {
+        let malformed: serde_json::Value =
+            serde_json::from_str(&calculate_quote_wasm("{not json", "2026-10-17")).unwrap();
+        assert_eq!(malformed["error"], "malformed_input");
+
+        let invalid: serde_json::Value = serde_json::from_str(&calculate_quote_wasm(
+            r#"{"length_mm":-1,"width_mm":500,"height_mm":300,"material":"Steel",
+                "prep_level":"Clean","color":"black","turnaround_days":7,"quantity":0,
+                "is_rush":false}"#,
+            "2026-10-17",
+        ))
+        .unwrap();
+        assert_eq!(invalid["error"], "invalid_input");
+        assert_eq!(invalid["field_errors"].as_array().unwrap().len(), 3);
+        assert_eq!(invalid["field_errors"][0]["field"], "length_mm");
+    }
+
+    #[test]
+    fn test_wasm_prices_with_the_book_in_force() {
+        let input = r#"{"length_mm":100,"width_mm":50,"height_mm":30,"material":"Aluminium",
+            "prep_level":"Clean","color":"9005","turnaround_days":7,"quantity":100,
+            "is_rush":false}"#;
+        let priced_on = |date| -> serde_json::Value {
+            serde_json::from_str(&calculate_quote_wasm(input, date)).unwrap()
+        };
+
+        // Volume tiers only apply once the 2025-02 book takes effect
+        let today = priced_on("2026-10-17");
+        assert_eq!(today["price_book_version"], "2025-01");
+        assert!(today["quantity_tier"].is_null());
+        let tiered = priced_on("2026-11-01");
+        assert_eq!(tiered["price_book_version"], "2025-02");
+        assert_eq!(tiered["quantity_tier"]["min_quantity"], 100);
+
+        assert_eq!(priced_on("2024-12-31")["error"], "invalid_price_book");
+        let undated = priced_on("today");
+        assert_eq!(undated["error"], "invalid_input");
+        assert_eq!(undated["field_errors"][0]["field"], "date");
+    }
+
+    #[test]
+    fn test_wasm_rush_follows_the_book_in_force() {
+        let quote = r#"{"lines":[{"length_mm":1000,"width_mm":500,"height_mm":300,
+            "material":"Steel","prep_level":"Clean","color":"9005","quantity":1}],
+            "turnaround_days":1,"is_rush":true}"#;
+        let priced_on = |date| -> serde_json::Value {
+            serde_json::from_str(&price_quote_wasm(quote, date)).unwrap()
         };
 
-        let output = calculate_quote(&input);
-        assert!(output.rush_surcharge > 0.0);
+        // Four days early: the flat rate until the graded curve takes effect
+        let flat = priced_on("2026-11-30");
+        assert_eq!(flat["price_book_version"], "2025-02");
+        assert_eq!(flat["rush_rate"], 0.5);
+        assert_eq!(flat["lead_days"], 5);
+        let graded = priced_on("2026-12-01");
+        assert_eq!(graded["price_book_version"], "2025-03");
+        assert_eq!(graded["rush_rate"], 1.0);
+    }
+
+    #[test]
+    fn test_shape_defaults_to_box() {
+        let input: QuoteInput = serde_json::from_str(
+            r#"{"length_mm":1000,"width_mm":500,"height_mm":300,"material":"Steel",
+                "prep_level":"Clean","color":"9005","turnaround_days":7,"quantity":1,
+                "is_rush":false}"#,
+        )
+        .unwrap();
+
+        assert_eq!(input.shape, PartShape::Box);
     }
 }
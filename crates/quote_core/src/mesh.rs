@@ -0,0 +1,484 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{PartShape, QuoteInput};
+
+type Vertex = [f64; 3];
+type Triangle = [Vertex; 3];
+
+/// Size of the binary STL header plus the triangle count
+const STL_BINARY_HEADER: usize = 84;
+/// Normal, three vertices and the attribute byte count
+const STL_BINARY_TRIANGLE: usize = 50;
+
+/// Supported CAD mesh formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum MeshFormat {
+    /// Binary or ASCII STL, detected from the content
+    Stl,
+    /// Wavefront OBJ
+    Obj,
+}
+
+impl MeshFormat {
+    /// Guess the format from a file name's extension
+    pub fn from_file_name(name: &str) -> Option<MeshFormat> {
+        let extension = name.rsplit_once('.')?.1.to_ascii_lowercase();
+        match extension.as_str() {
+            "stl" => Some(MeshFormat::Stl),
+            "obj" => Some(MeshFormat::Obj),
+            _ => None,
+        }
+    }
+}
+
+/// Length unit the mesh coordinates are expressed in
+///
+/// Neither STL nor OBJ records units; CAD exports are usually millimeters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum MeshUnits {
+    #[default]
+    Millimeters,
+    Centimeters,
+    Meters,
+    Inches,
+}
+
+impl MeshUnits {
+    fn to_mm(self) -> f64 {
+        match self {
+            MeshUnits::Millimeters => 1.0,
+            MeshUnits::Centimeters => 10.0,
+            MeshUnits::Meters => 1000.0,
+            MeshUnits::Inches => 25.4,
+        }
+    }
+
+    /// Parse a short unit name such as `"mm"` or `"in"`
+    pub fn from_abbreviation(unit: &str) -> Option<MeshUnits> {
+        match unit {
+            "mm" => Some(MeshUnits::Millimeters),
+            "cm" => Some(MeshUnits::Centimeters),
+            "m" => Some(MeshUnits::Meters),
+            "in" => Some(MeshUnits::Inches),
+            _ => None,
+        }
+    }
+}
+
+/// Why a mesh file could not be read
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeshError {
+    /// The file contains no triangles
+    Empty,
+    /// Binary STL shorter than its header or triangle count claims
+    Truncated,
+    /// A coordinate or index could not be parsed
+    InvalidNumber { line: usize },
+    /// An ASCII STL facet without exactly three vertices
+    InvalidFacet,
+    /// An OBJ face with fewer than three vertices
+    InvalidFace { line: usize },
+    /// An OBJ face refers to a vertex that does not exist
+    IndexOutOfRange { line: usize },
+    /// A file that is not valid UTF-8 text where text was expected
+    NotText,
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Empty => write!(f, "mesh contains no triangles"),
+            MeshError::Truncated => write!(f, "binary STL is truncated"),
+            MeshError::InvalidNumber { line } => write!(f, "invalid number on line {}", line),
+            MeshError::InvalidFacet => write!(f, "STL facet does not have three vertices"),
+            MeshError::InvalidFace { line } => {
+                write!(f, "face on line {} has fewer than three vertices", line)
+            }
+            MeshError::IndexOutOfRange { line } => {
+                write!(f, "face on line {} refers to a missing vertex", line)
+            }
+            MeshError::NotText => write!(f, "mesh file is not valid text"),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// Geometry measured from a triangle mesh, in millimeter-based units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MeshSummary {
+    pub triangle_count: usize,
+    /// Total surface area in m²
+    pub surface_area_m2: f64,
+    /// Enclosed volume in cm³ (only meaningful for closed meshes)
+    pub volume_cm3: f64,
+    /// Axis-aligned bounding box size (x, y, z) in mm
+    pub bounding_box_mm: [f64; 3],
+}
+
+/// Parse a mesh and measure its area, volume and bounding box
+pub fn analyze_mesh(
+    bytes: &[u8],
+    format: MeshFormat,
+    units: MeshUnits,
+) -> Result<MeshSummary, MeshError> {
+    let triangles = match format {
+        MeshFormat::Stl => parse_stl(bytes)?,
+        MeshFormat::Obj => parse_obj(std::str::from_utf8(bytes).map_err(|_| MeshError::NotText)?)?,
+    };
+
+    if triangles.is_empty() {
+        return Err(MeshError::Empty);
+    }
+
+    Ok(summarize(&triangles, units.to_mm()))
+}
+
+impl QuoteInput {
+    /// Price this part by the measured mesh instead of the bounding-box estimate
+    pub fn apply_mesh(&mut self, mesh: &MeshSummary) {
+        let [x, y, z] = mesh.bounding_box_mm;
+        self.length_mm = x;
+        self.width_mm = y;
+        self.height_mm = z;
+        self.shape = PartShape::ExplicitArea {
+            area_m2: mesh.surface_area_m2,
+        };
+    }
+}
+
+fn parse_stl(bytes: &[u8]) -> Result<Vec<Triangle>, MeshError> {
+    // Binary files may also start with "solid", so trust the size check first
+    if bytes.len() >= STL_BINARY_HEADER {
+        let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+        // The count comes from the file; it must not overflow on 32-bit wasm
+        let size = count
+            .checked_mul(STL_BINARY_TRIANGLE)
+            .and_then(|n| n.checked_add(STL_BINARY_HEADER));
+        if size == Some(bytes.len()) {
+            return Ok(parse_stl_binary(bytes, count));
+        }
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) if text.trim_start().starts_with("solid") && text.contains("facet") => {
+            parse_stl_ascii(text)
+        }
+        _ => Err(MeshError::Truncated),
+    }
+}
+
+fn parse_stl_binary(bytes: &[u8], count: usize) -> Vec<Triangle> {
+    let read_f32 = |offset: usize| {
+        f32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ]) as f64
+    };
+
+    (0..count)
+        .map(|i| {
+            // Skip the 12-byte facet normal; it is recomputed from the vertices anyway
+            let base = STL_BINARY_HEADER + i * STL_BINARY_TRIANGLE + 12;
+            let vertex = |v: usize| {
+                let at = base + v * 12;
+                [read_f32(at), read_f32(at + 4), read_f32(at + 8)]
+            };
+            [vertex(0), vertex(1), vertex(2)]
+        })
+        .collect()
+}
+
+fn parse_stl_ascii(text: &str) -> Result<Vec<Triangle>, MeshError> {
+    let mut triangles = Vec::new();
+    let mut facet = Vec::with_capacity(3);
+
+    for (index, line) in text.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("vertex") => facet.push(parse_vertex(tokens, index + 1)?),
+            Some("endfacet") => {
+                let [a, b, c] = facet[..] else {
+                    return Err(MeshError::InvalidFacet);
+                };
+                triangles.push([a, b, c]);
+                facet.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn parse_obj(text: &str) -> Result<Vec<Triangle>, MeshError> {
+    let mut vertices: Vec<Vertex> = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => vertices.push(parse_vertex(tokens, line_number)?),
+            Some("f") => {
+                let face = tokens
+                    .map(|token| resolve_obj_index(token, vertices.len(), line_number))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if face.len() < 3 {
+                    return Err(MeshError::InvalidFace { line: line_number });
+                }
+
+                // Fan-triangulate polygons around their first vertex
+                for pair in face[1..].windows(2) {
+                    triangles.push([vertices[face[0]], vertices[pair[0]], vertices[pair[1]]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+/// Turn an OBJ face token (`7`, `7/1`, `7//3`, `-1`) into a zero-based vertex index
+fn resolve_obj_index(token: &str, vertex_count: usize, line: usize) -> Result<usize, MeshError> {
+    let raw: i64 = token
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| MeshError::InvalidNumber { line })?;
+
+    let index = match raw {
+        0 => None,
+        n if n > 0 => Some(n as usize - 1),
+        n => vertex_count.checked_sub(n.unsigned_abs() as usize),
+    };
+
+    index
+        .filter(|&i| i < vertex_count)
+        .ok_or(MeshError::IndexOutOfRange { line })
+}
+
+fn parse_vertex<'a>(
+    mut tokens: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vertex, MeshError> {
+    let mut next = || {
+        tokens
+            .next()
+            .and_then(|t| t.parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+            .ok_or(MeshError::InvalidNumber { line })
+    };
+    Ok([next()?, next()?, next()?])
+}
+
+fn summarize(triangles: &[Triangle], scale_to_mm: f64) -> MeshSummary {
+    let mut area_mm2 = 0.0;
+    let mut signed_volume_mm3 = 0.0;
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+
+    for triangle in triangles {
+        let [a, b, c] = triangle.map(|v| v.map(|coord| coord * scale_to_mm));
+
+        let ab = sub(b, a);
+        let ac = sub(c, a);
+        area_mm2 += norm(cross(ab, ac)) / 2.0;
+
+        // Signed tetrahedra against the origin add up to the enclosed volume
+        signed_volume_mm3 += dot(a, cross(b, c)) / 6.0;
+
+        for vertex in [a, b, c] {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertex[axis]);
+                max[axis] = max[axis].max(vertex[axis]);
+            }
+        }
+    }
+
+    MeshSummary {
+        triangle_count: triangles.len(),
+        surface_area_m2: area_mm2 / 1_000_000.0,
+        volume_cm3: signed_volume_mm3.abs() / 1000.0,
+        bounding_box_mm: [max[0] - min[0], max[1] - min[1], max[2] - min[2]],
+    }
+}
+
+fn sub(a: Vertex, b: Vertex) -> Vertex {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: Vertex, b: Vertex) -> Vertex {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: Vertex, b: Vertex) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn norm(a: Vertex) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100 mm cube as eight vertices and six quads
+    const CUBE_OBJ: &str = "\
+# cube
+v 0 0 0
+v 100 0 0
+v 100 100 0
+v 0 100 0
+v 0 0 100
+v 100 0 100
+v 100 100 100
+v 0 100 100
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 2 3 7 6
+f 3 4 8 7
+f 4 1 5 8
+";
+
+    fn cube_triangles() -> Vec<Triangle> {
+        parse_obj(CUBE_OBJ).unwrap()
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    fn to_ascii_stl(triangles: &[Triangle]) -> String {
+        let mut out = String::from("solid cube\n");
+        for t in triangles {
+            out.push_str("  facet normal 0 0 0\n    outer loop\n");
+            for v in t {
+                out.push_str(&format!("      vertex {} {} {}\n", v[0], v[1], v[2]));
+            }
+            out.push_str("    endloop\n  endfacet\n");
+        }
+        out.push_str("endsolid cube\n");
+        out
+    }
+
+    fn to_binary_stl(triangles: &[Triangle]) -> Vec<u8> {
+        // Header deliberately starts with "solid" like many exporters write
+        let mut out = b"solid exported by CAD".to_vec();
+        out.resize(80, 0);
+        out.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for t in triangles {
+            out.extend_from_slice(&[0; 12]);
+            for v in t {
+                for coord in v {
+                    out.extend_from_slice(&(*coord as f32).to_le_bytes());
+                }
+            }
+            out.extend_from_slice(&[0; 2]);
+        }
+        out
+    }
+
+    #[test]
+    fn test_obj_cube_measurements() {
+        let summary =
+            analyze_mesh(CUBE_OBJ.as_bytes(), MeshFormat::Obj, MeshUnits::Millimeters).unwrap();
+
+        assert_eq!(summary.triangle_count, 12);
+        assert!(approx(summary.surface_area_m2, 0.06));
+        assert!(approx(summary.volume_cm3, 1000.0));
+        assert_eq!(summary.bounding_box_mm, [100.0, 100.0, 100.0]);
+    }
+
+    #[test]
+    fn test_ascii_and_binary_stl_agree() {
+        let triangles = cube_triangles();
+        let ascii = to_ascii_stl(&triangles);
+        let binary = to_binary_stl(&triangles);
+
+        let from_ascii =
+            analyze_mesh(ascii.as_bytes(), MeshFormat::Stl, MeshUnits::Millimeters).unwrap();
+        let from_binary = analyze_mesh(&binary, MeshFormat::Stl, MeshUnits::Millimeters).unwrap();
+
+        assert_eq!(from_ascii, from_binary);
+        assert!(approx(from_binary.surface_area_m2, 0.06));
+    }
+
+    #[test]
+    fn test_units_scale_area_and_volume() {
+        let summary =
+            analyze_mesh(CUBE_OBJ.as_bytes(), MeshFormat::Obj, MeshUnits::Centimeters).unwrap();
+
+        assert!(approx(summary.surface_area_m2, 6.0));
+        assert!(approx(summary.volume_cm3, 1_000_000.0));
+    }
+
+    #[test]
+    fn test_obj_negative_indices_and_slashes() {
+        let obj = "v 0 0 0\nv 10 0 0\nv 0 10 0\nf -3/1/1 -2//1 -1\n";
+        let triangles = parse_obj(obj).unwrap();
+        assert_eq!(triangles.len(), 1);
+        assert_eq!(triangles[0][1], [10.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_malformed_meshes_are_rejected() {
+        assert_eq!(
+            parse_obj("v 0 0 0\nf 1 2 3\n"),
+            Err(MeshError::IndexOutOfRange { line: 2 })
+        );
+        assert_eq!(
+            parse_obj("v 0 zero 0\n"),
+            Err(MeshError::InvalidNumber { line: 1 })
+        );
+        assert_eq!(
+            analyze_mesh(b"", MeshFormat::Obj, MeshUnits::Millimeters),
+            Err(MeshError::Empty)
+        );
+
+        let mut truncated = to_binary_stl(&cube_triangles());
+        truncated.truncate(200);
+        assert_eq!(
+            analyze_mesh(&truncated, MeshFormat::Stl, MeshUnits::Millimeters),
+            Err(MeshError::Truncated)
+        );
+
+        // A triangle count whose size overflows a 32-bit usize
+        let mut huge_count = to_binary_stl(&cube_triangles());
+        huge_count[80..84].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            analyze_mesh(&huge_count, MeshFormat::Stl, MeshUnits::Millimeters),
+            Err(MeshError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_format_from_file_name() {
+        assert_eq!(
+            MeshFormat::from_file_name("bracket.STL"),
+            Some(MeshFormat::Stl)
+        );
+        assert_eq!(
+            MeshFormat::from_file_name("frame.v2.obj"),
+            Some(MeshFormat::Obj)
+        );
+        assert_eq!(MeshFormat::from_file_name("drawing.pdf"), None);
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// ISO 4217 currency a price is charged in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    Eur,
+    Usd,
+    Gbp,
+    Chf,
+    Sek,
+    Nok,
+    Dkk,
+    Pln,
+}
+
+impl Currency {
+    /// ISO 4217 code, e.g. `"EUR"`
+    pub fn code(self) -> &'static str {
+        match self {
+            Currency::Eur => "EUR",
+            Currency::Usd => "USD",
+            Currency::Gbp => "GBP",
+            Currency::Chf => "CHF",
+            Currency::Sek => "SEK",
+            Currency::Nok => "NOK",
+            Currency::Dkk => "DKK",
+            Currency::Pln => "PLN",
+        }
+    }
+
+    /// Number of decimal places in one major unit; every supported currency uses cents
+    pub fn decimals(self) -> u32 {
+        2
+    }
+
+    fn minor_per_major(self) -> f64 {
+        10f64.powi(self.decimals() as i32)
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "EUR" => Ok(Currency::Eur),
+            "USD" => Ok(Currency::Usd),
+            "GBP" => Ok(Currency::Gbp),
+            "CHF" => Ok(Currency::Chf),
+            "SEK" => Ok(Currency::Sek),
+            "NOK" => Ok(Currency::Nok),
+            "DKK" => Ok(Currency::Dkk),
+            "PLN" => Ok(Currency::Pln),
+            other => Err(format!("unsupported currency: {}", other)),
+        }
+    }
+}
+
+/// How a fractional amount is rounded to whole minor units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /// Halves round away from zero (0.005 → 0.01)
+    #[default]
+    HalfUp,
+    /// Halves round to the even neighbour (0.005 → 0.00, 0.015 → 0.02), aka banker's rounding
+    HalfEven,
+}
+
+impl RoundingMode {
+    /// Round an amount in minor units to a whole number
+    pub fn round(self, minor: f64) -> i64 {
+        // Drop binary noise first so e.g. 0.285 × 100 = 28.499999… counts as a half
+        let minor = (minor * 1e6).round() / 1e6;
+        let rounded = match self {
+            RoundingMode::HalfUp => minor.round(),
+            RoundingMode::HalfEven => minor.round_ties_even(),
+        };
+        rounded as i64
+    }
+}
+
+/// Where rounding happens when a quote is split into priced lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingScope {
+    /// Each line is rounded and the total is the sum of the rounded lines
+    PerLine,
+    /// The total is rounded once; the base line absorbs the difference so lines still add up
+    #[default]
+    PerTotal,
+}
+
+/// Rounding rules of a price book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Rounding {
+    #[serde(default)]
+    pub mode: RoundingMode,
+    #[serde(default)]
+    pub scope: RoundingScope,
+}
+
+/// An amount in integer minor units (cents) of a currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Money {
+    /// Amount in minor units, e.g. 4275 for 42.75 EUR
+    pub amount: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: i64, currency: Currency) -> Money {
+        Money { amount, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Money {
+        Money::new(0, currency)
+    }
+
+    /// Round a major-unit amount (e.g. 42.745 EUR) to minor units
+    pub fn from_major(value: f64, currency: Currency, mode: RoundingMode) -> Money {
+        Money::new(mode.round(value * currency.minor_per_major()), currency)
+    }
+
+    /// The amount in major units, for display only
+    pub fn to_major(self) -> f64 {
+        self.amount as f64 / self.currency.minor_per_major()
+    }
+
+    /// Sum of two amounts, `None` if the currencies differ or the sum overflows
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        if self.currency != other.currency {
+            return None;
+        }
+        Some(Money::new(
+            self.amount.checked_add(other.amount)?,
+            self.currency,
+        ))
+    }
+
+    /// Difference of two amounts, `None` if the currencies differ or it overflows
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        if self.currency != other.currency {
+            return None;
+        }
+        Some(Money::new(
+            self.amount.checked_sub(other.amount)?,
+            self.currency,
+        ))
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.amount > 0
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = self.currency.decimals() as usize;
+        let scale = 10i64.pow(self.currency.decimals());
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let abs = self.amount.unsigned_abs();
+        write!(
+            f,
+            "{}{}.{:0width$} {}",
+            sign,
+            abs / scale as u64,
+            abs % scale as u64,
+            self.currency,
+            width = decimals
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_up_and_half_even_differ_on_ties() {
+        assert_eq!(
+            Money::from_major(0.125, Currency::Eur, RoundingMode::HalfUp).amount,
+            13
+        );
+        assert_eq!(
+            Money::from_major(0.125, Currency::Eur, RoundingMode::HalfEven).amount,
+            12
+        );
+        assert_eq!(
+            Money::from_major(0.135, Currency::Eur, RoundingMode::HalfEven).amount,
+            14
+        );
+        assert_eq!(
+            Money::from_major(-0.125, Currency::Eur, RoundingMode::HalfUp).amount,
+            -13
+        );
+    }
+
+    #[test]
+    fn test_float_noise_does_not_break_ties() {
+        // 0.285 is stored as 0.28499999999999998
+        assert_eq!(
+            Money::from_major(0.285, Currency::Eur, RoundingMode::HalfUp).amount,
+            29
+        );
+        assert_eq!(
+            Money::from_major(42.75, Currency::Eur, RoundingMode::HalfUp).amount,
+            4275
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_requires_same_currency() {
+        let eur = Money::new(1050, Currency::Eur);
+        let usd = Money::new(100, Currency::Usd);
+
+        assert_eq!(eur.checked_add(eur), Some(Money::new(2100, Currency::Eur)));
+        assert_eq!(
+            eur.checked_sub(Money::new(50, Currency::Eur))
+                .unwrap()
+                .amount,
+            1000
+        );
+        assert_eq!(eur.checked_add(usd), None);
+        assert_eq!(Money::new(i64::MAX, Currency::Eur).checked_add(eur), None);
+    }
+
+    #[test]
+    fn test_display_and_serde() {
+        assert_eq!(Money::new(4275, Currency::Eur).to_string(), "42.75 EUR");
+        assert_eq!(Money::new(-5, Currency::Usd).to_string(), "-0.05 USD");
+
+        let json = serde_json::to_string(&Money::new(4275, Currency::Eur)).unwrap();
+        assert_eq!(json, r#"{"amount":4275,"currency":"EUR"}"#);
+        assert_eq!("gbp".parse::<Currency>(), Ok(Currency::Gbp));
+    }
+}
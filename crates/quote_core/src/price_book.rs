@@ -0,0 +1,748 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ral_color, Capacity, CoatPricing, Currency, Finish, Material, Money, PrepLevel, Quote,
+    RalColor, Rounding, ShopLoad, VatRules,
+};
+
+/// Price books compiled into the crate, oldest first, used when no other
+/// books are configured. Published rates are never changed; new rates ship
+/// as a new version.
+const STANDARD_PRICE_BOOKS: [&str; 3] = [
+    include_str!("../price_books/2025-01.toml"),
+    include_str!("../price_books/2025-02.toml"),
+    include_str!("../price_books/2025-03.toml"),
+];
+
+/// Versioned set of rates `calculate_quote` prices with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PriceBook {
+    /// Unique label recorded on every quote priced with this book
+    pub version: String,
+    /// First day (YYYY-MM-DD) the book applies to new quotes
+    pub effective_from: String,
+    /// Currency the rates are expressed in
+    pub currency: Currency,
+    /// How line and total prices are rounded to minor units
+    #[serde(default)]
+    pub rounding: Rounding,
+    /// Coating rate per m² of coated area
+    pub base_rate_per_m2: f64,
+    pub material_multipliers: MaterialMultipliers,
+    pub prep_rates_per_m2: PrepRates,
+    pub rush: RushRule,
+    /// Color and finish surcharges; none when absent
+    #[serde(default)]
+    pub colors: ColorPricing,
+    /// Rates for primer, second color and clear coats; quotes with
+    /// additional coats are rejected when absent
+    #[serde(default)]
+    pub coats: Option<CoatPricing>,
+    /// Fees charged once per order; none when absent
+    #[serde(default)]
+    pub batch_fees: BatchFees,
+    /// Quantity breaks, by ascending `min_quantity`; no discounts when empty
+    #[serde(default)]
+    pub quantity_tiers: Vec<QuantityTier>,
+    /// Oven capacity; lead times are `rush.below_days` when absent
+    #[serde(default)]
+    pub capacity: Option<Capacity>,
+    /// Other currencies quotes may be priced in: units of that currency per
+    /// unit of `currency`
+    #[serde(default)]
+    pub exchange_rates: BTreeMap<Currency, f64>,
+    /// VAT rules; quotes are priced without tax when absent
+    #[serde(default)]
+    pub vat: Option<VatRules>,
+    /// Deposit taken at checkout on large orders; orders are paid in full
+    /// when absent
+    #[serde(default)]
+    pub deposit: Option<Deposit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MaterialMultipliers {
+    pub aluminium: f64,
+    pub steel: f64,
+    pub stainless: f64,
+}
+
+impl MaterialMultipliers {
+    pub fn for_material(&self, material: &Material) -> f64 {
+        match material {
+            Material::Aluminium => self.aluminium,
+            Material::Steel => self.steel,
+            Material::Stainless => self.stainless,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PrepRates {
+    pub clean: f64,
+    pub blast_clean: f64,
+    pub blast_prime: f64,
+}
+
+impl PrepRates {
+    pub fn for_level(&self, level: &PrepLevel) -> f64 {
+        match level {
+            PrepLevel::Clean => self.clean,
+            PrepLevel::BlastClean => self.blast_clean,
+            PrepLevel::BlastPrime => self.blast_prime,
+        }
+    }
+}
+
+/// Color surcharges, per m² of coated area
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ColorPricing {
+    /// Added when the color is not stocked in the chosen finish
+    #[serde(default)]
+    pub non_stock_per_m2: f64,
+    #[serde(default)]
+    pub finish_per_m2: FinishRates,
+    /// Extra rate for specific RAL codes, e.g. luminous and pearl powders
+    #[serde(default)]
+    pub color_per_m2: BTreeMap<String, f64>,
+}
+
+impl ColorPricing {
+    /// Total color surcharge rate for `color` in `finish`
+    pub fn rate_per_m2(&self, color: &RalColor, finish: Finish) -> f64 {
+        let non_stock = if color.is_stock(finish) {
+            0.0
+        } else {
+            self.non_stock_per_m2
+        };
+        let special = self.color_per_m2.get(color.code).copied().unwrap_or(0.0);
+
+        self.finish_per_m2.for_finish(finish) + non_stock + special
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct FinishRates {
+    pub gloss: f64,
+    pub satin: f64,
+    pub matte: f64,
+    pub textured: f64,
+    pub metallic: f64,
+    pub candy: f64,
+}
+
+impl FinishRates {
+    pub fn for_finish(&self, finish: Finish) -> f64 {
+        match finish {
+            Finish::Gloss => self.gloss,
+            Finish::Satin => self.satin,
+            Finish::Matte => self.matte,
+            Finish::Textured => self.textured,
+            Finish::Metallic => self.metallic,
+            Finish::Candy => self.candy,
+        }
+    }
+}
+
+/// Fees charged once per order rather than once per line
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct BatchFees {
+    /// Setting up the line, once per order
+    pub setup: f64,
+    /// Each change of powder after the first color of the order
+    pub color_change: f64,
+    /// Orders whose net total is below this are topped up to it
+    pub minimum_order: f64,
+}
+
+/// Part payment taken upfront on large orders, the balance being due at pickup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Deposit {
+    /// Gross amount due, in the book's currency, from which a deposit is taken
+    pub threshold: f64,
+    /// Share of the amount due paid at checkout, as a fraction (0.3 = 30%)
+    pub rate: f64,
+}
+
+impl Deposit {
+    fn validate(&self) -> Result<(), String> {
+        if !self.threshold.is_finite() || self.threshold < 0.0 {
+            return Err("deposit.threshold must be a non-negative number".into());
+        }
+        if !self.rate.is_finite() || self.rate <= 0.0 || self.rate >= 1.0 {
+            return Err("deposit.rate must be a fraction above 0 and below 1".into());
+        }
+        Ok(())
+    }
+}
+
+/// Volume discount for lines of at least `min_quantity` parts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuantityTier {
+    pub min_quantity: u32,
+    /// Share of the base price taken off, as a fraction (0.1 = 10%)
+    pub discount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RushRule {
+    /// Lead time in days assumed when the shop load is unknown; rush orders
+    /// due sooner than the lead time pay the surcharge
+    pub below_days: u32,
+    /// Share of the base price added as the rush surcharge when there is no `curve`
+    pub surcharge_rate: f64,
+    /// Graded surcharges by how many days before the lead time the order is
+    /// due, by ascending `days_early`
+    #[serde(default)]
+    pub curve: Vec<RushStep>,
+}
+
+/// Rush surcharge for orders due at least `days_early` days before the lead time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RushStep {
+    pub days_early: u32,
+    /// Share of the base price added, as a fraction (0.5 = 50%)
+    pub surcharge_rate: f64,
+}
+
+impl RushRule {
+    /// Surcharge rate for a rush order due in `turnaround_days` when the shop
+    /// needs `lead_days`
+    pub fn rate_for(&self, lead_days: u32, turnaround_days: u32) -> f64 {
+        let days_early = lead_days.saturating_sub(turnaround_days);
+        if days_early == 0 {
+            0.0
+        } else if self.curve.is_empty() {
+            self.surcharge_rate
+        } else {
+            self.curve
+                .iter()
+                .rev()
+                .find(|step| days_early >= step.days_early)
+                .map_or(0.0, |step| step.surcharge_rate)
+        }
+    }
+}
+
+/// Why a price book could not be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceBookError {
+    /// The file is not valid TOML/JSON for a price book
+    Parse(String),
+    /// The book parsed but contains unusable values
+    Invalid(String),
+}
+
+impl fmt::Display for PriceBookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceBookError::Parse(message) => write!(f, "failed to parse price book: {}", message),
+            PriceBookError::Invalid(message) => write!(f, "invalid price book: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PriceBookError {}
+
+impl PriceBook {
+    pub fn from_toml(text: &str) -> Result<PriceBook, PriceBookError> {
+        let book: PriceBook =
+            toml::from_str(text).map_err(|e| PriceBookError::Parse(e.to_string()))?;
+        book.validate()?;
+        Ok(book)
+    }
+
+    pub fn from_json(text: &str) -> Result<PriceBook, PriceBookError> {
+        let book: PriceBook =
+            serde_json::from_str(text).map_err(|e| PriceBookError::Parse(e.to_string()))?;
+        book.validate()?;
+        Ok(book)
+    }
+
+    /// Check the book is usable for pricing
+    pub fn validate(&self) -> Result<(), PriceBookError> {
+        if self.version.trim().is_empty() {
+            return Err(PriceBookError::Invalid("version must not be empty".into()));
+        }
+
+        if !is_iso_date(&self.effective_from) {
+            return Err(PriceBookError::Invalid(format!(
+                "effective_from must be YYYY-MM-DD, got {:?}",
+                self.effective_from
+            )));
+        }
+
+        let rates = [
+            ("base_rate_per_m2", self.base_rate_per_m2),
+            (
+                "material_multipliers.aluminium",
+                self.material_multipliers.aluminium,
+            ),
+            (
+                "material_multipliers.steel",
+                self.material_multipliers.steel,
+            ),
+            (
+                "material_multipliers.stainless",
+                self.material_multipliers.stainless,
+            ),
+            ("prep_rates_per_m2.clean", self.prep_rates_per_m2.clean),
+            (
+                "prep_rates_per_m2.blast_clean",
+                self.prep_rates_per_m2.blast_clean,
+            ),
+            (
+                "prep_rates_per_m2.blast_prime",
+                self.prep_rates_per_m2.blast_prime,
+            ),
+            ("rush.surcharge_rate", self.rush.surcharge_rate),
+            ("colors.non_stock_per_m2", self.colors.non_stock_per_m2),
+            ("batch_fees.setup", self.batch_fees.setup),
+            ("batch_fees.color_change", self.batch_fees.color_change),
+            ("batch_fees.minimum_order", self.batch_fees.minimum_order),
+        ];
+
+        if let Some(vat) = &self.vat {
+            vat.validate().map_err(PriceBookError::Invalid)?;
+        }
+
+        if let Some(coats) = &self.coats {
+            coats.validate().map_err(PriceBookError::Invalid)?;
+        }
+
+        if let Some(capacity) = &self.capacity {
+            capacity.validate().map_err(PriceBookError::Invalid)?;
+        }
+
+        if let Some(deposit) = &self.deposit {
+            deposit.validate().map_err(PriceBookError::Invalid)?;
+        }
+
+        for (i, step) in self.rush.curve.iter().enumerate() {
+            if !step.surcharge_rate.is_finite() || step.surcharge_rate < 0.0 {
+                return Err(PriceBookError::Invalid(format!(
+                    "rush.curve[{}].surcharge_rate must be a non-negative number",
+                    i
+                )));
+            }
+            let previous = i.checked_sub(1).map(|j| self.rush.curve[j].days_early);
+            if step.days_early == 0 || previous.is_some_and(|days| step.days_early <= days) {
+                return Err(PriceBookError::Invalid(format!(
+                    "rush.curve[{}].days_early must be above {}",
+                    i,
+                    previous.unwrap_or(0)
+                )));
+            }
+        }
+
+        for finish in Finish::ALL {
+            let rate = self.colors.finish_per_m2.for_finish(finish);
+            if !rate.is_finite() || rate < 0.0 {
+                return Err(PriceBookError::Invalid(format!(
+                    "colors.finish_per_m2.{:?} must be a non-negative number",
+                    finish
+                )));
+            }
+        }
+
+        for (code, rate) in &self.colors.color_per_m2 {
+            if ral_color(code).is_none() {
+                return Err(PriceBookError::Invalid(format!(
+                    "colors.color_per_m2.{} is not a RAL Classic code",
+                    code
+                )));
+            }
+            if !rate.is_finite() || *rate < 0.0 {
+                return Err(PriceBookError::Invalid(format!(
+                    "colors.color_per_m2.{} must be a non-negative number",
+                    code
+                )));
+            }
+        }
+
+        for (i, tier) in self.quantity_tiers.iter().enumerate() {
+            if !tier.discount.is_finite() || !(0.0..1.0).contains(&tier.discount) {
+                return Err(PriceBookError::Invalid(format!(
+                    "quantity_tiers[{}].discount must be a fraction below 1",
+                    i
+                )));
+            }
+            let previous = i
+                .checked_sub(1)
+                .map_or(1, |j| self.quantity_tiers[j].min_quantity);
+            if tier.min_quantity <= previous {
+                return Err(PriceBookError::Invalid(format!(
+                    "quantity_tiers[{}].min_quantity must be above {}",
+                    i, previous
+                )));
+            }
+        }
+
+        for (currency, rate) in &self.exchange_rates {
+            if !rate.is_finite() || *rate <= 0.0 {
+                return Err(PriceBookError::Invalid(format!(
+                    "exchange_rates.{} must be a positive number",
+                    currency
+                )));
+            }
+        }
+
+        for (name, value) in rates {
+            if !value.is_finite() || value < 0.0 {
+                return Err(PriceBookError::Invalid(format!(
+                    "{} must be a non-negative number",
+                    name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Units of `currency` per unit of the book's currency, if quotes can be priced in it
+    pub fn exchange_rate(&self, currency: Currency) -> Option<f64> {
+        if currency == self.currency {
+            Some(1.0)
+        } else {
+            self.exchange_rates.get(&currency).copied()
+        }
+    }
+
+    /// Deposit due at checkout on `amount_due`, or `None` when it is paid in full
+    pub fn deposit_for(&self, amount_due: Money) -> Option<Money> {
+        let deposit = self.deposit.as_ref()?;
+        let threshold = Money::from_major(
+            deposit.threshold * self.exchange_rate(amount_due.currency)?,
+            amount_due.currency,
+            self.rounding.mode,
+        );
+        if amount_due.amount < threshold.amount {
+            return None;
+        }
+
+        let amount = self
+            .rounding
+            .mode
+            .round(amount_due.amount as f64 * deposit.rate);
+        (amount > 0 && amount < amount_due.amount).then(|| Money::new(amount, amount_due.currency))
+    }
+
+    /// The largest quantity break a line of `quantity` parts reaches
+    pub fn quantity_tier(&self, quantity: u32) -> Option<&QuantityTier> {
+        self.quantity_tiers
+            .iter()
+            .rev()
+            .find(|tier| quantity >= tier.min_quantity)
+    }
+
+    /// Days the shop needs for `quote` at the current `load`
+    pub fn lead_days(&self, quote: &Quote, load: &ShopLoad) -> u32 {
+        self.capacity
+            .as_ref()
+            .map_or(self.rush.below_days, |capacity| {
+                capacity.lead_days(load, capacity.oven_hours(quote))
+            })
+    }
+
+    /// Currencies quotes can be priced in with this book
+    pub fn currencies(&self) -> Vec<Currency> {
+        let mut currencies = vec![self.currency];
+        currencies.extend(
+            self.exchange_rates
+                .keys()
+                .filter(|currency| **currency != self.currency),
+        );
+        currencies
+    }
+}
+
+/// All known price books, so old quotes can be re-priced with their own book
+#[derive(Debug, Clone)]
+pub struct PriceBookSet {
+    /// Sorted by `effective_from`
+    books: Vec<PriceBook>,
+}
+
+impl PriceBookSet {
+    pub fn new(mut books: Vec<PriceBook>) -> Result<PriceBookSet, PriceBookError> {
+        if books.is_empty() {
+            return Err(PriceBookError::Invalid("no price books configured".into()));
+        }
+
+        books.sort_by(|a, b| a.effective_from.cmp(&b.effective_from));
+
+        for (i, book) in books.iter().enumerate() {
+            if books[..i].iter().any(|other| other.version == book.version) {
+                return Err(PriceBookError::Invalid(format!(
+                    "duplicate price book version {}",
+                    book.version
+                )));
+            }
+        }
+
+        Ok(PriceBookSet { books })
+    }
+
+    /// Look up a book by version
+    pub fn get(&self, version: &str) -> Option<&PriceBook> {
+        self.books.iter().find(|book| book.version == version)
+    }
+
+    /// The book in force on `date` (YYYY-MM-DD): the latest one already effective
+    pub fn effective_at(&self, date: &str) -> Option<&PriceBook> {
+        self.books
+            .iter()
+            .rev()
+            .find(|book| book.effective_from.as_str() <= date)
+    }
+
+    /// The book with the latest `effective_from`, which may not apply yet
+    pub fn latest(&self) -> &PriceBook {
+        self.books.last().expect("a price book set is never empty")
+    }
+
+    pub fn books(&self) -> &[PriceBook] {
+        &self.books
+    }
+}
+
+impl Default for PriceBookSet {
+    /// Every standard price book shipped with the crate
+    fn default() -> Self {
+        PriceBookSet::new(
+            STANDARD_PRICE_BOOKS
+                .iter()
+                .map(|text| PriceBook::from_toml(text).expect("embedded price book is valid"))
+                .collect(),
+        )
+        .expect("embedded price books have distinct versions")
+    }
+}
+
+/// The newest standard book, for tests that exercise every feature
+#[cfg(test)]
+pub(crate) fn newest_standard_book() -> PriceBook {
+    PriceBookSet::default().latest().clone()
+}
+
+/// ISO dates compare correctly as strings, so only the shape needs checking
+pub(crate) fn is_iso_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(version: &str, effective_from: &str) -> PriceBook {
+        PriceBook {
+            version: version.to_string(),
+            effective_from: effective_from.to_string(),
+            ..newest_standard_book()
+        }
+    }
+
+    #[test]
+    fn test_standard_book_matches_original_rates() {
+        let set = PriceBookSet::default();
+        let book = set.get("2025-01").unwrap();
+
+        assert_eq!(book.effective_from, "2025-01-01");
+        assert_eq!(book.base_rate_per_m2, 25.0);
+        assert_eq!(
+            book.material_multipliers.for_material(&Material::Steel),
+            0.9
+        );
+        assert_eq!(
+            book.prep_rates_per_m2.for_level(&PrepLevel::BlastPrime),
+            25.0
+        );
+        assert_eq!(book.rush.below_days, 5);
+        assert_eq!(book.rush.surcharge_rate, 0.5);
+        assert!(book.quantity_tiers.is_empty());
+        assert!(book.rush.curve.is_empty());
+        assert!(book.capacity.is_none());
+        assert_eq!(book.colors, ColorPricing::default());
+        assert!(book.vat.is_none());
+    }
+
+    #[test]
+    fn test_standard_books_only_add_to_published_rates() {
+        let set = PriceBookSet::default();
+        let versions: Vec<&str> = set.books().iter().map(|b| b.version.as_str()).collect();
+        assert_eq!(versions, ["2025-01", "2025-02", "2025-03"]);
+        assert_eq!(set.latest().version, "2025-03");
+
+        // 2025-02 adds volume tiers, color surcharges and VAT and changes
+        // nothing else
+        let mut tiered = set.get("2025-02").unwrap().clone();
+        assert_eq!(tiered.quantity_tiers.len(), 4);
+        assert_eq!(tiered.colors.non_stock_per_m2, 6.0);
+        assert!(tiered.vat.is_some());
+        let published = set.get("2025-01").unwrap();
+        tiered.version = published.version.clone();
+        tiered.effective_from = published.effective_from.clone();
+        tiered.quantity_tiers.clear();
+        tiered.colors = ColorPricing::default();
+        tiered.vat = None;
+        assert_eq!(&tiered, published);
+
+        // 2025-03 grades the rush surcharge against oven capacity
+        let mut graded = set.get("2025-03").unwrap().clone();
+        assert_eq!(graded.rush.curve.len(), 4);
+        assert!(graded.capacity.is_some());
+        let tiered = set.get("2025-02").unwrap();
+        graded.version = tiered.version.clone();
+        graded.effective_from = tiered.effective_from.clone();
+        graded.rush.curve.clear();
+        graded.capacity = None;
+        assert_eq!(&graded, tiered);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let book = newest_standard_book();
+        let json = serde_json::to_string(&book).unwrap();
+        assert_eq!(PriceBook::from_json(&json).unwrap(), book);
+    }
+
+    #[test]
+    fn test_invalid_books_rejected() {
+        let mut negative = newest_standard_book();
+        negative.prep_rates_per_m2.blast_clean = -1.0;
+        assert!(matches!(
+            negative.validate(),
+            Err(PriceBookError::Invalid(_))
+        ));
+
+        assert!(book("2025-02", "2025/02/01").validate().is_err());
+
+        let mut unsorted_tiers = newest_standard_book();
+        unsorted_tiers.quantity_tiers.reverse();
+        assert!(unsorted_tiers.validate().is_err());
+
+        let mut unknown_color = newest_standard_book();
+        unknown_color
+            .colors
+            .color_per_m2
+            .insert("0000".to_string(), 1.0);
+        assert!(unknown_color.validate().is_err());
+        assert!(matches!(
+            PriceBook::from_toml("version = 1"),
+            Err(PriceBookError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_quantity_tier_lookup() {
+        let book = newest_standard_book();
+
+        assert_eq!(book.quantity_tier(24), None);
+        assert_eq!(book.quantity_tier(25).unwrap().discount, 0.05);
+        assert_eq!(book.quantity_tier(499).unwrap().min_quantity, 100);
+        assert_eq!(book.quantity_tier(10_000).unwrap().discount, 0.2);
+    }
+
+    #[test]
+    fn test_rush_curve() {
+        let book = newest_standard_book();
+
+        assert_eq!(book.rush.rate_for(5, 5), 0.0);
+        assert_eq!(book.rush.rate_for(5, 4), 0.25);
+        assert_eq!(book.rush.rate_for(5, 3), 0.5);
+        assert_eq!(book.rush.rate_for(10, 1), 1.0);
+
+        let flat = RushRule {
+            curve: Vec::new(),
+            ..book.rush.clone()
+        };
+        assert_eq!(flat.rate_for(5, 4), 0.5);
+        assert_eq!(flat.rate_for(3, 4), 0.0);
+    }
+
+    #[test]
+    fn test_exchange_rates() {
+        let mut book = PriceBook::from_toml(
+            &STANDARD_PRICE_BOOKS[0].replace("[exchange_rates]", "[exchange_rates]\nSEK = 11.5"),
+        )
+        .unwrap();
+
+        assert_eq!(book.exchange_rate(Currency::Eur), Some(1.0));
+        assert_eq!(book.exchange_rate(Currency::Sek), Some(11.5));
+        assert_eq!(book.exchange_rate(Currency::Pln), None);
+        assert_eq!(book.currencies()[0], Currency::Eur);
+
+        book.exchange_rates.insert(Currency::Pln, 0.0);
+        assert!(book.validate().is_err());
+    }
+
+    #[test]
+    fn test_deposit_above_threshold() {
+        let mut book = newest_standard_book();
+        assert_eq!(
+            book.deposit,
+            Some(Deposit {
+                threshold: 5000.0,
+                rate: 0.3
+            })
+        );
+
+        let eur = |amount| Money::new(amount, Currency::Eur);
+        assert_eq!(book.deposit_for(eur(499_999)), None);
+        assert_eq!(book.deposit_for(eur(500_000)), Some(eur(150_000)));
+        assert_eq!(book.deposit_for(eur(1_000_001)), Some(eur(300_000)));
+
+        // The threshold follows the exchange rate: 5000 EUR is 5400 USD
+        let usd = |amount| Money::new(amount, Currency::Usd);
+        assert_eq!(book.deposit_for(usd(539_999)), None);
+        assert_eq!(book.deposit_for(usd(540_000)), Some(usd(162_000)));
+
+        book.deposit.as_mut().unwrap().rate = 1.0;
+        assert!(book.validate().is_err());
+        book.deposit = None;
+        assert_eq!(book.deposit_for(eur(10_000_000)), None);
+    }
+
+    #[test]
+    fn test_effective_book_selection() {
+        let set = PriceBookSet::new(vec![
+            book("2026-03", "2026-03-01"),
+            book("2025-01", "2025-01-01"),
+        ])
+        .unwrap();
+
+        assert_eq!(set.effective_at("2024-12-31"), None);
+        assert_eq!(set.effective_at("2026-02-28").unwrap().version, "2025-01");
+        assert_eq!(set.effective_at("2026-03-01").unwrap().version, "2026-03");
+        assert_eq!(set.get("2025-01").unwrap().effective_from, "2025-01-01");
+    }
+
+    #[test]
+    fn test_duplicate_versions_rejected() {
+        let result = PriceBookSet::new(vec![
+            book("2025-01", "2025-01-01"),
+            book("2025-01", "2025-06-01"),
+        ]);
+        assert!(result.is_err());
+    }
+}
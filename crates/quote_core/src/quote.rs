@@ -0,0 +1,629 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    breakdown, coats, ral_color, Coat, CoatLine, Currency, Customer, Finish, Material, Money,
+    PartShape, PrepLevel, PriceBook, PriceBreakdown, QuantityTier, QuoteError, QuoteInput,
+    RoundingScope, VatBreakdown,
+};
+
+/// Most parts one quote can hold
+pub const MAX_QUOTE_LINES: usize = 10;
+
+/// One part of a quote, with its own geometry, material, prep and color
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuoteLine {
+    /// Customer's name for the part, e.g. "frame"
+    #[serde(default)]
+    pub reference: Option<String>,
+
+    /// Dimensions in millimeters: length x width x height
+    pub length_mm: f64,
+    pub width_mm: f64,
+    pub height_mm: f64,
+
+    /// Part shape used to derive the coated area from the dimensions
+    #[serde(default)]
+    pub shape: PartShape,
+
+    pub material: Material,
+    pub prep_level: PrepLevel,
+
+    /// RAL Classic color code (e.g., "9005", "9016")
+    pub color: String,
+    #[serde(default)]
+    pub finish: Finish,
+
+    /// Coats on top of the main color coat: primer, second color or clear
+    #[serde(default)]
+    pub coats: Vec<Coat>,
+
+    /// Quantity of this part
+    pub quantity: u32,
+}
+
+/// Several different parts priced together as one order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Quote {
+    pub lines: Vec<QuoteLine>,
+
+    /// Turnaround time in days for the whole order
+    pub turnaround_days: u32,
+
+    /// Rush order flag
+    pub is_rush: bool,
+
+    /// Currency to price in; the price book's own currency when omitted
+    #[serde(default)]
+    pub currency: Option<Currency>,
+
+    /// Who is invoiced, for VAT; a domestic consumer when omitted
+    #[serde(default)]
+    pub customer: Option<Customer>,
+}
+
+/// Price of one quote line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuoteLineOutput {
+    pub reference: Option<String>,
+    /// Coated area of a single part in m²
+    pub area_per_part_m2: f64,
+    /// Coated area across all parts of the line in m²
+    pub coated_area_m2: f64,
+    pub base_price: Money,
+    pub prep_surcharge: Money,
+    /// Special finishes, special powders and colors not in stock
+    pub color_surcharge: Money,
+    /// Additional coats, the sum of the `coats` totals
+    pub coats_surcharge: Money,
+    pub rush_surcharge: Money,
+    /// Quantity break the line reached, if any
+    #[serde(default)]
+    pub quantity_tier: Option<QuantityTier>,
+    /// Taken off the base price for the quantity tier
+    pub volume_discount: Money,
+    /// Line total: the five lines above less `volume_discount`
+    pub total_price: Money,
+    /// Each additional coat in application order
+    pub coats: Vec<CoatLine>,
+}
+
+/// Kind of fee charged once per order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum BatchFeeKind {
+    /// Setting up the line for the order
+    Setup,
+    /// Cleaning the booth between powders
+    ColorChange,
+    /// Top-up to the price book's minimum order charge
+    MinimumOrder,
+}
+
+impl BatchFeeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            BatchFeeKind::Setup => "Setup",
+            BatchFeeKind::ColorChange => "Color Change",
+            BatchFeeKind::MinimumOrder => "Minimum Order",
+        }
+    }
+}
+
+/// A fee charged once per order rather than once per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BatchFee {
+    pub kind: BatchFeeKind,
+    /// How many times the fee applies, e.g. the number of color changes
+    pub count: u32,
+    pub amount: Money,
+}
+
+/// Price of a multi-line quote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PricedQuote {
+    pub lines: Vec<QuoteLineOutput>,
+    pub batch_fees: Vec<BatchFee>,
+    /// Net total, always the sum of the line totals and batch fees
+    pub total_price: Money,
+    /// VAT on `total_price`; absent when the price book has no VAT rules
+    pub vat: Option<VatBreakdown>,
+    /// Days the shop needed for the order when it was priced; quotes from
+    /// before load-based lead times used the book's `rush.below_days`
+    #[serde(default)]
+    pub lead_days: Option<u32>,
+    /// Share of the base price added as the rush surcharge
+    #[serde(default)]
+    pub rush_rate: f64,
+    pub currency: Currency,
+    /// Units of `currency` per unit of the price book's currency
+    pub exchange_rate: f64,
+    /// Version of the price book the quote was priced with
+    pub price_book_version: String,
+    /// How every amount above was worked out; empty on quotes priced before
+    /// breakdowns were recorded
+    #[serde(default)]
+    pub breakdown: PriceBreakdown,
+}
+
+impl PricedQuote {
+    /// What the customer pays: the gross amount including VAT
+    pub fn amount_due(&self) -> Money {
+        self.vat.as_ref().map_or(self.total_price, |vat| vat.gross)
+    }
+
+    /// Coated area across all lines in m²
+    pub fn coated_area_m2(&self) -> f64 {
+        self.lines.iter().map(|line| line.coated_area_m2).sum()
+    }
+}
+
+impl QuoteInput {
+    /// The part this input describes, as a quote line
+    pub fn line(&self) -> QuoteLine {
+        QuoteLine {
+            reference: None,
+            length_mm: self.length_mm,
+            width_mm: self.width_mm,
+            height_mm: self.height_mm,
+            shape: self.shape.clone(),
+            material: self.material.clone(),
+            prep_level: self.prep_level.clone(),
+            color: self.color.clone(),
+            finish: self.finish,
+            coats: self.coats.clone(),
+            quantity: self.quantity,
+        }
+    }
+}
+
+impl From<QuoteInput> for Quote {
+    /// A quote with the single part the input describes
+    fn from(input: QuoteInput) -> Self {
+        Quote {
+            lines: vec![input.line()],
+            turnaround_days: input.turnaround_days,
+            is_rush: input.is_rush,
+            currency: input.currency,
+            customer: input.customer,
+        }
+    }
+}
+
+/// Currency and rounding a quote is priced with
+struct Pricing<'a> {
+    pub book: &'a PriceBook,
+    pub currency: Currency,
+    pub exchange_rate: f64,
+}
+
+impl<'a> Pricing<'a> {
+    fn new(book: &'a PriceBook, currency: Option<Currency>) -> Result<Self, QuoteError> {
+        let currency = currency.unwrap_or(book.currency);
+        let exchange_rate =
+            book.exchange_rate(currency)
+                .ok_or_else(|| QuoteError::UnsupportedCurrency {
+                    currency,
+                    price_book_version: book.version.clone(),
+                })?;
+
+        Ok(Pricing {
+            book,
+            currency,
+            exchange_rate,
+        })
+    }
+
+    /// Convert an amount in the book's currency and round it to minor units
+    fn money(&self, amount: f64) -> Money {
+        Money::from_major(
+            amount * self.exchange_rate,
+            self.currency,
+            self.book.rounding.mode,
+        )
+    }
+
+    /// Price one line with the order's rush surcharge rate
+    fn line(&self, line: &QuoteLine, rush_rate: f64) -> Result<QuoteLineOutput, QuoteError> {
+        let book = self.book;
+        let quantity = line.quantity as f64;
+
+        // Coated area of one part, from the part shape
+        let surface_area =
+            line.shape
+                .surface_area_m2(line.length_mm, line.width_mm, line.height_mm);
+
+        // Base price per m² with the material multiplier
+        let base_price = surface_area
+            * book.base_rate_per_m2
+            * quantity
+            * book.material_multipliers.for_material(&line.material);
+
+        // Volume discount on the base price for the line's quantity break
+        let quantity_tier = book.quantity_tier(line.quantity).cloned();
+        let volume_discount = base_price * quantity_tier.as_ref().map_or(0.0, |t| t.discount);
+
+        // Prep surcharge
+        let prep_surcharge =
+            surface_area * book.prep_rates_per_m2.for_level(&line.prep_level) * quantity;
+
+        // Color surcharge for the finish, special powders and colors not in stock
+        let color_rate = ral_color(&line.color)
+            .map_or(0.0, |color| book.colors.rate_per_m2(&color, line.finish));
+        let color_surcharge = surface_area * color_rate * quantity;
+
+        // Rush surcharge for turnarounds shorter than the shop's lead time
+        let rush_surcharge = (base_price - volume_discount) * rush_rate;
+
+        // Round to minor units; the lines always add up to the total
+        let prep = self.money(prep_surcharge);
+        let color = self.money(color_surcharge);
+        let rush = self.money(rush_surcharge);
+        let discount = self.money(volume_discount);
+
+        // Every additional coat is its own material, labour and oven cycle
+        let coats = coats::price_coats(line, surface_area, book, |amount| self.money(amount))?;
+        let coats_surcharge = Money::new(
+            coats
+                .iter()
+                .fold(0i64, |sum, coat| sum.saturating_add(coat.total.amount)),
+            self.currency,
+        );
+
+        let surcharges = prep
+            .amount
+            .saturating_add(color.amount)
+            .saturating_add(coats_surcharge.amount)
+            .saturating_add(rush.amount);
+
+        let (base, total) = match book.rounding.scope {
+            RoundingScope::PerLine => {
+                let base = self.money(base_price);
+                let total = base
+                    .amount
+                    .saturating_add(surcharges)
+                    .saturating_sub(discount.amount);
+                (base, Money::new(total, self.currency))
+            }
+            RoundingScope::PerTotal => {
+                // Coat lines are already rounded, so they join the total as they are
+                let total = self
+                    .money(
+                        base_price - volume_discount
+                            + prep_surcharge
+                            + color_surcharge
+                            + rush_surcharge,
+                    )
+                    .amount
+                    .saturating_add(coats_surcharge.amount);
+                let base = total
+                    .saturating_sub(surcharges)
+                    .saturating_add(discount.amount);
+                (
+                    Money::new(base, self.currency),
+                    Money::new(total, self.currency),
+                )
+            }
+        };
+
+        Ok(QuoteLineOutput {
+            reference: line.reference.clone(),
+            area_per_part_m2: surface_area,
+            coated_area_m2: surface_area * quantity,
+            base_price: base,
+            prep_surcharge: prep,
+            color_surcharge: color,
+            coats_surcharge,
+            rush_surcharge: rush,
+            quantity_tier,
+            volume_discount: discount,
+            total_price: total,
+            coats,
+        })
+    }
+
+    /// Rush surcharge rate of an order, against the shop's lead time
+    fn rush_rate(&self, is_rush: bool, turnaround_days: u32, lead_days: u32) -> f64 {
+        if is_rush {
+            self.book.rush.rate_for(lead_days, turnaround_days)
+        } else {
+            0.0
+        }
+    }
+
+    /// VAT on the net total, when the book has VAT rules
+    fn vat(
+        &self,
+        customer: Option<&Customer>,
+        total: Money,
+    ) -> Result<Option<VatBreakdown>, QuoteError> {
+        self.book
+            .vat
+            .as_ref()
+            .map(|rules| rules.breakdown(customer, total, self.book.rounding.mode))
+            .transpose()
+    }
+
+    /// Setup once per order, plus a color change for every powder after the
+    /// first, plus a top-up when the order is below the minimum charge
+    fn batch_fees(&self, lines: &[QuoteLine], lines_total: Money) -> Vec<BatchFee> {
+        let fees = &self.book.batch_fees;
+
+        let mut powders: Vec<(&str, Finish)> = Vec::new();
+        for line in lines {
+            let second_colors = line
+                .coats
+                .iter()
+                .filter_map(|coat| coat.color.as_deref().map(|color| (color, coat.finish)));
+            for powder in std::iter::once((line.color.as_str(), line.finish)).chain(second_colors) {
+                if !powders.contains(&powder) {
+                    powders.push(powder);
+                }
+            }
+        }
+        let color_changes = powders.len().saturating_sub(1) as u32;
+
+        let mut batch_fees: Vec<BatchFee> = [
+            (BatchFeeKind::Setup, 1, fees.setup),
+            (
+                BatchFeeKind::ColorChange,
+                color_changes,
+                fees.color_change * color_changes as f64,
+            ),
+        ]
+        .into_iter()
+        .map(|(kind, count, amount)| BatchFee {
+            kind,
+            count,
+            amount: self.money(amount),
+        })
+        .filter(|fee| fee.count > 0 && fee.amount.is_positive())
+        .collect();
+
+        let net = batch_fees.iter().fold(lines_total.amount, |sum, fee| {
+            sum.saturating_add(fee.amount.amount)
+        });
+        let top_up = self.money(fees.minimum_order).amount.saturating_sub(net);
+        if top_up > 0 {
+            batch_fees.push(BatchFee {
+                kind: BatchFeeKind::MinimumOrder,
+                count: 1,
+                amount: Money::new(top_up, self.currency),
+            });
+        }
+
+        batch_fees
+    }
+}
+
+/// Price a multi-line quote (native Rust function)
+///
+/// Each line is priced and rounded on its own, with its quantity break;
+/// setup, color change and minimum order charges then apply once for the
+/// whole order. Rush orders are priced against the book's `rush.below_days`
+/// lead time.
+pub fn price_quote(quote: &Quote, book: &PriceBook) -> Result<PricedQuote, QuoteError> {
+    price_quote_with_lead_time(quote, book, None)
+}
+
+/// Price a multi-line quote against the shop's current lead time
+///
+/// `lead_days` usually comes from [`PriceBook::lead_days`] with the booked
+/// oven hours; re-pricing a stored quote passes its recorded `lead_days`.
+pub fn price_quote_with_lead_time(
+    quote: &Quote,
+    book: &PriceBook,
+    lead_days: Option<u32>,
+) -> Result<PricedQuote, QuoteError> {
+    quote.validate()?;
+    price(quote, book, lead_days)
+}
+
+/// Price an already validated quote
+pub(crate) fn price(
+    quote: &Quote,
+    book: &PriceBook,
+    lead_days: Option<u32>,
+) -> Result<PricedQuote, QuoteError> {
+    let pricing = Pricing::new(book, quote.currency)?;
+    let lead_days = lead_days.unwrap_or(book.rush.below_days);
+    let rush_rate = pricing.rush_rate(quote.is_rush, quote.turnaround_days, lead_days);
+
+    let lines = quote
+        .lines
+        .iter()
+        .map(|line| pricing.line(line, rush_rate))
+        .collect::<Result<Vec<_>, _>>()?;
+    let lines_total = lines.iter().fold(0i64, |sum, line| {
+        sum.saturating_add(line.total_price.amount)
+    });
+    let batch_fees = pricing.batch_fees(&quote.lines, Money::new(lines_total, pricing.currency));
+
+    let total = batch_fees.iter().fold(lines_total, |sum, fee| {
+        sum.saturating_add(fee.amount.amount)
+    });
+    let total = Money::new(total, pricing.currency);
+
+    let mut priced = PricedQuote {
+        lines,
+        batch_fees,
+        total_price: total,
+        vat: pricing.vat(quote.customer.as_ref(), total)?,
+        lead_days: Some(lead_days),
+        rush_rate,
+        currency: pricing.currency,
+        exchange_rate: pricing.exchange_rate,
+        price_book_version: book.version.clone(),
+        breakdown: PriceBreakdown::default(),
+    };
+    priced.breakdown = breakdown::explain(quote, &priced, book);
+
+    Ok(priced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_book::newest_standard_book;
+    use crate::{BatchFees, CoatKind};
+
+    fn line(reference: &str, color: &str, quantity: u32) -> QuoteLine {
+        QuoteLine {
+            reference: Some(reference.to_string()),
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::Clean,
+            color: color.to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            quantity,
+        }
+    }
+
+    fn quote(lines: Vec<QuoteLine>) -> Quote {
+        Quote {
+            lines,
+            turnaround_days: 7,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        }
+    }
+
+    fn book_with_fees() -> PriceBook {
+        PriceBook {
+            batch_fees: BatchFees {
+                setup: 30.0,
+                color_change: 15.0,
+                minimum_order: 0.0,
+            },
+            ..newest_standard_book()
+        }
+    }
+
+    #[test]
+    fn test_single_line_matches_calculate_quote() {
+        let input = QuoteInput {
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::BlastClean,
+            color: "1026".to_string(),
+            finish: Finish::Matte,
+            coats: Vec::new(),
+            turnaround_days: 3,
+            quantity: 2,
+            is_rush: true,
+            currency: None,
+            customer: None,
+        };
+        let book = newest_standard_book();
+
+        let single = crate::calculate_quote(&input, &book).unwrap();
+        let priced = price_quote(&Quote::from(input), &book).unwrap();
+
+        assert_eq!(priced.lines[0].total_price, single.total_price);
+        assert_eq!(priced.lines[0].rush_surcharge, single.rush_surcharge);
+        assert_eq!(priced.amount_due(), single.amount_due());
+    }
+
+    #[test]
+    fn test_batch_fees_charged_once_per_order() {
+        let mut frame = line("frame", "9005", 1);
+        frame.coats.push(Coat {
+            kind: CoatKind::Color,
+            color: Some("3020".to_string()),
+            finish: Finish::Gloss,
+            coverage_percent: 20.0,
+        });
+        let quote = quote(vec![
+            frame,
+            line("wheels", "9005", 4),
+            line("brackets", "9016", 10),
+        ]);
+
+        let priced = price_quote(&quote, &book_with_fees()).unwrap();
+
+        // One setup; 9005 → 3020 → 9016 is two color changes
+        assert_eq!(
+            priced.batch_fees,
+            [
+                BatchFee {
+                    kind: BatchFeeKind::Setup,
+                    count: 1,
+                    amount: Money::new(3000, Currency::Eur),
+                },
+                BatchFee {
+                    kind: BatchFeeKind::ColorChange,
+                    count: 2,
+                    amount: Money::new(3000, Currency::Eur),
+                },
+            ]
+        );
+
+        let lines: i64 = priced.lines.iter().map(|l| l.total_price.amount).sum();
+        assert_eq!(priced.total_price.amount, lines + 6000);
+        assert_eq!(priced.vat.as_ref().unwrap().net, priced.total_price);
+        assert_eq!(priced.coated_area_m2(), 1.9 * 15.0);
+    }
+
+    #[test]
+    fn test_line_errors_name_the_line() {
+        let mut bad = line("bracket", "0000", 0);
+        bad.length_mm = 1.0;
+        let mut quote = quote(vec![line("frame", "9005", 1), bad]);
+        quote.turnaround_days = 0;
+
+        let fields: Vec<String> = match price_quote(&quote, &newest_standard_book()) {
+            Err(QuoteError::Invalid(errors)) => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("expected validation errors, got {:?}", other),
+        };
+        assert_eq!(
+            fields,
+            [
+                "lines[1].length_mm",
+                "lines[1].color",
+                "lines[1].quantity",
+                "turnaround_days"
+            ]
+        );
+
+        let empty = Quote {
+            lines: Vec::new(),
+            ..quote
+        };
+        assert!(price_quote(&empty, &newest_standard_book()).is_err());
+    }
+
+    #[test]
+    fn test_rush_surcharge_follows_shop_lead_time() {
+        let mut quote = quote(vec![line("frame", "9005", 1)]);
+        quote.turnaround_days = 3;
+        quote.is_rush = true;
+        let book = newest_standard_book();
+        let rush = |lead_days| {
+            let priced = price_quote_with_lead_time(&quote, &book, lead_days).unwrap();
+            let line = &priced.lines[0];
+            (
+                priced.rush_rate,
+                line.rush_surcharge.amount * 100 / line.base_price.amount,
+            )
+        };
+
+        // Two days early against the book's 5 days, a week early on a busy shop
+        assert_eq!(rush(None), (0.5, 50));
+        assert_eq!(rush(Some(10)), (1.0, 100));
+        // A quiet shop makes three days the normal turnaround
+        assert_eq!(rush(Some(3)), (0.0, 0));
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Money, QuoteError, RoundingMode};
+
+/// EU member states, by ISO 3166-1 alpha-2 code
+const EU_COUNTRIES: [&str; 27] = [
+    "AT", "BE", "BG", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GR", "HR", "HU", "IE", "IT",
+    "LT", "LU", "LV", "MT", "NL", "PL", "PT", "RO", "SE", "SI", "SK",
+];
+
+/// Who the quote is invoiced to, for VAT
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Customer {
+    /// ISO 3166-1 alpha-2 country code, e.g. "LV"
+    pub country: String,
+    #[serde(default)]
+    pub customer_type: CustomerType,
+    /// EU VAT number (e.g. "LT100001234567"); businesses need it for reverse
+    /// charge, and quotes with a malformed one are refused
+    #[serde(default)]
+    pub vat_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CustomerType {
+    #[default]
+    Consumer,
+    /// VAT-registered business
+    Business,
+}
+
+/// VAT rules of a price book
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VatRules {
+    /// Country the shop is VAT-registered in; quotes without a customer are taxed here
+    pub seller_country: String,
+    /// Standard VAT rate per EU country code, as a fraction (0.21 = 21%)
+    pub rates: BTreeMap<String, f64>,
+}
+
+/// Which VAT regime applies to a quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum VatTreatment {
+    /// VAT charged at the rate of `country`
+    Standard,
+    /// EU business customer in another member state accounts for the VAT
+    ReverseCharge,
+    /// Customer outside the EU, zero-rated
+    Export,
+}
+
+/// Net, VAT and gross amounts of a quote
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VatBreakdown {
+    pub treatment: VatTreatment,
+    /// Customer country the treatment was decided for
+    pub country: String,
+    /// Rate applied to the net amount (0 for reverse charge and exports)
+    pub rate: f64,
+    pub net: Money,
+    pub vat: Money,
+    pub gross: Money,
+    /// Customer VAT number the reverse charge relies on
+    pub vat_id: Option<String>,
+    /// Wording required on the invoice, if any
+    pub note: Option<String>,
+}
+
+impl VatTreatment {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VatTreatment::Standard => "standard",
+            VatTreatment::ReverseCharge => "reverse_charge",
+            VatTreatment::Export => "export",
+        }
+    }
+}
+
+impl VatBreakdown {
+    /// The rate as a percentage for display, e.g. 25.5
+    pub fn rate_percent(&self) -> f64 {
+        (self.rate * 10_000.0).round() / 100.0
+    }
+}
+
+impl VatRules {
+    /// Work out the VAT on `net` for `customer`
+    pub fn breakdown(
+        &self,
+        customer: Option<&Customer>,
+        net: Money,
+        mode: RoundingMode,
+    ) -> Result<VatBreakdown, QuoteError> {
+        let country = customer
+            .map(|c| c.country.trim().to_ascii_uppercase())
+            .unwrap_or_else(|| self.seller_country.clone());
+
+        let business_vat_id = customer
+            .filter(|c| c.customer_type == CustomerType::Business)
+            .and_then(|c| c.vat_id.as_deref())
+            .map(str::trim)
+            .filter(|id| !id.is_empty());
+
+        // A business that gives a VAT number must give a valid one, rather
+        // than be charged VAT it did not expect
+        let business_vat_id = match business_vat_id {
+            Some(raw) if EU_COUNTRIES.contains(&country.as_str()) => {
+                let vat_id = normalize_vat_id(raw);
+                if !is_vat_id_for(&vat_id, &country) {
+                    return Err(QuoteError::InvalidVatId {
+                        vat_id: raw.to_string(),
+                        country,
+                    });
+                }
+                Some(vat_id)
+            }
+            _ => None,
+        };
+
+        let (treatment, rate, vat_id, note) = if !EU_COUNTRIES.contains(&country.as_str()) {
+            (
+                VatTreatment::Export,
+                0.0,
+                None,
+                Some("Export outside the EU, VAT zero-rated".to_string()),
+            )
+        } else if let Some(vat_id) = business_vat_id.filter(|_| country != self.seller_country) {
+            (
+                VatTreatment::ReverseCharge,
+                0.0,
+                Some(vat_id),
+                Some("Reverse charge: VAT to be accounted for by the recipient".to_string()),
+            )
+        } else {
+            let rate = *self
+                .rates
+                .get(&country)
+                .ok_or_else(|| QuoteError::UnsupportedCountry {
+                    country: country.clone(),
+                })?;
+            (VatTreatment::Standard, rate, None, None)
+        };
+
+        let vat = Money::new(mode.round(net.amount as f64 * rate), net.currency);
+        let gross = Money::new(net.amount.saturating_add(vat.amount), net.currency);
+
+        Ok(VatBreakdown {
+            treatment,
+            country,
+            rate,
+            net,
+            vat,
+            gross,
+            vat_id,
+            note,
+        })
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.rates.contains_key(&self.seller_country) {
+            return Err(format!(
+                "vat.rates has no rate for seller country {}",
+                self.seller_country
+            ));
+        }
+
+        for (country, rate) in &self.rates {
+            if !EU_COUNTRIES.contains(&country.as_str()) {
+                return Err(format!("vat.rates.{} is not an EU country code", country));
+            }
+            if !rate.is_finite() || !(0.0..1.0).contains(rate) {
+                return Err(format!("vat.rates.{} must be a fraction below 1", country));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn normalize_vat_id(vat_id: &str) -> String {
+    vat_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Format check only: the country prefix and 2-12 characters after it
+fn is_vat_id_for(vat_id: &str, country: &str) -> bool {
+    // Greece uses EL rather than its ISO code
+    let prefix = if country == "GR" { "EL" } else { country };
+
+    vat_id
+        .strip_prefix(prefix)
+        .is_some_and(|rest| (2..=12).contains(&rest.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Currency;
+
+    fn rules() -> VatRules {
+        VatRules {
+            seller_country: "LV".to_string(),
+            rates: BTreeMap::from([
+                ("LV".to_string(), 0.21),
+                ("EE".to_string(), 0.24),
+                ("LT".to_string(), 0.21),
+            ]),
+        }
+    }
+
+    fn customer(country: &str, customer_type: CustomerType, vat_id: Option<&str>) -> Customer {
+        Customer {
+            country: country.to_string(),
+            customer_type,
+            vat_id: vat_id.map(str::to_string),
+        }
+    }
+
+    fn net() -> Money {
+        Money::new(10_000, Currency::Eur)
+    }
+
+    #[test]
+    fn test_domestic_and_default_customers_pay_seller_rate() {
+        let default = rules()
+            .breakdown(None, net(), RoundingMode::HalfUp)
+            .unwrap();
+        assert_eq!(default.treatment, VatTreatment::Standard);
+        assert_eq!(default.vat.amount, 2_100);
+        assert_eq!(default.gross.amount, 12_100);
+
+        // A Latvian business pays Latvian VAT even with a VAT number
+        let business = customer("lv", CustomerType::Business, Some("LV40003000000"));
+        let domestic = rules()
+            .breakdown(Some(&business), net(), RoundingMode::HalfUp)
+            .unwrap();
+        assert_eq!(domestic.vat.amount, 2_100);
+    }
+
+    #[test]
+    fn test_eu_consumer_pays_destination_rate() {
+        let consumer = customer("EE", CustomerType::Consumer, None);
+        let vat = rules()
+            .breakdown(Some(&consumer), net(), RoundingMode::HalfUp)
+            .unwrap();
+        assert_eq!(vat.country, "EE");
+        assert_eq!(vat.vat.amount, 2_400);
+    }
+
+    #[test]
+    fn test_eu_business_reverse_charge() {
+        let business = customer("LT", CustomerType::Business, Some("lt 100001234567"));
+        let vat = rules()
+            .breakdown(Some(&business), net(), RoundingMode::HalfUp)
+            .unwrap();
+
+        assert_eq!(vat.treatment, VatTreatment::ReverseCharge);
+        assert_eq!(vat.vat.amount, 0);
+        assert_eq!(vat.gross, net());
+        assert_eq!(vat.vat_id.as_deref(), Some("LT100001234567"));
+        assert!(vat.note.is_some());
+
+        let wrong_country = customer("LT", CustomerType::Business, Some("EE100001234"));
+        assert!(matches!(
+            rules().breakdown(Some(&wrong_country), net(), RoundingMode::HalfUp),
+            Err(QuoteError::InvalidVatId { .. })
+        ));
+
+        // Malformed numbers are refused rather than charged VAT, at home too
+        for (country, vat_id) in [("LT", "---"), ("LT", "LT1"), ("LV", "40003")] {
+            let malformed = customer(country, CustomerType::Business, Some(vat_id));
+            assert!(matches!(
+                rules().breakdown(Some(&malformed), net(), RoundingMode::HalfUp),
+                Err(QuoteError::InvalidVatId { vat_id: ref id, .. }) if id == vat_id
+            ));
+        }
+
+        let blank = customer("LT", CustomerType::Business, Some("  "));
+        let vat = rules()
+            .breakdown(Some(&blank), net(), RoundingMode::HalfUp)
+            .unwrap();
+        assert_eq!(vat.treatment, VatTreatment::Standard);
+    }
+
+    #[test]
+    fn test_export_and_unknown_countries() {
+        let export = customer("NO", CustomerType::Consumer, None);
+        let vat = rules()
+            .breakdown(Some(&export), net(), RoundingMode::HalfUp)
+            .unwrap();
+        assert_eq!(vat.treatment, VatTreatment::Export);
+        assert_eq!(vat.gross, net());
+
+        let missing_rate = customer("FR", CustomerType::Consumer, None);
+        assert!(matches!(
+            rules().breakdown(Some(&missing_rate), net(), RoundingMode::HalfUp),
+            Err(QuoteError::UnsupportedCountry { .. })
+        ));
+    }
+}
@@ -0,0 +1,345 @@
+//! Input limits, kept in line with the frontend's zod schema (`quote-schema.ts`)
+
+use crate::coats::validate_coats;
+use crate::{
+    ral_color, Customer, FieldError, PartShape, Quote, QuoteError, QuoteInput, QuoteLine,
+    MAX_QUOTE_LINES,
+};
+
+pub const MIN_DIMENSION_MM: f64 = 10.0;
+pub const MAX_DIMENSION_MM: f64 = 5000.0;
+/// Coated area of a single part: the surface of a box at the largest dimensions
+pub const MAX_AREA_M2: f64 = 6.0 * (MAX_DIMENSION_MM / 1000.0) * (MAX_DIMENSION_MM / 1000.0);
+pub const MIN_TURNAROUND_DAYS: u32 = 1;
+pub const MAX_TURNAROUND_DAYS: u32 = 30;
+pub const MIN_QUANTITY: u32 = 1;
+pub const MAX_QUANTITY: u32 = 10_000;
+
+impl QuoteInput {
+    /// Check every field, reporting all problems at once
+    pub fn validate(&self) -> Result<(), QuoteError> {
+        let mut errors = Vec::new();
+        self.line().validate_fields("", &mut errors);
+        validate_order(self.turnaround_days, self.customer.as_ref(), &mut errors);
+        into_result(errors)
+    }
+}
+
+impl Quote {
+    /// Check every line and order field, reporting all problems at once
+    pub fn validate(&self) -> Result<(), QuoteError> {
+        let mut errors = Vec::new();
+
+        if self.lines.is_empty() {
+            errors.push(FieldError::new("lines", "a quote needs at least one part"));
+        } else if self.lines.len() > MAX_QUOTE_LINES {
+            errors.push(FieldError::new(
+                "lines",
+                format!(
+                    "at most {} parts per quote; contact us for larger orders",
+                    MAX_QUOTE_LINES
+                ),
+            ));
+        }
+
+        for (i, line) in self.lines.iter().enumerate() {
+            line.validate_fields(&format!("lines[{}].", i), &mut errors);
+        }
+        validate_order(self.turnaround_days, self.customer.as_ref(), &mut errors);
+        into_result(errors)
+    }
+}
+
+impl QuoteLine {
+    /// Check the part's fields, naming them with `prefix` (e.g. `"lines[2]."`)
+    fn validate_fields(&self, prefix: &str, errors: &mut Vec<FieldError>) {
+        let field = |name: &str| format!("{}{}", prefix, name);
+
+        let dimensions = [
+            ("length_mm", self.length_mm),
+            ("width_mm", self.width_mm),
+            ("height_mm", self.height_mm),
+        ];
+        for (name, value) in dimensions {
+            let min = self.min_dimension_mm(name);
+            if !value.is_finite() || value < min || value > MAX_DIMENSION_MM {
+                let message = if min >= MIN_DIMENSION_MM {
+                    format!("must be between {} and {} mm", min, MAX_DIMENSION_MM)
+                } else if min > 0.0 {
+                    format!("must be above 0 and at most {} mm", MAX_DIMENSION_MM)
+                } else {
+                    format!("must be between 0 and {} mm", MAX_DIMENSION_MM)
+                };
+                errors.push(FieldError::new(&field(name), message));
+            }
+        }
+
+        match self.shape {
+            PartShape::WireMesh { open_area_percent }
+                if !(0.0..100.0).contains(&open_area_percent) =>
+            {
+                errors.push(FieldError::new(
+                    &field("shape.open_area_percent"),
+                    "must be at least 0 and below 100",
+                ));
+            }
+            PartShape::ExplicitArea { area_m2 }
+                if !area_m2.is_finite() || area_m2 <= 0.0 || area_m2 > MAX_AREA_M2 =>
+            {
+                errors.push(FieldError::new(
+                    &field("shape.area_m2"),
+                    format!("must be above 0 and at most {} m²", MAX_AREA_M2),
+                ));
+            }
+            _ => {}
+        }
+
+        if !is_ral_code(&self.color) {
+            errors.push(FieldError::new(
+                &field("color"),
+                "RAL code must be 4 digits (e.g., 9005)",
+            ));
+        } else if ral_color(&self.color).is_none() {
+            errors.push(FieldError::new(
+                &field("color"),
+                format!("RAL {} is not in the RAL Classic catalog", self.color),
+            ));
+        }
+
+        if !(MIN_QUANTITY..=MAX_QUANTITY).contains(&self.quantity) {
+            errors.push(FieldError::new(
+                &field("quantity"),
+                format!(
+                    "must be between {} and {}; contact us for larger orders",
+                    MIN_QUANTITY, MAX_QUANTITY
+                ),
+            ));
+        }
+
+        validate_coats(self, prefix, errors);
+    }
+
+    /// Smallest accepted value for a dimension
+    ///
+    /// Panel thickness may be below the handling minimum, and for a measured
+    /// area the dimensions are only a bounding box (a flat mesh has no height).
+    fn min_dimension_mm(&self, field: &str) -> f64 {
+        match (&self.shape, field) {
+            (PartShape::ExplicitArea { .. }, _) => 0.0,
+            (PartShape::FlatPanel, "height_mm") => f64::MIN_POSITIVE,
+            _ => MIN_DIMENSION_MM,
+        }
+    }
+}
+
+/// Fields that apply to the whole order rather than one part
+fn validate_order(turnaround_days: u32, customer: Option<&Customer>, errors: &mut Vec<FieldError>) {
+    if !(MIN_TURNAROUND_DAYS..=MAX_TURNAROUND_DAYS).contains(&turnaround_days) {
+        errors.push(FieldError::new(
+            "turnaround_days",
+            format!(
+                "must be between {} and {} days",
+                MIN_TURNAROUND_DAYS, MAX_TURNAROUND_DAYS
+            ),
+        ));
+    }
+
+    if let Some(customer) = customer {
+        let country = customer.country.trim();
+        if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+            errors.push(FieldError::new(
+                "customer.country",
+                "must be a two-letter country code (e.g., LV)",
+            ));
+        }
+    }
+}
+
+fn into_result(errors: Vec<FieldError>) -> Result<(), QuoteError> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(QuoteError::Invalid(errors))
+    }
+}
+
+fn is_ral_code(color: &str) -> bool {
+    color.len() == 4 && color.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Coat, CoatKind, Customer, CustomerType, Finish, Material, PrepLevel};
+
+    use super::*;
+
+    fn valid() -> QuoteInput {
+        QuoteInput {
+            length_mm: 1000.0,
+            width_mm: 500.0,
+            height_mm: 300.0,
+            shape: PartShape::Box,
+            material: Material::Steel,
+            prep_level: PrepLevel::Clean,
+            color: "9005".to_string(),
+            finish: Finish::Gloss,
+            coats: Vec::new(),
+            turnaround_days: 7,
+            quantity: 1,
+            is_rush: false,
+            currency: None,
+            customer: None,
+        }
+    }
+
+    fn fields(input: &QuoteInput) -> Vec<String> {
+        match input.validate() {
+            Err(QuoteError::Invalid(errors)) => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_valid_input_passes() {
+        assert_eq!(valid().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_all_field_errors_reported() {
+        let input = QuoteInput {
+            length_mm: -5.0,
+            width_mm: f64::NAN,
+            height_mm: 6000.0,
+            color: "RAL9005".to_string(),
+            turnaround_days: 0,
+            quantity: 10_001,
+            customer: Some(Customer {
+                country: "Latvia".to_string(),
+                customer_type: CustomerType::Consumer,
+                vat_id: None,
+            }),
+            ..valid()
+        };
+
+        assert_eq!(
+            fields(&input),
+            [
+                "length_mm",
+                "width_mm",
+                "height_mm",
+                "color",
+                "quantity",
+                "turnaround_days",
+                "customer.country"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_limits_match_frontend_schema() {
+        let edge = QuoteInput {
+            length_mm: 10.0,
+            width_mm: 5000.0,
+            turnaround_days: 30,
+            quantity: 10_000,
+            ..valid()
+        };
+        assert_eq!(edge.validate(), Ok(()));
+
+        let too_small = QuoteInput {
+            length_mm: 9.9,
+            ..valid()
+        };
+        assert_eq!(fields(&too_small), ["length_mm"]);
+
+        let unknown_color = QuoteInput {
+            color: "9999".to_string(),
+            ..valid()
+        };
+        assert_eq!(fields(&unknown_color), ["color"]);
+    }
+
+    #[test]
+    fn test_shape_specific_limits() {
+        let panel = QuoteInput {
+            height_mm: 2.0,
+            shape: PartShape::FlatPanel,
+            ..valid()
+        };
+        assert_eq!(panel.validate(), Ok(()));
+
+        let mesh = QuoteInput {
+            height_mm: 0.0,
+            shape: PartShape::ExplicitArea { area_m2: 0.0 },
+            ..valid()
+        };
+        assert_eq!(fields(&mesh), ["shape.area_m2"]);
+
+        let enormous = QuoteInput {
+            shape: PartShape::ExplicitArea { area_m2: 1e12 },
+            ..mesh.clone()
+        };
+        assert_eq!(fields(&enormous), ["shape.area_m2"]);
+        let largest = QuoteInput {
+            shape: PartShape::ExplicitArea {
+                area_m2: MAX_AREA_M2,
+            },
+            ..mesh
+        };
+        assert_eq!(largest.validate(), Ok(()));
+
+        let grating = QuoteInput {
+            shape: PartShape::WireMesh {
+                open_area_percent: 100.0,
+            },
+            ..valid()
+        };
+        assert_eq!(fields(&grating), ["shape.open_area_percent"]);
+    }
+
+    #[test]
+    fn test_coat_stack_limits() {
+        let coat = |kind: CoatKind, color: Option<&str>| Coat {
+            kind,
+            color: color.map(str::to_string),
+            finish: Finish::Gloss,
+            coverage_percent: 100.0,
+        };
+
+        let two_tone = QuoteInput {
+            coats: vec![
+                coat(CoatKind::EpoxyPrimer, None),
+                Coat {
+                    coverage_percent: 40.0,
+                    ..coat(CoatKind::Color, Some("5010"))
+                },
+                coat(CoatKind::Clear, None),
+            ],
+            ..valid()
+        };
+        assert_eq!(two_tone.validate(), Ok(()));
+
+        let invalid = QuoteInput {
+            coats: vec![
+                coat(CoatKind::ZincPrimer, None),
+                coat(CoatKind::EpoxyPrimer, None),
+                Coat {
+                    coverage_percent: 0.0,
+                    ..coat(CoatKind::Color, None)
+                },
+                coat(CoatKind::Clear, Some("9005")),
+            ],
+            ..valid()
+        };
+        assert_eq!(
+            fields(&invalid),
+            [
+                "coats",
+                "coats",
+                "coats[2].coverage_percent",
+                "coats[2].color",
+                "coats[3].color"
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+-- Quotes priced on the server by quote_core
+CREATE TABLE quotes (
+    id UUID PRIMARY KEY,
+    input JSONB NOT NULL,
+    output JSONB NOT NULL,
+    total_amount BIGINT NOT NULL,
+    currency TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Orders created when a quote is taken to checkout
+CREATE TABLE orders (
+    id UUID PRIMARY KEY,
+    quote_id UUID NOT NULL REFERENCES quotes (id),
+    status TEXT NOT NULL,
+    customer_email TEXT,
+    total_amount BIGINT NOT NULL,
+    currency TEXT NOT NULL,
+    stripe_checkout_session_id TEXT UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX orders_quote_id_idx ON orders (quote_id);
+
+-- Priced lines of an order, mirroring the checkout line items
+CREATE TABLE order_items (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    quantity INTEGER NOT NULL,
+    unit_amount BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX order_items_order_id_idx ON order_items (order_id);
+
+-- Payment attempts against an order
+CREATE TABLE payments (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders (id),
+    provider TEXT NOT NULL,
+    provider_reference TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    currency TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (provider, provider_reference)
+);
+
+CREATE INDEX payments_order_id_idx ON payments (order_id);
+
+-- Verified webhook deliveries, keyed by the provider's event ID
+CREATE TABLE webhook_events (
+    id TEXT PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    processed_at TIMESTAMPTZ
+);
@@ -0,0 +1,4 @@
+-- Track redeliveries and admin replays of webhook events
+ALTER TABLE webhook_events
+    ADD COLUMN delivery_count INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN replayed_at TIMESTAMPTZ;
@@ -0,0 +1,22 @@
+-- Quote outputs now store every amount as integer minor units with its
+-- currency: {"amount": 4275, "currency": "EUR"} instead of 42.75.
+-- The stored total stays authoritative; the base line absorbs any rounding
+-- difference so the lines add up to it, matching the per-total rounding rule.
+UPDATE quotes
+SET output = output || jsonb_build_object(
+        'base_price', jsonb_build_object(
+            'amount', total_amount
+                - round((output->>'prep_surcharge')::numeric * 100)
+                - round((output->>'rush_surcharge')::numeric * 100),
+            'currency', currency),
+        'prep_surcharge', jsonb_build_object(
+            'amount', round((output->>'prep_surcharge')::numeric * 100),
+            'currency', currency),
+        'rush_surcharge', jsonb_build_object(
+            'amount', round((output->>'rush_surcharge')::numeric * 100),
+            'currency', currency),
+        'total_price', jsonb_build_object(
+            'amount', total_amount,
+            'currency', currency)
+    )
+WHERE jsonb_typeof(output->'total_price') = 'number';
@@ -0,0 +1,6 @@
+-- Quotes now carry a color surcharge line; older quotes had none
+UPDATE quotes
+SET output = output || jsonb_build_object(
+        'color_surcharge', jsonb_build_object('amount', 0, 'currency', output->'total_price'->>'currency')
+    )
+WHERE NOT output ? 'color_surcharge';
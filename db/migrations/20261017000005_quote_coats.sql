@@ -0,0 +1,7 @@
+-- Quotes now itemize additional coats; older quotes had none
+UPDATE quotes
+SET output = output || jsonb_build_object(
+        'coats_surcharge', jsonb_build_object('amount', 0, 'currency', output->'total_price'->>'currency'),
+        'coats', '[]'::jsonb
+    )
+WHERE NOT output ? 'coats_surcharge';
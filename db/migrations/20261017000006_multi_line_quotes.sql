@@ -0,0 +1,27 @@
+-- Quotes now hold several parts: the old single part becomes the only line,
+-- and its priced amounts the only priced line
+UPDATE quotes
+SET input = jsonb_build_object(
+        'lines', jsonb_build_array(input - 'turnaround_days' - 'is_rush' - 'currency' - 'customer'),
+        'turnaround_days', input->'turnaround_days',
+        'is_rush', input->'is_rush',
+        'currency', input->'currency',
+        'customer', input->'customer'
+    ),
+    output = jsonb_build_object(
+        'lines', jsonb_build_array(
+            jsonb_build_object(
+                'reference', NULL,
+                'area_per_part_m2', 0,
+                'coated_area_m2', 0,
+                'coats', '[]'::jsonb
+            ) || (output - 'vat' - 'currency' - 'exchange_rate' - 'price_book_version')
+        ),
+        'batch_fees', '[]'::jsonb,
+        'total_price', output->'total_price',
+        'vat', output->'vat',
+        'currency', output->'currency',
+        'exchange_rate', COALESCE(output->'exchange_rate', '1'::jsonb),
+        'price_book_version', COALESCE(output->'price_book_version', '""'::jsonb)
+    )
+WHERE NOT input ? 'lines';
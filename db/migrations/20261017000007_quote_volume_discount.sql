@@ -0,0 +1,16 @@
+-- Quote lines now carry a volume discount; older lines had none
+UPDATE quotes
+SET output = jsonb_set(
+        output,
+        '{lines}',
+        (
+            SELECT jsonb_agg(
+                line || jsonb_build_object(
+                    'volume_discount', jsonb_build_object('amount', 0, 'currency', output->>'currency')
+                )
+                ORDER BY ordinality
+            )
+            FROM jsonb_array_elements(output->'lines') WITH ORDINALITY AS lines (line, ordinality)
+        )
+    )
+WHERE NOT output->'lines'->0 ? 'volume_discount';
@@ -0,0 +1,11 @@
+-- Oven time reserved for paid orders, one row per order and day
+CREATE TABLE oven_bookings (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    hours DOUBLE PRECISION NOT NULL CHECK (hours > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (order_id, day)
+);
+
+CREATE INDEX oven_bookings_day_idx ON oven_bookings (day);
@@ -0,0 +1,27 @@
+-- Customer accounts; emails are stored lowercased
+CREATE TABLE customers (
+    id UUID PRIMARY KEY,
+    email TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    name TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Issued refresh tokens, keyed by the token's `jti`; a token is usable once
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY,
+    customer_id UUID NOT NULL REFERENCES customers (id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX refresh_tokens_customer_id_idx ON refresh_tokens (customer_id);
+
+-- Quotes and orders made while signed in; anonymous ones have no customer
+ALTER TABLE quotes ADD COLUMN customer_id UUID REFERENCES customers (id);
+ALTER TABLE orders ADD COLUMN customer_id UUID REFERENCES customers (id);
+
+CREATE INDEX quotes_customer_id_idx ON quotes (customer_id);
+CREATE INDEX orders_customer_id_idx ON orders (customer_id);
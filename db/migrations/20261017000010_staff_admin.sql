@@ -0,0 +1,36 @@
+-- Shop staff signing in to the admin API; emails are stored lowercased
+CREATE TABLE staff (
+    id UUID PRIMARY KEY,
+    email TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    name TEXT,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'sales', 'operator')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Every order status change, by staff or by payment webhooks (no staff)
+CREATE TABLE order_status_changes (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    from_status TEXT NOT NULL,
+    to_status TEXT NOT NULL,
+    staff_id UUID REFERENCES staff (id),
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX order_status_changes_order_id_idx ON order_status_changes (order_id);
+
+-- Prices agreed by staff in place of the quoted price; the latest one applies
+CREATE TABLE price_overrides (
+    id UUID PRIMARY KEY,
+    quote_id UUID NOT NULL REFERENCES quotes (id) ON DELETE CASCADE,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    currency TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    staff_id UUID NOT NULL REFERENCES staff (id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX price_overrides_quote_id_idx ON price_overrides (quote_id, created_at);
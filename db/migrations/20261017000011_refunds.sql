@@ -0,0 +1,30 @@
+-- What the provider actually captured for a payment, and how much of it has
+-- been refunded. `payment_intent_id` is what refunds are issued against.
+ALTER TABLE payments
+    ADD COLUMN payment_intent_id TEXT,
+    ADD COLUMN captured_amount BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN refunded_amount BIGINT NOT NULL DEFAULT 0,
+    ADD CONSTRAINT payments_refund_within_capture CHECK (refunded_amount <= captured_amount);
+
+CREATE INDEX payments_payment_intent_id_idx ON payments (payment_intent_id);
+
+-- Refunds of a payment, whether issued by staff here or in the provider's dashboard
+CREATE TABLE refunds (
+    id UUID PRIMARY KEY,
+    payment_id UUID NOT NULL REFERENCES payments (id),
+    order_id UUID NOT NULL REFERENCES orders (id),
+    provider_refund_id TEXT UNIQUE,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    currency TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL,
+    staff_id UUID REFERENCES staff (id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX refunds_payment_id_idx ON refunds (payment_id);
+CREATE INDEX refunds_order_id_idx ON refunds (order_id);
+
+-- Payments settled before captures were tracked captured their full amount
+UPDATE payments SET captured_amount = amount WHERE status = 'succeeded';
@@ -0,0 +1,8 @@
+-- Large orders pay a deposit at checkout and the balance at pickup; orders
+-- paid in full have no deposit
+ALTER TABLE orders ADD COLUMN deposit_amount BIGINT CHECK (deposit_amount > 0);
+
+-- Which part of the order a payment covers, and where the customer pays it
+ALTER TABLE payments
+    ADD COLUMN kind TEXT NOT NULL DEFAULT 'full',
+    ADD COLUMN checkout_url TEXT;
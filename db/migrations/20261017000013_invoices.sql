@@ -0,0 +1,25 @@
+-- Business orders paid by bank transfer against an invoice, instead of at
+-- checkout. Numbers run on across years: INV-2026-000042.
+CREATE SEQUENCE invoice_number_seq;
+
+CREATE TABLE invoices (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL UNIQUE REFERENCES orders (id),
+    payment_id UUID NOT NULL REFERENCES payments (id),
+    number TEXT NOT NULL UNIQUE,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    currency TEXT NOT NULL,
+    customer_email TEXT NOT NULL,
+    -- The account the transfer goes to, as printed on the invoice
+    account_holder TEXT NOT NULL,
+    iban TEXT NOT NULL,
+    bic TEXT NOT NULL,
+    issued_on DATE NOT NULL,
+    due_on DATE NOT NULL CHECK (due_on >= issued_on),
+    -- When the transfer arrived, and who recorded it
+    paid_on DATE,
+    received_by UUID REFERENCES staff (id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX invoices_unpaid_due_on_idx ON invoices (due_on) WHERE paid_on IS NULL;